@@ -1,3 +1,7 @@
 mod dip721;
+mod http;
+mod icrc2;
 
 pub use dip721::Dip721Client;
+pub use http::HttpClient;
+pub use icrc2::Icrc2Client;
@@ -4,62 +4,777 @@ use dip721_rs::{
     TxEvent,
 };
 
+use serde_bytes::ByteBuf;
+
 use crate::actor::alice;
+use crate::did::{
+    Account, AllowlistPhase, AssetMetadata, Auction, AuctionKind, CreateCollectionArgs,
+    ExtCommonError, ExtResult, ExtTokensResult, ExtTransferError, ExtTransferRequest, Listing,
+    MarketplaceSettings, MintSale, MintVoucher, Offer, ProposeSwapArgs, Royalty, RoyaltyPayment,
+    Swap, SwapPayment, TransferBatch,
+};
 use crate::TestEnv;
 
-pub struct Dip721Client<'a> {
-    pub env: &'a TestEnv,
-}
+pub struct Dip721Client<'a> {
+    pub env: &'a TestEnv,
+}
+
+impl<'a> Dip721Client<'a> {
+    pub fn new(env: &'a TestEnv) -> Self {
+        Self { env }
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "dip721_metadata",
+                Encode!(&()).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "dip721_stats",
+                Encode!(&()).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn logo(&self) -> Option<String> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "dip721_logo",
+                Encode!(&()).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn set_logo(&self, caller: Principal, logo: String) {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "dip721_set_logo",
+                Encode!(&logo).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_binary_logo(&self, caller: Principal, content_type: &str, data: ByteBuf) {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_logo",
+                Encode!(&content_type, &data).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_collection_royalty(
+        &self,
+        caller: Principal,
+        royalty: Option<Royalty>,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_collection_royalty",
+                Encode!(&royalty).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn royalty_info(
+        &self,
+        token_identifier: TokenIdentifier,
+        sale_price: Nat,
+    ) -> Result<Vec<RoyaltyPayment>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "royalty_info",
+                Encode!(&token_identifier, &sale_price).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn set_marketplace_settings(
+        &self,
+        caller: Principal,
+        settings: MarketplaceSettings,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_marketplace_settings",
+                Encode!(&settings).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn list_token(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+        price: Nat,
+        ledger: Principal,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "list_token",
+                Encode!(&token_identifier, &price, &ledger).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_listing(&self, token_identifier: TokenIdentifier) -> Option<Listing> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_listing",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn buy(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "buy",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn create_auction(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+        ledger: Principal,
+        kind: AuctionKind,
+        ends_at: u64,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "create_auction",
+                Encode!(&token_identifier, &ledger, &kind, &ends_at).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn bid(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+        amount: Nat,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "bid",
+                Encode!(&token_identifier, &amount).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_auction(&self, token_identifier: TokenIdentifier) -> Option<Auction> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_auction",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn make_offer(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+        amount: Nat,
+        expires_at: u64,
+        ledger: Principal,
+    ) -> Result<u64, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "make_offer",
+                Encode!(&token_identifier, &amount, &expires_at, &ledger).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn accept_offer(&self, caller: Principal, id: u64) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "accept_offer",
+                Encode!(&id).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_offer(&self, id: u64) -> Option<Offer> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_offer",
+                Encode!(&id).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn propose_swap(&self, caller: Principal, args: ProposeSwapArgs) -> Result<u64, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "propose_swap",
+                Encode!(&args).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn cancel_swap(&self, caller: Principal, id: u64) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "cancel_swap",
+                Encode!(&id).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn accept_swap(
+        &self,
+        caller: Principal,
+        id: u64,
+        offered: Vec<TokenIdentifier>,
+        payment: Option<SwapPayment>,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "accept_swap",
+                Encode!(&id, &offered, &payment).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_swap(&self, id: u64) -> Option<Swap> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_swap",
+                Encode!(&id).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn set_mint_sale(&self, caller: Principal, sale: Option<MintSale>) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_mint_sale",
+                Encode!(&sale).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn create_collection(
+        &self,
+        caller: Principal,
+        args: CreateCollectionArgs,
+    ) -> Result<u64, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "create_collection",
+                Encode!(&args).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn collection_mint(
+        &self,
+        caller: Principal,
+        collection_id: u64,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "collection_mint",
+                Encode!(
+                    &collection_id,
+                    &to,
+                    &token_identifier,
+                    &Vec::<(String, GenericValue)>::new()
+                )
+                .unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn collection_owner_of(
+        &self,
+        collection_id: u64,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Principal>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "collection_owner_of",
+                Encode!(&collection_id, &token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn collection_total_supply(&self, collection_id: u64) -> Result<Nat, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "collection_total_supply",
+                Encode!(&collection_id).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn attach_child(
+        &self,
+        caller: Principal,
+        parent: TokenIdentifier,
+        child: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "attach_child",
+                Encode!(&parent, &child).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn detach_child(
+        &self,
+        caller: Principal,
+        parent: TokenIdentifier,
+        child: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "detach_child",
+                Encode!(&parent, &child).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_token_children(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_token_children",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn root_owner_of(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Principal>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "root_owner_of",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn ext_token_identifier_of(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<String, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "ext_token_identifier_of",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn bearer(&self, token: String) -> ExtResult<String, ExtCommonError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "bearer",
+                Encode!(&token).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn get_registry(&self) -> Vec<(u32, String)> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "getRegistry",
+                Encode!(&()).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn tokens_ext(&self, account_identifier: String) -> ExtTokensResult {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "tokens_ext",
+                Encode!(&account_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    pub fn ext_transfer(
+        &self,
+        caller: Principal,
+        request: ExtTransferRequest,
+    ) -> ExtResult<Nat, ExtTransferError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "transfer",
+                Encode!(&request).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn account_balance_of(&self, account: Account) -> Result<Nat, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "account_balance_of",
+                Encode!(&account).unwrap(),
+            )
+            .expect("query failed")
+    }
 
-impl<'a> Dip721Client<'a> {
-    pub fn new(env: &'a TestEnv) -> Self {
-        Self { env }
+    pub fn account_token_identifiers(
+        &self,
+        account: Account,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "account_token_identifiers",
+                Encode!(&account).unwrap(),
+            )
+            .expect("query failed")
     }
 
-    pub fn metadata(&self) -> Metadata {
+    pub fn account_of(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Account>, NftError> {
         self.env
             .query(
                 self.env.dip721_id,
                 alice(),
-                "dip721_metadata",
-                Encode!(&()).unwrap(),
+                "account_of",
+                Encode!(&token_identifier).unwrap(),
             )
             .expect("query failed")
     }
 
-    pub fn stats(&self) -> Stats {
+    pub fn account_transfer(
+        &self,
+        caller: Principal,
+        from_subaccount: Option<ByteBuf>,
+        to: Account,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "account_transfer",
+                Encode!(&from_subaccount, &to, &token_identifier).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn account_mint(
+        &self,
+        caller: Principal,
+        to: Account,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "account_mint",
+                Encode!(&to, &token_identifier, &properties).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn create_edition_template(
+        &self,
+        caller: Principal,
+        properties: Vec<(String, GenericValue)>,
+        max_editions: Option<u64>,
+    ) -> Result<u64, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "create_edition_template",
+                Encode!(&properties, &max_editions).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn mint_edition(
+        &self,
+        caller: Principal,
+        to: Principal,
+        template_id: u64,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<TokenIdentifier, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "mint_edition",
+                Encode!(&to, &template_id, &properties).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_edition_count(&self, template_id: u64) -> Result<u64, NftError> {
         self.env
             .query(
                 self.env.dip721_id,
                 alice(),
-                "dip721_stats",
-                Encode!(&()).unwrap(),
+                "get_edition_count",
+                Encode!(&template_id).unwrap(),
             )
             .expect("query failed")
     }
 
-    pub fn logo(&self) -> Option<String> {
+    pub fn public_mint(
+        &self,
+        caller: Principal,
+        quantity: u64,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "public_mint",
+                Encode!(&quantity).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_allowlist_phase(
+        &self,
+        caller: Principal,
+        id: u32,
+        phase: Option<AllowlistPhase>,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_allowlist_phase",
+                Encode!(&id, &phase).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn allowlist_mint(
+        &self,
+        caller: Principal,
+        phase_id: u32,
+        quantity: u64,
+        max_quantity: u64,
+        proof: Vec<ByteBuf>,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "allowlist_mint",
+                Encode!(&phase_id, &quantity, &max_quantity, &proof).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_random_pool(&self, caller: Principal, size: u64) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_random_pool",
+                Encode!(&size).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_sale_minted(&self) -> u64 {
         self.env
             .query(
                 self.env.dip721_id,
                 alice(),
-                "dip721_logo",
+                "get_sale_minted",
                 Encode!(&()).unwrap(),
             )
             .expect("query failed")
     }
 
-    pub fn set_logo(&self, caller: Principal, logo: String) {
+    pub fn set_allowed_origins(&self, caller: Principal, origins: Vec<String>) {
         self.env
             .update(
                 self.env.dip721_id,
                 caller,
-                "dip721_set_logo",
-                Encode!(&logo).unwrap(),
+                "set_allowed_origins",
+                Encode!(&origins).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_http_minters(&self, caller: Principal, minters: Vec<Principal>) {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_http_minters",
+                Encode!(&minters).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_voucher_signers(&self, caller: Principal, signers: Vec<Principal>) {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_voucher_signers",
+                Encode!(&signers).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn redeem_voucher(
+        &self,
+        caller: Principal,
+        voucher: MintVoucher,
+        properties: Vec<(String, GenericValue)>,
+        public_key: ByteBuf,
+        signature: ByteBuf,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "redeem_voucher",
+                Encode!(&voucher, &properties, &public_key, &signature).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn set_provenance_hash(&self, caller: Principal, hash: ByteBuf) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_provenance_hash",
+                Encode!(&hash).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn reveal(
+        &self,
+        caller: Principal,
+        metadata: Vec<Vec<(String, GenericValue)>>,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "reveal",
+                Encode!(&metadata).unwrap(),
             )
             .expect("update failed")
     }
 
+    pub fn verify_reveal(&self) -> Result<bool, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "verify_reveal",
+                Encode!(&()).unwrap(),
+            )
+            .expect("query failed")
+    }
+
     pub fn name(&self) -> Option<String> {
         self.env
             .query(
@@ -173,6 +888,37 @@ impl<'a> Dip721Client<'a> {
             .expect("query failed")
     }
 
+    pub fn set_user(
+        &self,
+        caller: Principal,
+        token_identifier: TokenIdentifier,
+        user: Principal,
+        expires_at: u64,
+    ) -> Result<Nat, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "set_user",
+                Encode!(&token_identifier, &user, &expires_at).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn user_of(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Principal>, NftError> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "user_of",
+                Encode!(&token_identifier).unwrap(),
+            )
+            .expect("query failed")
+    }
+
     pub fn owner_of(
         &self,
         token_identifier: TokenIdentifier,
@@ -320,6 +1066,21 @@ impl<'a> Dip721Client<'a> {
             .expect("query failed")
     }
 
+    pub fn transfer_batch(
+        &self,
+        caller: Principal,
+        transfers: Vec<(Principal, Principal, TokenIdentifier)>,
+    ) -> Result<TransferBatch, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "transfer_batch",
+                Encode!(&transfers).unwrap(),
+            )
+            .expect("update failed")
+    }
+
     pub fn transfer(
         &self,
         caller: Principal,
@@ -406,4 +1167,58 @@ impl<'a> Dip721Client<'a> {
             )
             .expect("query failed")
     }
+
+    pub fn create_asset(
+        &self,
+        caller: Principal,
+        content_type: &str,
+        sha256: ByteBuf,
+    ) -> Result<u64, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "create_asset",
+                Encode!(&content_type, &sha256).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn upload_asset_chunk(
+        &self,
+        caller: Principal,
+        id: u64,
+        chunk: ByteBuf,
+    ) -> Result<(), NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "upload_asset_chunk",
+                Encode!(&id, &chunk).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn finalize_asset(&self, caller: Principal, id: u64) -> Result<AssetMetadata, NftError> {
+        self.env
+            .update(
+                self.env.dip721_id,
+                caller,
+                "finalize_asset",
+                Encode!(&id).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn get_asset(&self, id: u64) -> Option<AssetMetadata> {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "get_asset",
+                Encode!(&id).unwrap(),
+            )
+            .expect("query failed")
+    }
 }
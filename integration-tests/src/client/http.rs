@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use candid::Encode;
+use serde_bytes::ByteBuf;
+
+use crate::actor::alice;
+use crate::did::{HttpRequest, HttpResponse};
+use crate::TestEnv;
+
+pub struct HttpClient<'a> {
+    pub env: &'a TestEnv,
+}
+
+impl<'a> HttpClient<'a> {
+    pub fn new(env: &'a TestEnv) -> Self {
+        Self { env }
+    }
+
+    pub fn get(&self, url: &str, headers: &[(&str, &str)]) -> HttpResponse {
+        self.request("GET", url, headers, &[])
+    }
+
+    /// Sends a POST request, following the upgrade to an update call as the HTTP gateway does
+    pub fn post(&self, url: &str, body: &serde_json::Value) -> HttpResponse {
+        let headers = [("content-type", "application/json")];
+        let body = body.to_string();
+        let response = self.request("POST", url, &headers, body.as_bytes());
+        if response.upgrade != Some(true) {
+            return response;
+        }
+
+        self.env
+            .update(
+                self.env.dip721_id,
+                alice(),
+                "http_request_update",
+                Encode!(&Self::http_request("POST", url, &headers, body.as_bytes())).unwrap(),
+            )
+            .expect("update failed")
+    }
+
+    pub fn json_rpc(&self, body: &serde_json::Value) -> HttpResponse {
+        self.request(
+            "GET",
+            "/",
+            &[("content-type", "application/json")],
+            body.to_string().as_bytes(),
+        )
+    }
+
+    pub fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> HttpResponse {
+        self.env
+            .query(
+                self.env.dip721_id,
+                alice(),
+                "http_request",
+                Encode!(&Self::http_request(method, url, headers, body)).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    fn http_request(method: &str, url: &str, headers: &[(&str, &str)], body: &[u8]) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+            body: ByteBuf::from(body),
+        }
+    }
+}
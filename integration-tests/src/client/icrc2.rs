@@ -0,0 +1,54 @@
+use candid::{Encode, Nat, Principal, Reserved};
+
+use crate::actor::alice;
+use crate::did::{Account, ApproveArgs};
+use crate::TestEnv;
+
+pub struct Icrc2Client<'a> {
+    pub env: &'a TestEnv,
+    pub ledger_id: Principal,
+}
+
+impl<'a> Icrc2Client<'a> {
+    pub fn new(env: &'a TestEnv, ledger_id: Principal) -> Self {
+        Self { env, ledger_id }
+    }
+
+    pub fn balance_of(&self, owner: Principal) -> Nat {
+        self.env
+            .query(
+                self.ledger_id,
+                alice(),
+                "icrc1_balance_of",
+                Encode!(&Account::from(owner)).unwrap(),
+            )
+            .expect("query failed")
+    }
+
+    /// Approve `spender` to spend `amount` of the caller's tokens; the approve error is not decoded
+    pub fn approve(
+        &self,
+        caller: Principal,
+        spender: Principal,
+        amount: Nat,
+    ) -> Result<Nat, Reserved> {
+        let args = ApproveArgs {
+            from_subaccount: None,
+            spender: spender.into(),
+            amount,
+            expected_allowance: None,
+            expires_at: None,
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        };
+        self.env
+            .update(
+                self.ledger_id,
+                caller,
+                "icrc2_approve",
+                Encode!(&args).unwrap(),
+            )
+            .expect("update failed")
+    }
+}
@@ -0,0 +1,269 @@
+//! Canister types not exposed by `dip721-rs`
+
+use std::collections::HashMap;
+
+use candid::{CandidType, Nat, Principal};
+use dip721_rs::SupportedInterface;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AssetMetadata {
+    pub id: u64,
+    pub content_type: String,
+    pub sha256: ByteBuf,
+    pub size: u64,
+    pub chunks: u32,
+    pub finalized: bool,
+    pub created_at: u64,
+    pub created_by: Principal,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: ByteBuf,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: HashMap<String, String>,
+    pub body: ByteBuf,
+    pub upgrade: Option<bool>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Royalty {
+    pub basis_points: u16,
+    pub recipients: Vec<RoyaltyRecipient>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RoyaltyRecipient {
+    pub recipient: Principal,
+    pub share: u16,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RoyaltyPayment {
+    pub recipient: Principal,
+    pub amount: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Listing {
+    pub seller: Principal,
+    pub price: Nat,
+    pub ledger: Principal,
+    pub listed_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct PlatformFee {
+    pub basis_points: u16,
+    pub recipient: Principal,
+}
+
+#[derive(Clone, Debug, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct MarketplaceSettings {
+    pub ledgers: Vec<Principal>,
+    pub platform_fee: Option<PlatformFee>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum AuctionKind {
+    English {
+        reserve_price: Nat,
+        min_increment: Nat,
+        extension: u64,
+    },
+    Dutch {
+        start_price: Nat,
+        end_price: Nat,
+    },
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Bid {
+    pub bidder: Principal,
+    pub amount: Nat,
+    pub placed_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Auction {
+    pub seller: Principal,
+    pub ledger: Principal,
+    pub kind: AuctionKind,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub bids: Vec<Bid>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Offer {
+    pub token_identifier: Nat,
+    pub bidder: Principal,
+    pub ledger: Principal,
+    pub amount: Nat,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SwapPayment {
+    pub ledger: Principal,
+    pub amount: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ProposeSwapArgs {
+    pub counterparty: Principal,
+    pub offered: Vec<Nat>,
+    pub requested: Vec<Nat>,
+    pub payment: Option<SwapPayment>,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Swap {
+    pub proposer: Principal,
+    pub counterparty: Principal,
+    pub offered: Vec<Nat>,
+    pub requested: Vec<Nat>,
+    pub payment: Option<SwapPayment>,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AllowlistPhase {
+    pub merkle_root: ByteBuf,
+    pub price: Nat,
+    pub ledger: Principal,
+    pub treasury: Principal,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct MintSale {
+    pub price: Nat,
+    pub ledger: Principal,
+    pub treasury: Principal,
+    pub wallet_limit: u64,
+    pub allocation: u64,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateCollectionArgs {
+    pub name: String,
+    pub symbol: String,
+    pub logo: Option<String>,
+    pub custodians: Vec<Principal>,
+    pub supported_interfaces: Vec<SupportedInterface>,
+    pub max_supply: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct MintVoucher {
+    pub token_identifier: Nat,
+    pub to: Principal,
+    pub properties_hash: ByteBuf,
+    pub canister_id: Principal,
+    pub expires_at: u64,
+    pub nonce: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct TransferBatch {
+    pub batch_id: Nat,
+    pub tx_ids: Vec<Nat>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ExtResult<T, E> {
+    #[serde(rename = "ok")]
+    Ok(T),
+    #[serde(rename = "err")]
+    Err(E),
+}
+
+pub type ExtTokensResult =
+    ExtResult<Vec<(u32, Option<ExtListing>, Option<ByteBuf>)>, ExtCommonError>;
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ExtCommonError {
+    InvalidToken(String),
+    Other(String),
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ExtUser {
+    #[serde(rename = "address")]
+    Address(String),
+    #[serde(rename = "principal")]
+    Principal(Principal),
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ExtListing {
+    pub locked: Option<candid::Int>,
+    pub price: u64,
+    pub seller: Principal,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ExtTransferRequest {
+    pub from: ExtUser,
+    pub to: ExtUser,
+    pub token: String,
+    pub amount: Nat,
+    pub memo: ByteBuf,
+    pub notify: bool,
+    pub subaccount: Option<ByteBuf>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ExtTransferError {
+    Unauthorized(String),
+    InsufficientBalance,
+    Rejected,
+    InvalidToken(String),
+    CannotNotify(String),
+    Other(String),
+}
+
+/// ICRC-1 account
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<ByteBuf>,
+}
+
+impl From<Principal> for Account {
+    fn from(owner: Principal) -> Self {
+        Self {
+            owner,
+            subaccount: None,
+        }
+    }
+}
+
+/// Arguments of `icrc2_approve`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ApproveArgs {
+    pub from_subaccount: Option<ByteBuf>,
+    pub spender: Account,
+    pub amount: Nat,
+    pub expected_allowance: Option<Nat>,
+    pub expires_at: Option<u64>,
+    pub fee: Option<Nat>,
+    pub memo: Option<ByteBuf>,
+    pub created_at_time: Option<u64>,
+}
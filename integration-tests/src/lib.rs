@@ -4,24 +4,32 @@
 
 pub mod actor;
 pub mod client;
+pub mod did;
 mod wasm;
 
 use std::io::Read as _;
 use std::path::PathBuf;
 use std::vec;
 
-use actor::admin;
-use candid::{CandidType, Decode, Encode, Principal};
+use actor::{admin, alice, bob};
+use candid::{CandidType, Decode, Encode, Nat, Principal};
 use dip721_rs::SupportedInterface;
 use pocket_ic::common::rest::SubnetConfigSet;
 use pocket_ic::{PocketIc, WasmResult};
 use serde::de::DeserializeOwned;
-use wasm::Dip721InitArgs;
+use wasm::{
+    Dip721InitArgs, LedgerArchiveOptions, LedgerArgument, LedgerFeatureFlags, LedgerInitArgs,
+};
 
 use self::wasm::Canister;
 
 const DEFAULT_CYCLES: u128 = 2_000_000_000_000_000;
 
+/// Fee of the ICRC-1 ledger installed by [`TestEnv::install_icrc1_ledger`]
+pub const LEDGER_FEE: u64 = 10_000;
+/// Initial balance of alice and bob on the ICRC-1 ledger
+pub const LEDGER_INITIAL_BALANCE: u64 = 100_000_000_000;
+
 /// Test environment
 pub struct TestEnv {
     pub pic: PocketIc,
@@ -116,6 +124,44 @@ impl TestEnv {
         pic.install_canister(id, wasm_bytes, init_arg, None);
     }
 
+    /// Upgrade the dip721 canister with the same wasm, running its `pre_upgrade` and `post_upgrade` hooks
+    pub fn upgrade_dip721(&self) {
+        let wasm_bytes = Self::load_wasm(Canister::Dip721);
+        self.pic
+            .upgrade_canister(self.dip721_id, wasm_bytes, Encode!(&()).unwrap(), None)
+            .expect("upgrade failed");
+    }
+
+    /// Install an ICRC-1 ledger with ICRC-2 enabled, where alice and bob own [`LEDGER_INITIAL_BALANCE`]
+    pub fn install_icrc1_ledger(&self) -> Principal {
+        let id = self.pic.create_canister();
+        self.pic.add_cycles(id, DEFAULT_CYCLES);
+        let wasm_bytes = Self::load_wasm(Canister::Icrc1Ledger);
+
+        let init_arg = LedgerArgument::Init(LedgerInitArgs {
+            minting_account: admin().into(),
+            transfer_fee: Nat::from(LEDGER_FEE),
+            token_symbol: "TKN".to_string(),
+            token_name: "Token".to_string(),
+            metadata: vec![],
+            initial_balances: vec![
+                (alice().into(), Nat::from(LEDGER_INITIAL_BALANCE)),
+                (bob().into(), Nat::from(LEDGER_INITIAL_BALANCE)),
+            ],
+            feature_flags: Some(LedgerFeatureFlags { icrc2: true }),
+            archive_options: LedgerArchiveOptions {
+                num_blocks_to_archive: 1_000,
+                trigger_threshold: 1_000,
+                controller_id: admin(),
+            },
+        });
+        let init_arg = Encode!(&init_arg).unwrap();
+
+        self.pic.install_canister(id, wasm_bytes, init_arg, None);
+
+        id
+    }
+
     fn load_wasm(canister: Canister) -> Vec<u8> {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push(canister.as_path());
@@ -1,9 +1,11 @@
 use std::path::Path;
 
-use candid::{CandidType, Principal};
+use candid::{CandidType, Nat, Principal};
 use dip721_rs::SupportedInterface;
 use serde::Deserialize;
 
+use crate::did::Account;
+
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct Dip721InitArgs {
     pub custodians: Vec<Principal>,
@@ -13,8 +15,44 @@ pub struct Dip721InitArgs {
     pub logo: Option<String>,
 }
 
+/// Init arguments of the ICRC-1 ledger
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum LedgerArgument {
+    Init(LedgerInitArgs),
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct LedgerInitArgs {
+    pub minting_account: Account,
+    pub transfer_fee: Nat,
+    pub token_symbol: String,
+    pub token_name: String,
+    pub metadata: Vec<(String, LedgerMetadataValue)>,
+    pub initial_balances: Vec<(Account, Nat)>,
+    pub feature_flags: Option<LedgerFeatureFlags>,
+    pub archive_options: LedgerArchiveOptions,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum LedgerMetadataValue {
+    Text(String),
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct LedgerFeatureFlags {
+    pub icrc2: bool,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct LedgerArchiveOptions {
+    pub num_blocks_to_archive: u64,
+    pub trigger_threshold: u64,
+    pub controller_id: Principal,
+}
+
 pub enum Canister {
     Dip721,
+    Icrc1Ledger,
 }
 
 impl Canister {
@@ -23,6 +61,7 @@ impl Canister {
             Canister::Dip721 => {
                 Path::new("../.dfx/local/canisters/dip721-canister/dip721-canister.wasm")
             }
+            Canister::Icrc1Ledger => Path::new("../.artifact/icrc1-ledger.wasm.gz"),
         }
     }
 }
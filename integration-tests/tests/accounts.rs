@@ -0,0 +1,75 @@
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::did::Account;
+use integration_tests::TestEnv;
+use serde_bytes::ByteBuf;
+
+#[test]
+#[serial_test::serial]
+fn test_should_transfer_tokens_between_subaccounts() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let token_identifier = Nat::from(1_u64);
+    let subaccount = ByteBuf::from(vec![1_u8; 32]);
+    let alice_subaccount = Account {
+        owner: alice(),
+        subaccount: Some(subaccount.clone()),
+    };
+    assert!(client
+        .account_mint(
+            alice(),
+            alice_subaccount.clone(),
+            token_identifier.clone(),
+            vec![]
+        )
+        .is_err());
+    assert!(client
+        .account_mint(
+            admin(),
+            alice_subaccount.clone(),
+            token_identifier.clone(),
+            vec![]
+        )
+        .is_ok());
+    assert_eq!(
+        client.account_of(token_identifier.clone()).unwrap(),
+        Some(alice_subaccount.clone())
+    );
+    assert_eq!(
+        client.account_balance_of(alice_subaccount.clone()).unwrap(),
+        Nat::from(1_u64)
+    );
+    // DIP721 methods only operate on the default subaccount
+    assert!(client.balance_of(alice()).is_err());
+    assert!(client
+        .transfer(alice(), bob(), token_identifier.clone())
+        .is_err());
+
+    assert!(client
+        .account_transfer(
+            alice(),
+            None,
+            Account::from(bob()),
+            token_identifier.clone()
+        )
+        .is_err());
+    assert!(client
+        .account_transfer(
+            alice(),
+            Some(subaccount),
+            Account::from(bob()),
+            token_identifier.clone()
+        )
+        .is_ok());
+    assert!(client
+        .account_token_identifiers(alice_subaccount)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        client.owner_of(token_identifier.clone()).unwrap(),
+        Some(bob())
+    );
+    assert!(client.transfer(bob(), alice(), token_identifier).is_ok());
+}
@@ -0,0 +1,75 @@
+use std::time::UNIX_EPOCH;
+
+use candid::{Nat, Principal};
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::AllowlistPhase;
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+use serde_bytes::ByteBuf;
+use sha2::{Digest as _, Sha256};
+
+const PRICE: u64 = 100_000_000;
+
+fn leaf(principal: Principal, max_quantity: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(principal.as_slice());
+    hasher.update(max_quantity.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_mint_from_allowlist_phase() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+    let treasury = Principal::from_slice(&[1; 29]);
+
+    let alice_leaf = leaf(alice(), 2);
+    let bob_leaf = leaf(bob(), 1);
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let phase = AllowlistPhase {
+        merkle_root: ByteBuf::from(node(alice_leaf, bob_leaf).to_vec()),
+        price: PRICE.into(),
+        ledger: ledger_id,
+        treasury,
+        starts_at: now,
+        ends_at: now + 3_600_000_000_000,
+    };
+    assert!(client.set_allowlist_phase(admin(), 1, Some(phase)).is_ok());
+    assert!(ledger
+        .approve(alice(), env.dip721_id, Nat::from(10 * PRICE))
+        .is_ok());
+
+    let proof = vec![ByteBuf::from(bob_leaf.to_vec())];
+    // bob's proof doesn't hold for alice
+    assert!(client
+        .allowlist_mint(alice(), 1, 1, 1, vec![ByteBuf::from(alice_leaf.to_vec())])
+        .is_err());
+    let tokens = client
+        .allowlist_mint(alice(), 1, 2, 2, proof.clone())
+        .unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(client.owner_of(tokens[1].clone()).unwrap(), Some(alice()));
+    assert!(client.allowlist_mint(alice(), 1, 1, 2, proof).is_err());
+    // the canister forwards the payment to the treasury, minus the ledger fee
+    assert_eq!(ledger.balance_of(treasury), 2 * PRICE - LEDGER_FEE);
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE - 2 * PRICE - 2 * LEDGER_FEE
+    );
+}
@@ -0,0 +1,86 @@
+use candid::Encode;
+use dip721_rs::NftError;
+use integration_tests::actor::{admin, alice};
+use integration_tests::client::{Dip721Client, HttpClient};
+use integration_tests::TestEnv;
+use serde_bytes::ByteBuf;
+
+/// sha256 of "hello world"
+const HELLO_WORLD_SHA256: [u8; 32] = [
+    0xb9, 0x4d, 0x27, 0xb9, 0x93, 0x4d, 0x3e, 0x08, 0xa5, 0x2e, 0x52, 0xd7, 0xda, 0x7d, 0xab, 0xfa,
+    0xc4, 0x84, 0xef, 0xe3, 0x7a, 0x53, 0x80, 0xee, 0x90, 0x88, 0xf7, 0xac, 0xe2, 0xef, 0xcd, 0xe9,
+];
+
+#[test]
+#[serial_test::serial]
+fn test_should_upload_and_serve_asset() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    let id = client
+        .create_asset(
+            admin(),
+            "text/plain",
+            ByteBuf::from(HELLO_WORLD_SHA256.to_vec()),
+        )
+        .unwrap();
+    assert!(client
+        .upload_asset_chunk(admin(), id, ByteBuf::from(b"hello ".to_vec()))
+        .is_ok());
+    assert!(client
+        .upload_asset_chunk(admin(), id, ByteBuf::from(b"world".to_vec()))
+        .is_ok());
+    // not served before finalize
+    assert_eq!(http.get(&format!("/assets/{id}"), &[]).status_code, 404);
+
+    let asset = client.finalize_asset(admin(), id).unwrap();
+    assert_eq!(asset.size, 11);
+    assert_eq!(client.get_asset(id), Some(asset));
+
+    let response = http.get(&format!("/assets/{id}"), &[]);
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.body.as_slice(), b"hello world");
+    assert_eq!(
+        response.headers.get("content-type").map(String::as_str),
+        Some("text/plain")
+    );
+
+    let response = http.get(&format!("/assets/{id}"), &[("range", "bytes=0-4")]);
+    assert_eq!(response.status_code, 206);
+    assert_eq!(response.body.as_slice(), b"hello");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_not_allow_asset_upload_by_non_custodian() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    assert!(env
+        .update::<Result<u64, NftError>>(
+            env.dip721_id,
+            alice(),
+            "create_asset",
+            Encode!(&"text/plain", &ByteBuf::from(HELLO_WORLD_SHA256.to_vec())).unwrap(),
+        )
+        .is_err());
+    assert!(client.get_asset(0).is_none());
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_serve_logo() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert_eq!(http.get("/logo", &[]).status_code, 404);
+    client.set_binary_logo(admin(), "image/svg+xml", ByteBuf::from(b"<svg/>".to_vec()));
+
+    let response = http.get("/logo", &[]);
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.body.as_slice(), b"<svg/>");
+    assert_eq!(client.logo().as_deref(), Some("PHN2Zy8+"));
+    assert!(client.metadata().logo.unwrap().ends_with("/logo"));
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::{AuctionKind, MarketplaceSettings};
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+
+const RESERVE_PRICE: u64 = 100_000_000;
+const MIN_INCREMENT: u64 = 10_000_000;
+
+#[test]
+#[serial_test::serial]
+fn test_should_settle_english_auction_on_timer() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: None,
+            },
+        )
+        .is_ok());
+
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    assert!(client
+        .create_auction(
+            alice(),
+            1_u64.into(),
+            ledger_id,
+            AuctionKind::English {
+                reserve_price: RESERVE_PRICE.into(),
+                min_increment: MIN_INCREMENT.into(),
+                extension: 60_000_000_000,
+            },
+            now + 600_000_000_000,
+        )
+        .is_ok());
+    // token is locked
+    assert!(client.transfer(alice(), bob(), 1_u64.into()).is_err());
+
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(10 * RESERVE_PRICE))
+        .is_ok());
+    assert!(client
+        .bid(bob(), 1_u64.into(), (RESERVE_PRICE - 1).into())
+        .is_err());
+    assert!(client
+        .bid(bob(), 1_u64.into(), RESERVE_PRICE.into())
+        .is_ok());
+    assert!(client
+        .bid(bob(), 1_u64.into(), (RESERVE_PRICE + MIN_INCREMENT).into())
+        .is_ok());
+    assert_eq!(client.get_auction(1_u64.into()).unwrap().bids.len(), 2);
+
+    env.pic.advance_time(Duration::from_secs(601));
+    for _ in 0..5 {
+        env.pic.tick();
+    }
+
+    assert!(client.get_auction(1_u64.into()).is_none());
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(bob()));
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE + RESERVE_PRICE + MIN_INCREMENT - LEDGER_FEE
+    );
+    // approve, two transfer_from and the refund fees
+    assert_eq!(
+        ledger.balance_of(bob()),
+        LEDGER_INITIAL_BALANCE - RESERVE_PRICE - MIN_INCREMENT - 4 * LEDGER_FEE
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_keep_open_auction_on_upgrade() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: None,
+            },
+        )
+        .is_ok());
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    assert!(client
+        .create_auction(
+            alice(),
+            1_u64.into(),
+            ledger_id,
+            AuctionKind::English {
+                reserve_price: RESERVE_PRICE.into(),
+                min_increment: MIN_INCREMENT.into(),
+                extension: 60_000_000_000,
+            },
+            now + 600_000_000_000,
+        )
+        .is_ok());
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(10 * RESERVE_PRICE))
+        .is_ok());
+    assert!(client
+        .bid(bob(), 1_u64.into(), RESERVE_PRICE.into())
+        .is_ok());
+
+    env.upgrade_dip721();
+
+    // the escrowed bid is still held by the auction
+    let auction = client.get_auction(1_u64.into()).unwrap();
+    assert_eq!(auction.bids.len(), 1);
+    assert_eq!(auction.bids[0].bidder, bob());
+}
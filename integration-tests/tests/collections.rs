@@ -0,0 +1,46 @@
+use dip721_rs::SupportedInterface;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::did::CreateCollectionArgs;
+use integration_tests::TestEnv;
+
+#[test]
+#[serial_test::serial]
+fn test_should_mint_in_collection() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let collection_id = client
+        .create_collection(
+            admin(),
+            CreateCollectionArgs {
+                name: "collection".to_string(),
+                symbol: "COL".to_string(),
+                logo: None,
+                custodians: vec![bob()],
+                supported_interfaces: vec![SupportedInterface::Mint],
+                max_supply: Some(1),
+            },
+        )
+        .unwrap();
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client
+        .collection_mint(bob(), collection_id, bob(), 1_u64.into())
+        .is_ok());
+    assert!(client
+        .collection_mint(bob(), collection_id, bob(), 2_u64.into())
+        .is_err());
+
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+    assert_eq!(
+        client
+            .collection_owner_of(collection_id, 1_u64.into())
+            .unwrap(),
+        Some(bob())
+    );
+    assert_eq!(
+        client.collection_total_supply(collection_id).unwrap(),
+        1_u64
+    );
+}
@@ -0,0 +1,40 @@
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::TestEnv;
+
+#[test]
+#[serial_test::serial]
+fn test_should_transfer_children_with_parent() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let parent = Nat::from(1_u64);
+    let child = Nat::from(2_u64);
+    for token_identifier in [&parent, &child] {
+        assert!(client
+            .mint(admin(), alice(), token_identifier.clone(), vec![])
+            .is_ok());
+    }
+
+    assert!(client
+        .attach_child(bob(), parent.clone(), child.clone())
+        .is_err());
+    assert!(client
+        .attach_child(alice(), parent.clone(), child.clone())
+        .is_ok());
+    assert_eq!(
+        client.get_token_children(parent.clone()).unwrap(),
+        vec![child.clone()]
+    );
+    assert!(client.transfer(alice(), bob(), child.clone()).is_err());
+
+    assert!(client.transfer(alice(), bob(), parent.clone()).is_ok());
+    assert_eq!(client.owner_of(child.clone()).unwrap(), Some(bob()));
+    assert_eq!(client.root_owner_of(child.clone()).unwrap(), Some(bob()));
+
+    assert!(client
+        .detach_child(bob(), parent.clone(), child.clone())
+        .is_ok());
+    assert!(client.transfer(bob(), alice(), child).is_ok());
+}
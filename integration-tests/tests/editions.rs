@@ -0,0 +1,37 @@
+use dip721_rs::GenericValue;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::TestEnv;
+
+#[test]
+#[serial_test::serial]
+fn test_should_mint_editions_of_template() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let name = (
+        "name".to_string(),
+        GenericValue::TextContent("artwork".to_string()),
+    );
+    let template_id = client
+        .create_edition_template(admin(), vec![name.clone()], Some(2))
+        .unwrap();
+
+    let token_identifier = client
+        .mint_edition(admin(), alice(), template_id, vec![])
+        .unwrap();
+    assert!(client
+        .mint_edition(admin(), bob(), template_id, vec![])
+        .is_ok());
+    assert!(client
+        .mint_edition(admin(), bob(), template_id, vec![])
+        .is_err());
+    assert_eq!(client.get_edition_count(template_id).unwrap(), 2);
+
+    let metadata = client.token_metadata(token_identifier).unwrap();
+    assert_eq!(metadata.owner, Some(alice()));
+    assert!(metadata.properties.contains(&name));
+    assert!(metadata
+        .properties
+        .contains(&("edition".to_string(), GenericValue::Nat64Content(1))));
+}
@@ -0,0 +1,52 @@
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::did::{ExtCommonError, ExtResult, ExtTransferRequest, ExtUser};
+use integration_tests::TestEnv;
+use serde_bytes::ByteBuf;
+
+#[test]
+#[serial_test::serial]
+fn test_should_transfer_tokens_through_ext() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let token_identifier = Nat::from(1_u64);
+    assert!(client
+        .mint(admin(), alice(), token_identifier.clone(), vec![])
+        .is_ok());
+    let token = client
+        .ext_token_identifier_of(token_identifier.clone())
+        .unwrap();
+    let ExtResult::Ok(alice_identifier) = client.bearer(token.clone()) else {
+        panic!("token should have a bearer");
+    };
+    assert_eq!(client.get_registry(), vec![(1, alice_identifier.clone())]);
+    assert_eq!(
+        client.tokens_ext(alice_identifier.clone()),
+        ExtResult::Ok(vec![(1, None, None)])
+    );
+
+    let request = ExtTransferRequest {
+        from: ExtUser::Address(alice_identifier.clone()),
+        to: ExtUser::Principal(bob()),
+        token: token.clone(),
+        amount: 1_u64.into(),
+        memo: ByteBuf::default(),
+        notify: false,
+        subaccount: None,
+    };
+    assert!(matches!(
+        client.ext_transfer(bob(), request.clone()),
+        ExtResult::Err(_)
+    ));
+    assert_eq!(
+        client.ext_transfer(alice(), request),
+        ExtResult::Ok(Nat::from(1_u64))
+    );
+    assert_eq!(client.owner_of(token_identifier).unwrap(), Some(bob()));
+    assert_eq!(
+        client.tokens_ext(alice_identifier),
+        ExtResult::Err(ExtCommonError::Other("No tokens".to_string()))
+    );
+}
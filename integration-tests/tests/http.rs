@@ -0,0 +1,197 @@
+use dip721_rs::GenericValue;
+use integration_tests::actor::{admin, alice};
+use integration_tests::client::{Dip721Client, HttpClient};
+use integration_tests::TestEnv;
+
+#[test]
+#[serial_test::serial]
+fn test_should_route_rest_requests() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), alice(), 2_u64.into(), vec![]).is_ok());
+
+    assert_eq!(http.get("/metadata", &[]).status_code, 200);
+    assert_eq!(http.get("/stats", &[]).status_code, 200);
+    assert_eq!(http.get("/tokens/1", &[]).status_code, 200);
+    assert_eq!(http.get("/tokens/3", &[]).status_code, 404);
+    assert_eq!(http.get("/transactions/0", &[]).status_code, 200);
+
+    let response = http.get(&format!("/owners/{}/tokens?limit=1", alice()), &[]);
+    assert_eq!(response.status_code, 200);
+    let page: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["tokens"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_render_token_json() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert!(client
+        .mint(
+            admin(),
+            alice(),
+            1_u64.into(),
+            vec![
+                (
+                    "name".to_string(),
+                    GenericValue::TextContent("Sword".to_string())
+                ),
+                ("damage".to_string(), GenericValue::Nat32Content(10)),
+            ]
+        )
+        .is_ok());
+
+    let response = http.get("/token/1.json", &[]);
+    assert_eq!(response.status_code, 200);
+    let json: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "name": "Sword",
+            "attributes": [{ "trait_type": "damage", "value": 10 }]
+        })
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_call_json_rpc() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+
+    let response = http.json_rpc(&serde_json::json!([
+        { "jsonrpc": "2.0", "method": "dip721_owner_of", "params": [1], "id": 1 },
+        { "jsonrpc": "2.0", "method": "dip721_owner_of", "params": [2], "id": 2 },
+        { "jsonrpc": "2.0", "method": "dip721_name" }
+    ]));
+    assert_eq!(response.status_code, 200);
+    let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!([
+            { "jsonrpc": "2.0", "result": alice().to_text(), "id": 1 },
+            {
+                "jsonrpc": "2.0",
+                "error": { "code": -32000, "message": "token not found", "data": "TokenNotFound" },
+                "id": 2
+            }
+        ])
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_execute_signed_requests() {
+    use ed25519_dalek::Signer as _;
+
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    // DER encoded ed25519 public key
+    let key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+    let public_key = [
+        [
+            0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+        ]
+        .as_slice(),
+        key.verifying_key().as_bytes().as_slice(),
+    ]
+    .concat();
+    let signer = candid::Principal::self_authenticating(&public_key);
+    let signed = |payload: serde_json::Value| {
+        let payload = payload.to_string();
+        serde_json::json!({
+            "payload": payload,
+            "public_key": hex::encode(&public_key),
+            "signature": hex::encode(key.sign(payload.as_bytes()).to_bytes()),
+        })
+    };
+
+    client.set_http_minters(admin(), vec![signer]);
+    let response = http.post(
+        "/mint",
+        &signed(serde_json::json!({
+            "canister_id": env.dip721_id.to_text(),
+            "method": "mint",
+            "nonce": 1,
+            "expires_at": u64::MAX,
+            "to": signer.to_text(),
+            "token_identifier": 1
+        })),
+    );
+    assert_eq!(response.status_code, 200);
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(signer));
+
+    let transfer = signed(serde_json::json!({
+        "canister_id": env.dip721_id.to_text(),
+        "method": "transfer",
+        "nonce": 2,
+        "expires_at": u64::MAX,
+        "to": alice().to_text(),
+        "token_identifier": 1
+    }));
+    assert_eq!(http.post("/transfer", &transfer).status_code, 200);
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+
+    // replay
+    assert_eq!(http.post("/transfer", &transfer).status_code, 400);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_set_cors_and_cache_headers() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    client.set_allowed_origins(admin(), vec!["https://example.com".to_string()]);
+    let response = http.request(
+        "OPTIONS",
+        "/metadata",
+        &[("origin", "https://example.com")],
+        &[],
+    );
+    assert_eq!(response.status_code, 204);
+    assert_eq!(
+        response.headers.get("access-control-allow-origin").unwrap(),
+        "https://example.com"
+    );
+    let response = http.get("/metadata", &[("origin", "https://example.org")]);
+    assert!(!response.headers.contains_key("access-control-allow-origin"));
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    let response = http.get("/transactions/0", &[]);
+    assert_eq!(response.status_code, 200);
+    let etag = response.headers.get("etag").unwrap();
+    let response = http.get("/transactions/0", &[("if-none-match", etag)]);
+    assert_eq!(response.status_code, 304);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_serve_metrics() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_err());
+
+    let response = http.get("/metrics", &[]);
+    assert_eq!(response.status_code, 200);
+    let text = String::from_utf8(response.body.into_vec()).unwrap();
+    assert!(text.contains("dip721_total_supply 1\n"));
+    assert!(text.contains("dip721_calls_total{method=\"dip721_mint\"} 2\n"));
+    assert!(text.contains("dip721_errors_total{method=\"dip721_mint\",error=\"ExistedNFT\"} 1\n"));
+}
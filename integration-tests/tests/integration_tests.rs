@@ -1,5 +1,21 @@
+mod accounts;
+mod allowlist;
+mod assets;
+mod auctions;
+mod collections;
+mod composables;
+mod editions;
+mod ext;
+mod http;
 mod inspect;
+mod marketplace;
 mod nft;
+mod offers;
+mod public_mint;
+mod reveal;
+mod royalties;
+mod swaps;
+mod vouchers;
 
 use integration_tests::TestEnv;
 
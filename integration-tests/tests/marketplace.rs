@@ -0,0 +1,106 @@
+use candid::{Nat, Principal};
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::{MarketplaceSettings, PlatformFee, Royalty, RoyaltyRecipient};
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+
+const PRICE: u64 = 1_000_000_000;
+
+#[test]
+#[serial_test::serial]
+fn test_should_buy_listed_token() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+    let artist = Principal::from_slice(&[1; 29]);
+    let platform = Principal::from_slice(&[2; 29]);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: Some(PlatformFee {
+                    basis_points: 250,
+                    recipient: platform,
+                }),
+            },
+        )
+        .is_ok());
+    assert!(client
+        .set_collection_royalty(
+            admin(),
+            Some(Royalty {
+                basis_points: 1_000,
+                recipients: vec![RoyaltyRecipient {
+                    recipient: artist,
+                    share: 10_000,
+                }],
+            }),
+        )
+        .is_ok());
+
+    assert!(client
+        .list_token(alice(), 1_u64.into(), PRICE.into(), ledger_id)
+        .is_ok());
+    assert!(client
+        .list_token(bob(), 1_u64.into(), PRICE.into(), ledger_id)
+        .is_err());
+
+    // buyer must approve the canister first
+    assert!(client.buy(bob(), 1_u64.into()).is_err());
+    assert!(client.get_listing(1_u64.into()).is_some());
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(PRICE + LEDGER_FEE))
+        .is_ok());
+    assert!(client.buy(bob(), 1_u64.into()).is_ok());
+
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(bob()));
+    assert!(client.get_listing(1_u64.into()).is_none());
+    // approve and transfer_from fees are paid by the buyer
+    assert_eq!(
+        ledger.balance_of(bob()),
+        LEDGER_INITIAL_BALANCE - PRICE - 2 * LEDGER_FEE
+    );
+    // the payouts fees are taken from the split
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE + PRICE * 8_750 / 10_000 - LEDGER_FEE
+    );
+    assert_eq!(
+        ledger.balance_of(artist),
+        PRICE * 1_000 / 10_000 - LEDGER_FEE
+    );
+    assert_eq!(
+        ledger.balance_of(platform),
+        PRICE * 250 / 10_000 - LEDGER_FEE
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_invalidate_listing_on_transfer() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: None,
+            },
+        )
+        .is_ok());
+    assert!(client
+        .list_token(alice(), 1_u64.into(), PRICE.into(), ledger_id)
+        .is_ok());
+
+    assert!(client.transfer(alice(), bob(), 1_u64.into()).is_ok());
+    assert!(client.get_listing(1_u64.into()).is_none());
+    assert!(client.buy(bob(), 1_u64.into()).is_err());
+}
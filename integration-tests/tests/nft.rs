@@ -1,3 +1,5 @@
+use std::time::{Duration, UNIX_EPOCH};
+
 use integration_tests::actor::{admin, alice, bob};
 use integration_tests::client::Dip721Client;
 use integration_tests::TestEnv;
@@ -14,6 +16,27 @@ fn test_should_mint() {
     assert_eq!(token.owner, Some(alice()));
 }
 
+#[test]
+#[serial_test::serial]
+fn test_should_keep_tokens_on_upgrade() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), bob(), 2_u64.into(), vec![]).is_ok());
+
+    env.upgrade_dip721();
+    env.upgrade_dip721();
+
+    assert_eq!(client.total_supply(), 2_u64);
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+    assert_eq!(client.owner_of(2_u64.into()).unwrap(), Some(bob()));
+    assert_eq!(client.balance_of(alice()).unwrap(), 1_u64);
+    // custodians are kept too
+    assert!(client.mint(admin(), alice(), 3_u64.into(), vec![]).is_ok());
+    assert_eq!(client.total_supply(), 3_u64);
+}
+
 #[test]
 #[serial_test::serial]
 fn test_should_transfer() {
@@ -27,6 +50,41 @@ fn test_should_transfer() {
     assert_eq!(token.owner, Some(alice()));
 }
 
+#[test]
+#[serial_test::serial]
+fn test_should_transfer_batch() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    assert!(client.mint(admin(), admin(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), admin(), 2_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), bob(), 3_u64.into(), vec![]).is_ok());
+
+    assert!(client
+        .transfer_batch(
+            admin(),
+            vec![
+                (admin(), alice(), 1_u64.into()),
+                (bob(), alice(), 3_u64.into()),
+            ],
+        )
+        .is_err());
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(admin()));
+
+    let batch = client
+        .transfer_batch(
+            admin(),
+            vec![
+                (admin(), alice(), 1_u64.into()),
+                (admin(), bob(), 2_u64.into()),
+            ],
+        )
+        .unwrap();
+    assert_eq!(batch.tx_ids.len(), 2);
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+    assert_eq!(client.owner_of(2_u64.into()).unwrap(), Some(bob()));
+}
+
 #[test]
 #[serial_test::serial]
 fn test_should_not_allow_transfer() {
@@ -84,3 +142,29 @@ fn test_should_burn() {
 
     assert!(token.is_burned);
 }
+
+#[test]
+#[serial_test::serial]
+fn test_should_lend_token_to_user() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    assert!(client
+        .set_user(bob(), 1_u64.into(), bob(), now + 60_000_000_000)
+        .is_err());
+    assert!(client
+        .set_user(alice(), 1_u64.into(), bob(), now + 60_000_000_000)
+        .is_ok());
+    assert_eq!(client.user_of(1_u64.into()).unwrap(), Some(bob()));
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+
+    env.pic.advance_time(Duration::from_secs(61));
+    assert_eq!(client.user_of(1_u64.into()).unwrap(), None);
+}
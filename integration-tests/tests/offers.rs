@@ -0,0 +1,78 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::MarketplaceSettings;
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+
+const AMOUNT: u64 = 100_000_000;
+
+#[test]
+#[serial_test::serial]
+fn test_should_accept_and_expire_offers() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), alice(), 2_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: None,
+            },
+        )
+        .is_ok());
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(10 * AMOUNT))
+        .is_ok());
+
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let accepted = client
+        .make_offer(
+            bob(),
+            1_u64.into(),
+            AMOUNT.into(),
+            now + 3_600_000_000_000,
+            ledger_id,
+        )
+        .unwrap();
+    let expiring = client
+        .make_offer(
+            bob(),
+            2_u64.into(),
+            AMOUNT.into(),
+            now + 60_000_000_000,
+            ledger_id,
+        )
+        .unwrap();
+
+    assert!(client.accept_offer(bob(), accepted).is_err());
+    assert!(client.accept_offer(alice(), accepted).is_ok());
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(bob()));
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE + AMOUNT - LEDGER_FEE
+    );
+
+    env.pic.advance_time(Duration::from_secs(61));
+    for _ in 0..5 {
+        env.pic.tick();
+    }
+    assert!(client.get_offer(expiring).is_none());
+    assert_eq!(client.owner_of(2_u64.into()).unwrap(), Some(alice()));
+    // approve, two transfer_from and the refund fees
+    assert_eq!(
+        ledger.balance_of(bob()),
+        LEDGER_INITIAL_BALANCE - AMOUNT - 4 * LEDGER_FEE
+    );
+}
@@ -0,0 +1,98 @@
+use std::time::UNIX_EPOCH;
+
+use candid::{Nat, Principal};
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::MintSale;
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+
+const PRICE: u64 = 100_000_000;
+
+#[test]
+#[serial_test::serial]
+fn test_should_mint_from_public_sale() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+    let treasury = Principal::from_slice(&[1; 29]);
+
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let sale = MintSale {
+        price: PRICE.into(),
+        ledger: ledger_id,
+        treasury,
+        wallet_limit: 2,
+        allocation: 3,
+        starts_at: now,
+        ends_at: now + 3_600_000_000_000,
+    };
+    assert!(client.set_mint_sale(admin(), Some(sale)).is_ok());
+
+    // payment fails without an approval, and doesn't consume the supply
+    assert!(client.public_mint(alice(), 1).is_err());
+    assert_eq!(client.get_sale_minted(), 0);
+
+    assert!(ledger
+        .approve(alice(), env.dip721_id, Nat::from(10 * PRICE))
+        .is_ok());
+    let tokens = client.public_mint(alice(), 2).unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(client.owner_of(tokens[0].clone()).unwrap(), Some(alice()));
+    assert!(client.public_mint(alice(), 1).is_err());
+    // the canister forwards the payment to the treasury, minus the ledger fee
+    assert_eq!(ledger.balance_of(treasury), 2 * PRICE - LEDGER_FEE);
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE - 2 * PRICE - 2 * LEDGER_FEE
+    );
+
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(10 * PRICE))
+        .is_ok());
+    assert!(client.public_mint(bob(), 2).is_err());
+    assert!(client.public_mint(bob(), 1).is_ok());
+    assert_eq!(client.get_sale_minted(), 3);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_should_mint_random_identifiers() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let sale = MintSale {
+        price: 0_u64.into(),
+        ledger: Principal::management_canister(),
+        treasury: admin(),
+        wallet_limit: 3,
+        allocation: 3,
+        starts_at: now,
+        ends_at: now + 3_600_000_000_000,
+    };
+    assert!(client.set_mint_sale(admin(), Some(sale)).is_ok());
+    assert!(client.set_random_pool(admin(), 3).is_ok());
+
+    // the CSPRNG is seeded from raw_rand by the global timer
+    for _ in 0..5 {
+        env.pic.tick();
+    }
+    let mut tokens = client.public_mint(alice(), 3).unwrap();
+    tokens.sort();
+    assert_eq!(
+        tokens,
+        vec![Nat::from(1_u64), Nat::from(2_u64), Nat::from(3_u64)]
+    );
+    assert!(client.public_mint(bob(), 1).is_err());
+}
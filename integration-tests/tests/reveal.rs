@@ -0,0 +1,34 @@
+use dip721_rs::GenericValue;
+use integration_tests::actor::{admin, alice};
+use integration_tests::client::Dip721Client;
+use integration_tests::TestEnv;
+use serde_bytes::ByteBuf;
+use sha2::{Digest as _, Sha256};
+
+#[test]
+#[serial_test::serial]
+fn test_should_reveal_committed_metadata() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    let metadata = (0..3)
+        .map(|index| vec![("index".to_string(), GenericValue::Nat64Content(index))])
+        .collect::<Vec<_>>();
+    let mut hasher = Sha256::new();
+    for properties in &metadata {
+        hasher.update(Sha256::digest(candid::encode_one(properties).unwrap()));
+    }
+    let hash = ByteBuf::from(hasher.finalize().to_vec());
+    assert!(client.set_provenance_hash(admin(), hash).is_ok());
+
+    for id in 1_u64..=3 {
+        assert!(client.mint(admin(), alice(), id.into(), vec![]).is_ok());
+    }
+    assert!(client.reveal(admin(), metadata[1..].to_vec()).is_err());
+    assert!(client.reveal(admin(), metadata).is_ok());
+    // the tokens are given their metadata by the timer
+    for _ in 0..3 {
+        env.pic.tick();
+    }
+    assert!(client.verify_reveal().unwrap());
+}
@@ -0,0 +1,36 @@
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, HttpClient};
+use integration_tests::did::{Royalty, RoyaltyPayment, RoyaltyRecipient};
+use integration_tests::TestEnv;
+
+#[test]
+#[serial_test::serial]
+fn test_should_set_collection_royalty() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let http = HttpClient::new(&env);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    let royalty = Royalty {
+        basis_points: 500,
+        recipients: vec![RoyaltyRecipient {
+            recipient: bob(),
+            share: 10_000,
+        }],
+    };
+    assert!(client
+        .set_collection_royalty(admin(), Some(royalty))
+        .is_ok());
+
+    assert_eq!(
+        client.royalty_info(1_u64.into(), 2_000_u64.into()).unwrap(),
+        vec![RoyaltyPayment {
+            recipient: bob(),
+            amount: 100_u64.into()
+        }]
+    );
+
+    let response = http.get("/token/1.json", &[]);
+    let json: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(json["royalty"]["basis_points"], 500);
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use candid::Nat;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::{Dip721Client, Icrc2Client};
+use integration_tests::did::{MarketplaceSettings, ProposeSwapArgs, SwapPayment};
+use integration_tests::{TestEnv, LEDGER_FEE, LEDGER_INITIAL_BALANCE};
+
+const AMOUNT: u64 = 100_000_000;
+
+#[test]
+#[serial_test::serial]
+fn test_should_accept_and_expire_swaps() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+    let ledger_id = env.install_icrc1_ledger();
+    let ledger = Icrc2Client::new(&env, ledger_id);
+
+    assert!(client.mint(admin(), alice(), 1_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), alice(), 2_u64.into(), vec![]).is_ok());
+    assert!(client.mint(admin(), bob(), 3_u64.into(), vec![]).is_ok());
+    assert!(client
+        .set_marketplace_settings(
+            admin(),
+            MarketplaceSettings {
+                ledgers: vec![ledger_id],
+                platform_fee: None,
+            },
+        )
+        .is_ok());
+    assert!(ledger
+        .approve(bob(), env.dip721_id, Nat::from(10 * AMOUNT))
+        .is_ok());
+
+    let now = env
+        .pic
+        .get_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let accepted = client
+        .propose_swap(
+            alice(),
+            ProposeSwapArgs {
+                counterparty: bob(),
+                offered: vec![1_u64.into()],
+                requested: vec![3_u64.into()],
+                payment: Some(SwapPayment {
+                    ledger: ledger_id,
+                    amount: AMOUNT.into(),
+                }),
+                expires_at: now + 3_600_000_000_000,
+            },
+        )
+        .unwrap();
+    let expiring = client
+        .propose_swap(
+            alice(),
+            ProposeSwapArgs {
+                counterparty: bob(),
+                offered: vec![2_u64.into()],
+                requested: vec![3_u64.into()],
+                payment: None,
+                expires_at: now + 60_000_000_000,
+            },
+        )
+        .unwrap();
+
+    // offered tokens are locked
+    assert!(client.transfer(alice(), admin(), 1_u64.into()).is_err());
+    let payment = Some(SwapPayment {
+        ledger: ledger_id,
+        amount: AMOUNT.into(),
+    });
+    assert!(client
+        .accept_swap(alice(), accepted, vec![1_u64.into()], payment.clone())
+        .is_err());
+    assert!(client
+        .accept_swap(bob(), accepted, vec![1_u64.into()], None)
+        .is_err());
+    assert!(client
+        .accept_swap(bob(), accepted, vec![1_u64.into()], payment)
+        .is_ok());
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(bob()));
+    assert_eq!(client.owner_of(3_u64.into()).unwrap(), Some(alice()));
+    assert_eq!(
+        ledger.balance_of(alice()),
+        LEDGER_INITIAL_BALANCE + AMOUNT - LEDGER_FEE
+    );
+
+    env.pic.advance_time(Duration::from_secs(61));
+    for _ in 0..5 {
+        env.pic.tick();
+    }
+    assert!(client.get_swap(expiring).is_none());
+    assert!(client.transfer(alice(), admin(), 2_u64.into()).is_ok());
+}
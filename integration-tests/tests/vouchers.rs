@@ -0,0 +1,63 @@
+use dip721_rs::GenericValue;
+use ed25519_dalek::Signer as _;
+use integration_tests::actor::{admin, alice, bob};
+use integration_tests::client::Dip721Client;
+use integration_tests::did::MintVoucher;
+use integration_tests::TestEnv;
+use serde_bytes::ByteBuf;
+use sha2::{Digest as _, Sha256};
+
+#[test]
+#[serial_test::serial]
+fn test_should_redeem_voucher() {
+    let env = TestEnv::init();
+    let client = Dip721Client::new(&env);
+
+    // DER encoded ed25519 public key
+    let key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+    let public_key = [
+        [
+            0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+        ]
+        .as_slice(),
+        key.verifying_key().as_bytes().as_slice(),
+    ]
+    .concat();
+    client.set_voucher_signers(
+        admin(),
+        vec![candid::Principal::self_authenticating(&public_key)],
+    );
+
+    let properties = vec![(
+        "name".to_string(),
+        GenericValue::TextContent("nft".to_string()),
+    )];
+    let voucher = MintVoucher {
+        token_identifier: 1_u64.into(),
+        to: alice(),
+        properties_hash: ByteBuf::from(
+            Sha256::digest(candid::encode_one(&properties).unwrap()).to_vec(),
+        ),
+        canister_id: env.dip721_id,
+        expires_at: u64::MAX,
+        nonce: 1,
+    };
+    let signature = key.sign(&candid::encode_one(&voucher).unwrap());
+
+    // anyone can redeem the voucher, once
+    for expected in [true, false] {
+        assert_eq!(
+            client
+                .redeem_voucher(
+                    bob(),
+                    voucher.clone(),
+                    properties.clone(),
+                    ByteBuf::from(public_key.clone()),
+                    ByteBuf::from(signature.to_bytes().to_vec()),
+                )
+                .is_ok(),
+            expected
+        );
+    }
+    assert_eq!(client.owner_of(1_u64.into()).unwrap(), Some(alice()));
+}
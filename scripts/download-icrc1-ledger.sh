@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Downloads the ICRC-1 ledger wasm used by the integration tests
+
+IC_VERSION="${IC_VERSION:-d87954601e4b22972899e9957e800406a0a6b929}"
+ARTIFACT_DIR=".artifact"
+LEDGER_WASM="$ARTIFACT_DIR/icrc1-ledger.wasm.gz"
+
+if [ -f "$LEDGER_WASM" ]; then
+  exit 0
+fi
+
+mkdir -p "$ARTIFACT_DIR"
+curl -fsSL -o "$LEDGER_WASM" "https://download.dfinity.systems/ic/$IC_VERSION/canisters/ic-icrc1-ledger.wasm.gz"
@@ -3,11 +3,21 @@
 //! API for App
 
 mod configuration;
+mod ext;
+mod guard;
 mod inspect;
+mod ledger;
+mod marketplace;
 mod memory;
+mod merkle;
+mod metrics;
+mod random;
+pub mod signature;
 pub mod storage;
 #[cfg(test)]
-mod test_utils;
+pub mod test_utils;
+
+use std::collections::BTreeSet;
 
 use async_trait::async_trait;
 use candid::{Nat, Principal};
@@ -17,11 +27,40 @@ use dip721_rs::{
     TokenMetadata, TxEvent,
 };
 
+use ic_stable_structures::Memory as _;
+use num_traits::ToPrimitive as _;
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+
+use self::ext::Ext;
+use self::guard::{CallerGuard, MintGuard};
 pub use self::inspect::Inspect;
-use self::storage::{TokensStorage, TxHistory};
-use crate::did::CanisterInitData;
+use self::ledger::Ledger;
+use self::marketplace::Marketplace;
+use self::merkle::Merkle;
+use self::metrics::Metrics;
+use self::random::Random;
+use self::signature::Signature;
+use self::storage::{
+    AllowlistStorage, AssetsStorage, AuctionsStorage, ChildrenStorage, EditionsStorage,
+    ListingsStorage, NoncesStorage, OffersStorage, ProceedsStorage, RandomPoolStorage,
+    RevealStorage, RoyaltiesStorage, SaleStorage, SwapsStorage, TokensStorage, TxHistory,
+    UsersStorage,
+};
+use crate::did::{
+    Account, AllowlistPhase, AssetMetadata, Auction, AuctionKind, Bid, CanisterInitData,
+    Collection, CollectionId, CreateCollectionArgs, EditionTemplate, ExtAccountIdentifier,
+    ExtCommonError, ExtMetadata, ExtResult, ExtTokenIdentifier, ExtTokenIndex, ExtTokensResult,
+    ExtTransferError, ExtTransferRequest, ExtUser, Listing, Logo, MarketplaceSettings, MintSale,
+    MintVoucher, Offer, ProposeSwapArgs, Provenance, RandomPool, Reveal, Royalty, RoyaltyPayment,
+    Subaccount, Swap, SwapPayment, TokenEdition, TokenJsonMapping, TokenUser, TransferBatch,
+    BASIS_POINTS, DEFAULT_COLLECTION_ID,
+};
 use crate::utils::caller;
 
+/// Token property referencing the asset id of the token media
+pub const TOKEN_ASSET_PROPERTY: &str = "asset_id";
+
 #[derive(Default)]
 /// App canister
 pub struct App;
@@ -34,14 +73,92 @@ impl App {
         Configuration::set_name(init_data.name);
         Configuration::set_symbol(init_data.symbol);
         if let Some(logo) = init_data.logo {
-            Configuration::set_logo(logo);
+            Configuration::set_logo(Logo::from_text(&logo));
+        }
+        if let Some(hash) = init_data.provenance_hash {
+            Self::commit_provenance(hash).expect("invalid provenance hash");
         }
+        // the CSPRNG is seeded by the global timer
+        Self::schedule_global_timer();
     }
 
     /// Task to execute on post upgrade
     pub fn post_upgrade() {
         // update upgraded at timestamp
         Configuration::set_upgraded_at();
+        // logo used to be stored as base64 text
+        Configuration::migrate_legacy_logo();
+        // tokens used to be held by principals only, before accounts, and are indexed by the timer
+        TokensStorage::start_owner_index_migration();
+        // timers are cleared on upgrade: schedule the global timer again, so that it resumes the migrations of the
+        // legacy tokens and of the owner index, the pending reveal, and seeds the CSPRNG, which lives in the heap
+        Self::schedule_global_timer();
+    }
+
+    /// Sets the logo of the NFT canister as binary data of the given MIME type.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_logo(content_type: String, data: ByteBuf) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_logo(Logo {
+            content_type,
+            data,
+            encoding: None,
+        });
+    }
+
+    /// Returns the logo of the NFT canister
+    pub fn get_logo() -> Option<Logo> {
+        Configuration::get_logo()
+    }
+
+    /// Returns the URL the logo is served at over HTTP
+    fn logo_url() -> Option<String> {
+        Configuration::get_logo().map(|_| Self::canister_url("/logo"))
+    }
+
+    /// Returns the URL of the given path served by the canister over HTTP
+    pub fn canister_url(path: &str) -> String {
+        format!("{}{path}", Self::get_http_base_url())
+    }
+
+    /// Returns the base URL the canister is served at over HTTP, `https://<canister id>.raw.icp0.io` unless set
+    pub fn get_http_base_url() -> String {
+        Configuration::get_http_base_url()
+            .unwrap_or_else(|| format!("https://{}.raw.icp0.io", crate::utils::id()))
+    }
+
+    /// Sets the base URL the canister is served at over HTTP, such as a custom domain; `None` restores the default.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_http_base_url(url: Option<String>) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        let url = url.map(|url| url.trim_end_matches('/').to_string());
+        if url
+            .as_deref()
+            .is_some_and(|url| !url.starts_with("https://") && !url.starts_with("http://"))
+        {
+            return Err(NftError::Other("invalid base url".to_string()));
+        }
+        Configuration::set_http_base_url(url);
+
+        Ok(())
+    }
+
+    /// Returns the mapping of token properties to the marketplace metadata JSON
+    pub fn get_token_json_mapping() -> TokenJsonMapping {
+        Configuration::get_token_json_mapping()
+    }
+
+    /// Sets the mapping of token properties to the marketplace metadata JSON.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_token_json_mapping(mapping: TokenJsonMapping) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_token_json_mapping(mapping);
     }
 
     /// Set token property
@@ -54,257 +171,2437 @@ impl App {
             ic_cdk::trap("Unauthorized");
         }
 
-        TokensStorage::set_token_property(&token_identifier, key, value)
+        TokensStorage::set_token_property(DEFAULT_COLLECTION_ID, &token_identifier, key, value)
     }
-}
 
-#[async_trait]
-impl Dip721 for App {
-    /// Returns the Metadata of the NFT canister which includes custodians, logo, name, symbol.
-    fn dip721_metadata() -> Metadata {
-        Metadata {
-            created_at: Configuration::get_created_at(),
-            custodians: Self::dip721_custodians(),
-            logo: Self::dip721_logo(),
-            name: Self::dip721_name(),
-            symbol: Self::dip721_symbol(),
-            upgraded_at: Configuration::get_upgraded_at(),
+    /// Create a new asset, whose content must then be uploaded in chunks.
+    /// Caller must be the custodian of NFT canister.
+    pub fn create_asset(content_type: String, sha256: ByteBuf) -> Result<u64, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
         }
+        let sha256 = sha256
+            .as_slice()
+            .try_into()
+            .map_err(|_| NftError::Other("invalid sha256".to_string()))?;
+
+        Ok(AssetsStorage::create(content_type, sha256))
     }
 
-    /// Returns the Stats of the NFT canister which includes cycles, totalSupply, totalTransactions, totalUniqueHolders.
-    fn dip721_stats() -> Stats {
-        Stats {
-            cycles: Self::dip721_cycles(),
-            total_supply: Self::dip721_total_supply(),
-            total_transactions: Self::dip721_total_transactions(),
-            total_unique_holders: Self::dip721_total_unique_holders(),
+    /// Append a chunk to the content of a pending asset.
+    /// Caller must be the custodian of NFT canister.
+    pub fn upload_asset_chunk(id: u64, chunk: ByteBuf) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
         }
+
+        AssetsStorage::upload_chunk(id, chunk.into_vec())
     }
 
-    /// Returns the logo of the NFT contract as Base64 encoded text.
-    fn dip721_logo() -> Option<String> {
-        Configuration::get_logo()
+    /// Verify the asset checksum and make it available over HTTP.
+    /// Caller must be the custodian of NFT canister.
+    pub fn finalize_asset(id: u64) -> Result<AssetMetadata, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+
+        AssetsStorage::finalize(id)
     }
 
-    /// Sets the logo of the NFT canister. Base64 encoded text is recommended.
+    /// Delete an asset, unless a token references it as its media.
     /// Caller must be the custodian of NFT canister.
-    fn dip721_set_logo(logo: String) {
+    pub fn delete_asset(id: u64) -> Result<(), NftError> {
         if !Inspect::inspect_is_custodian(caller()) {
             ic_cdk::trap("Unauthorized");
         }
-        Configuration::set_logo(logo);
+        let asset_property = GenericValue::Nat64Content(id);
+        let referenced = TokensStorage::tokens_properties(DEFAULT_COLLECTION_ID)
+            .iter()
+            .flat_map(|(_, properties)| properties)
+            .any(|(key, value)| key == TOKEN_ASSET_PROPERTY && value == &asset_property);
+        if referenced {
+            return Err(NftError::Other(
+                "asset is referenced by a token".to_string(),
+            ));
+        }
+
+        AssetsStorage::delete(id)
     }
 
-    /// Returns the name of the NFT canister.
-    fn dip721_name() -> Option<String> {
-        Configuration::get_name()
+    /// Get asset metadata
+    pub fn get_asset(id: u64) -> Option<AssetMetadata> {
+        AssetsStorage::get_asset(id)
     }
 
-    /// Sets the name of the NFT contract.
+    /// Read the content of an asset in the range `[start, end)`
+    pub fn read_asset(id: u64, start: u64, end: u64) -> Result<Vec<u8>, NftError> {
+        AssetsStorage::read(id, start, end)
+    }
+
+    /// Reference a finalized asset as the token media.
     /// Caller must be the custodian of NFT canister.
-    fn dip721_set_name(name: String) {
+    pub fn set_token_asset(
+        token_identifier: TokenIdentifier,
+        asset_id: u64,
+    ) -> Result<(), NftError> {
         if !Inspect::inspect_is_custodian(caller()) {
             ic_cdk::trap("Unauthorized");
         }
-        Configuration::set_name(name);
+        match AssetsStorage::get_asset(asset_id) {
+            Some(asset) if asset.finalized => {}
+            _ => return Err(NftError::Other("asset not found".to_string())),
+        }
+
+        TokensStorage::set_token_property(
+            DEFAULT_COLLECTION_ID,
+            &token_identifier,
+            TOKEN_ASSET_PROPERTY.to_string(),
+            GenericValue::Nat64Content(asset_id),
+        )
     }
 
-    /// Returns the symbol of the NFT contract.
-    fn dip721_symbol() -> Option<String> {
-        Configuration::get_symbol()
+    /// Returns the royalty of the tokens which don't have their own
+    pub fn get_collection_royalty() -> Option<Royalty> {
+        Configuration::get_collection_royalty()
     }
 
-    /// Set symbol
+    /// Sets the royalty of the tokens which don't have their own.
     /// Caller must be the custodian of NFT canister.
-    fn dip721_set_symbol(symbol: String) {
+    pub fn set_collection_royalty(royalty: Option<Royalty>) -> Result<(), NftError> {
         if !Inspect::inspect_is_custodian(caller()) {
             ic_cdk::trap("Unauthorized");
         }
-        Configuration::set_symbol(symbol);
+        Self::check_royalty(royalty.as_ref())?;
+        Configuration::set_collection_royalty(royalty);
+
+        Ok(())
     }
 
-    /// Returns a list of the canister custodians
-    fn dip721_custodians() -> Vec<Principal> {
-        Configuration::get_custodians()
+    /// Returns the royalty set on the token, overriding the collection one
+    pub fn get_token_royalty(token_identifier: TokenIdentifier) -> Option<Royalty> {
+        RoyaltiesStorage::get(&token_identifier)
     }
 
-    /// Set canister custodians
+    /// Sets the royalty of the token; `None` makes the token fall back to the collection royalty.
     /// Caller must be the custodian of NFT canister.
-    fn dip721_set_custodians(custodians: Vec<Principal>) {
+    pub fn set_token_royalty(
+        token_identifier: TokenIdentifier,
+        royalty: Option<Royalty>,
+    ) -> Result<(), NftError> {
         if !Inspect::inspect_is_custodian(caller()) {
             ic_cdk::trap("Unauthorized");
         }
-        Configuration::set_custodians(&custodians);
-    }
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Self::check_royalty(royalty.as_ref())?;
+        RoyaltiesStorage::set(&token_identifier, royalty);
 
-    /// Returns canister cycles
-    fn dip721_cycles() -> Nat {
-        crate::utils::cycles()
+        Ok(())
     }
 
-    /// Returns total unique holders of tokens
-    fn dip721_total_unique_holders() -> Nat {
-        TokensStorage::total_unique_holders().into()
+    /// Returns the royalty applying to the token: its own one or the collection one
+    pub fn royalty(token_identifier: &TokenIdentifier) -> Option<Royalty> {
+        RoyaltiesStorage::get(token_identifier).or_else(Configuration::get_collection_royalty)
     }
 
-    /// Returns metadata for token
-    fn dip721_token_metadata(token_identifier: TokenIdentifier) -> Result<TokenMetadata, NftError> {
-        TokensStorage::get_token(&token_identifier)
+    /// Returns the royalty payments due on the sale of the token at `sale_price`, as in EIP-2981.
+    ///
+    /// Returns no payments if no royalty applies to the token.
+    pub fn royalty_info(
+        token_identifier: TokenIdentifier,
+        sale_price: Nat,
+    ) -> Result<Vec<RoyaltyPayment>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+
+        Ok(Self::royalty(&token_identifier)
+            .map(|royalty| royalty.payments(&sale_price))
+            .unwrap_or_default())
     }
 
-    /// Returns the count of NFTs owned by user.
-    /// If the user does not own any NFTs, returns an error containing NftError.
-    fn dip721_balance_of(owner: Principal) -> Result<Nat, NftError> {
-        match TokensStorage::tokens_by_owner(owner) {
-            tokens if tokens.is_empty() => Err(NftError::OwnerNotFound),
-            tokens => Ok(tokens.len().into()),
+    fn check_royalty(royalty: Option<&Royalty>) -> Result<(), NftError> {
+        match royalty {
+            Some(royalty) if !royalty.is_valid() => {
+                Err(NftError::Other("invalid royalty".to_string()))
+            }
+            _ => Ok(()),
         }
     }
 
-    /// Returns the owner of the token.
-    /// Returns an error containing NftError if token_identifier is invalid.
-    fn dip721_owner_of(token_identifier: TokenIdentifier) -> Result<Option<Principal>, NftError> {
-        TokensStorage::get_token(&token_identifier).map(|token| token.owner)
+    /// Returns the settings of the marketplace
+    pub fn get_marketplace_settings() -> MarketplaceSettings {
+        Configuration::get_marketplace_settings()
     }
 
-    /// Returns the list of the token_identifier of the NFT associated with owner.
-    /// Returns an error containing NftError if principal is invalid.
-    fn dip721_owner_token_identifiers(owner: Principal) -> Result<Vec<TokenIdentifier>, NftError> {
-        match TokensStorage::tokens_by_owner(owner) {
-            tokens if tokens.is_empty() => Err(NftError::OwnerNotFound),
-            tokens => Ok(tokens),
+    /// Sets the ledgers tokens can be listed on and the platform fee of the marketplace.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_marketplace_settings(settings: MarketplaceSettings) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
         }
-    }
-
-    /// Returns the list of the token_metadata of the NFT associated with owner.
-    /// Returns an error containing NftError if principal is invalid.
-    fn dip721_owner_token_metadata(owner: Principal) -> Result<Vec<TokenMetadata>, NftError> {
-        let tokens = Self::dip721_owner_token_identifiers(owner)?;
-        let mut metadata = Vec::with_capacity(tokens.len());
-        for token in tokens {
-            metadata.push(Self::dip721_token_metadata(token)?);
+        if settings
+            .platform_fee
+            .as_ref()
+            .is_some_and(|fee| fee.basis_points > BASIS_POINTS)
+        {
+            return Err(NftError::Other("invalid platform fee".to_string()));
         }
+        Configuration::set_marketplace_settings(settings);
 
-        if metadata.is_empty() {
-            return Err(NftError::OwnerNotFound);
-        }
+        Ok(())
+    }
 
-        Ok(metadata)
+    /// Returns the listing of the token, if listed
+    pub fn get_listing(token_identifier: TokenIdentifier) -> Option<Listing> {
+        ListingsStorage::get(&token_identifier)
     }
 
-    /// Returns the Principal of the operator of the NFT associated with token_identifier.
-    fn dip721_operator_of(
+    /// Returns all the tokens listed on the marketplace
+    pub fn get_listings() -> Vec<(TokenIdentifier, Listing)> {
+        ListingsStorage::listings()
+    }
+
+    /// List the token for sale at `price` on `ledger`, replacing its previous listing.
+    /// Caller must be the owner of the token; the listing is removed when the token is transferred or burned.
+    pub fn list_token(
         token_identifier: TokenIdentifier,
-    ) -> Result<Option<Principal>, NftError> {
-        TokensStorage::get_token(&token_identifier).map(|token| token.operator)
+        price: Nat,
+        ledger: Principal,
+    ) -> Result<(), NftError> {
+        let seller = caller();
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        if !Self::is_owned_by(&token_identifier, seller) {
+            return Err(NftError::UnauthorizedOwner);
+        }
+        TokensStorage::check_transferable(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Self::check_ledger(ledger)?;
+        if price == 0_u64 {
+            return Err(NftError::Other("price must be positive".to_string()));
+        }
+
+        ListingsStorage::insert(
+            &token_identifier,
+            Listing {
+                seller,
+                price,
+                ledger,
+                listed_at: crate::utils::time(),
+            },
+        );
+
+        Ok(())
     }
 
-    /// Returns the list of the token_identifier of the NFT associated with operator.
-    fn dip721_operator_token_identifiers(
-        operator: Principal,
-    ) -> Result<Vec<TokenIdentifier>, NftError> {
-        match TokensStorage::tokens_by_operator(operator) {
-            tokens if tokens.is_empty() => Err(NftError::OperatorNotFound),
-            tokens => Ok(tokens),
+    /// Remove the listing of the token. Caller must be the seller.
+    pub fn cancel_listing(token_identifier: TokenIdentifier) -> Result<(), NftError> {
+        match ListingsStorage::get(&token_identifier) {
+            None => Err(NftError::Other("token not listed".to_string())),
+            Some(listing) if listing.seller != caller() => Err(NftError::UnauthorizedOwner),
+            Some(_) => {
+                ListingsStorage::remove(&token_identifier);
+                Ok(())
+            }
         }
     }
 
-    /// Returns the list of the token_metadata of the NFT associated with operator.
-    fn dip721_operator_token_metadata(operator: Principal) -> Result<Vec<TokenMetadata>, NftError> {
-        let tokens = Self::dip721_operator_token_identifiers(operator)?;
-        let mut metadata = Vec::with_capacity(tokens.len());
-        for token in tokens {
-            metadata.push(Self::dip721_token_metadata(token)?);
+    /// Buy a listed token, paying its price with an ICRC-2 `transfer_from` from the caller to the canister.
+    ///
+    /// The caller must have approved the canister to spend the price plus the ledger fee.
+    /// Once paid, the token is transferred to the caller and the price is split between the platform,
+    /// the royalty recipients and the seller. If the token can't be transferred anymore, the price is
+    /// refunded minus the ledger fee of the refund.
+    pub async fn buy(token_identifier: TokenIdentifier) -> Result<Nat, NftError> {
+        let buyer = caller();
+        let listing = ListingsStorage::get(&token_identifier)
+            .ok_or_else(|| NftError::Other("token not listed".to_string()))?;
+        if listing.seller == buyer {
+            return Err(NftError::SelfTransfer);
+        }
+        if !Self::is_owned_by(&token_identifier, listing.seller) {
+            ListingsStorage::remove(&token_identifier);
+            return Err(NftError::OwnerNotFound);
         }
 
-        if metadata.is_empty() {
-            return Err(NftError::OperatorNotFound);
+        // the listing is taken while awaiting the ledger, so that the token can't be bought twice
+        ListingsStorage::remove(&token_identifier);
+        let paid = match Ledger::fee(listing.ledger).await {
+            Ok(fee) => Ledger::transfer_from(
+                listing.ledger,
+                buyer,
+                crate::utils::id(),
+                listing.price.clone(),
+            )
+            .await
+            .map(|_| fee),
+            Err(err) => Err(err),
+        };
+        let ledger_fee = match paid {
+            Ok(fee) => fee,
+            Err(err) => {
+                // the seller may have transferred or relisted the token in the meantime
+                if Self::is_owned_by(&token_identifier, listing.seller)
+                    && ListingsStorage::get(&token_identifier).is_none()
+                {
+                    ListingsStorage::insert(&token_identifier, listing);
+                }
+                return Err(err);
+            }
+        };
+
+        if !Self::is_owned_by(&token_identifier, listing.seller) {
+            // refund the buyer
+            Marketplace::refund(listing.ledger, buyer, listing.price).await;
+            return Err(NftError::OwnerNotFound);
         }
+        let tx_id = TokensStorage::transfer(DEFAULT_COLLECTION_ID, &token_identifier, buyer)?;
 
-        Ok(metadata)
+        let split = Marketplace::split(
+            &listing.price,
+            listing.seller,
+            Self::royalty(&token_identifier).as_ref(),
+            Configuration::get_marketplace_settings()
+                .platform_fee
+                .as_ref(),
+        );
+        Marketplace::payout(listing.ledger, &ledger_fee, split).await;
+
+        Ok(tx_id)
     }
 
-    /// Returns the list of the interfaces supported by this canister
-    fn dip721_supported_interfaces() -> Vec<SupportedInterface> {
-        Configuration::get_supported_interfaces()
+    fn check_ledger(ledger: Principal) -> Result<(), NftError> {
+        if Configuration::get_marketplace_settings()
+            .ledgers
+            .contains(&ledger)
+        {
+            Ok(())
+        } else {
+            Err(NftError::Other("ledger not accepted".to_string()))
+        }
     }
 
-    /// Returns the total supply of the NFT.
-    /// NFTs that are minted and later burned explicitly or sent to the zero address should also count towards totalSupply.
-    fn dip721_total_supply() -> Nat {
-        TokensStorage::total_supply().into()
+    /// Returns whether the token is held by the default subaccount of owner
+    fn is_owned_by(token_identifier: &TokenIdentifier, owner: Principal) -> bool {
+        TokensStorage::is_held_by(DEFAULT_COLLECTION_ID, token_identifier, owner, None)
     }
 
-    // Calling approve grants the operator the ability to make update calls to the specificied token_identifier.
-    // Approvals given by the approve function are independent from approvals given by the setApprovalForAll.
-    //
-    // If the approval goes through, returns a nat that represents the CAP History transaction ID that can be used at the transaction method.
-    /// Interface: approval
-    fn dip721_approve(
-        operator: Principal,
+    /// Returns the auction of the token, if any
+    pub fn get_auction(token_identifier: TokenIdentifier) -> Option<Auction> {
+        AuctionsStorage::get(&token_identifier)
+    }
+
+    /// Returns all the auctions
+    pub fn get_auctions() -> Vec<(TokenIdentifier, Auction)> {
+        AuctionsStorage::auctions()
+    }
+
+    /// Put the token up for auction on `ledger` until `ends_at`, removing its listing.
+    /// Caller must be the owner of the token, which can't be transferred until the auction is settled.
+    pub fn create_auction(
         token_identifier: TokenIdentifier,
-    ) -> Result<Nat, NftError> {
-        if !Inspect::inspect_is_owner(caller(), &token_identifier) {
+        ledger: Principal,
+        kind: AuctionKind,
+        ends_at: u64,
+    ) -> Result<(), NftError> {
+        let seller = caller();
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        if !Self::is_owned_by(&token_identifier, seller) {
             return Err(NftError::UnauthorizedOwner);
         }
-
-        if Configuration::has_interface(SupportedInterface::Approval) {
-            TokensStorage::approve(operator, &token_identifier)
-        } else {
-            Err(NftError::Other("Not implemented".to_string()))
+        if AuctionsStorage::contains(&token_identifier) {
+            return Err(NftError::Other(
+                "token is already under auction".to_string(),
+            ));
+        }
+        TokensStorage::check_transferable(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Self::check_ledger(ledger)?;
+        let now = crate::utils::time();
+        if ends_at <= now {
+            return Err(NftError::Other(
+                "auction must end in the future".to_string(),
+            ));
         }
+        let valid_kind = match &kind {
+            AuctionKind::English {
+                reserve_price,
+                min_increment,
+                ..
+            } => reserve_price > &0_u64 && min_increment > &0_u64,
+            AuctionKind::Dutch {
+                start_price,
+                end_price,
+            } => end_price > &0_u64 && start_price >= end_price,
+        };
+        if !valid_kind {
+            return Err(NftError::Other("invalid auction prices".to_string()));
+        }
+
+        ListingsStorage::remove(&token_identifier);
+        AuctionsStorage::insert(
+            &token_identifier,
+            Auction {
+                seller,
+                ledger,
+                kind,
+                starts_at: now,
+                ends_at,
+                bids: vec![],
+            },
+        );
+        Self::schedule_global_timer();
+
+        Ok(())
     }
 
-    /// Enable or disable an operator to manage all of the tokens for the caller of this function. The contract allows multiple operators per owner.
-    /// Approvals granted by the approve function are independent from the approvals granted by setApprovalForAll function.
-    /// If the approval goes through, returns a nat that represents the CAP History transaction ID that can be used at the transaction method.
-    /// Interface: approval
-    fn dip721_set_approval_for_all(operator: Principal, approved: bool) -> Result<Nat, NftError> {
-        if Configuration::has_interface(SupportedInterface::Approval) {
-            let tokens_by_owner = Self::dip721_owner_token_identifiers(caller())?;
-            let mut tx_id = None;
-            for token in tokens_by_owner {
-                if approved {
-                    tx_id = Some(TokensStorage::approve(operator, &token)?);
-                } else {
-                    tx_id = Some(TokensStorage::revoke_approval(operator, &token)?);
-                }
+    /// Cancel the auction of the token. Caller must be the seller and the auction must have no bids.
+    pub fn cancel_auction(token_identifier: TokenIdentifier) -> Result<(), NftError> {
+        match AuctionsStorage::get(&token_identifier) {
+            None => Err(NftError::Other("token not under auction".to_string())),
+            Some(auction) if auction.seller != caller() => Err(NftError::UnauthorizedOwner),
+            Some(auction) if !auction.bids.is_empty() => {
+                Err(NftError::Other("auction has bids".to_string()))
             }
-            if let Some(tx_id) = tx_id {
-                Ok(tx_id)
-            } else {
-                Err(NftError::TokenNotFound)
+            Some(_) => {
+                AuctionsStorage::remove(&token_identifier);
+                Self::schedule_global_timer();
+                Ok(())
             }
-        } else {
-            Err(NftError::Other("Not implemented".to_string()))
         }
     }
 
-    /// Returns true if the given operator is an approved operator for all the tokens owned by the caller through the use of the setApprovalForAll method, returns false otherwise.
-    /// Interface: approval
-    fn dip721_is_approved_for_all(owner: Principal, operator: Principal) -> Result<bool, NftError> {
-        if Configuration::has_interface(SupportedInterface::Approval) {
-            for token in Self::dip721_owner_token_identifiers(owner)? {
-                let token = TokensStorage::get_token(&token)?;
-                if token.operator != Some(operator) {
-                    return Ok(false);
+    /// Bid on the auction of the token, escrowing the bid with an ICRC-2 `transfer_from` from the caller to the canister.
+    ///
+    /// The caller must have approved the canister to spend the bid plus the ledger fee.
+    /// English auction bids are escrowed until the auction is settled, when outbid bidders are refunded
+    /// minus the ledger fee of the refund.
+    /// Dutch auctions are won straight away by the first bid covering the current price, which is the amount paid.
+    pub async fn bid(token_identifier: TokenIdentifier, amount: Nat) -> Result<(), NftError> {
+        let bidder = caller();
+        let auction = AuctionsStorage::get(&token_identifier)
+            .ok_or_else(|| NftError::Other("token not under auction".to_string()))?;
+        let now = crate::utils::time();
+        Self::check_bid(&auction, bidder, &amount, now)?;
+        let amount = match auction.kind {
+            AuctionKind::English { .. } => amount,
+            AuctionKind::Dutch { .. } => auction.min_bid(now),
+        };
+        let ledger = auction.ledger;
+        Ledger::transfer_from(ledger, bidder, crate::utils::id(), amount.clone()).await?;
+
+        // the auction may have been outbid or won while awaiting the ledger
+        let now = crate::utils::time();
+        let auction = AuctionsStorage::get(&token_identifier)
+            .ok_or_else(|| NftError::Other("token not under auction".to_string()))
+            .and_then(|auction| Self::check_bid(&auction, bidder, &amount, now).map(|_| auction));
+        let mut auction = match auction {
+            Ok(auction) => auction,
+            Err(err) => {
+                // refund the bidder
+                Marketplace::refund(ledger, bidder, amount).await;
+                return Err(err);
+            }
+        };
+
+        auction.bids.push(Bid {
+            bidder,
+            amount,
+            placed_at: now,
+        });
+        match auction.kind {
+            AuctionKind::English { extension, .. } => {
+                if auction.ends_at.saturating_sub(now) < extension {
+                    auction.ends_at = now + extension;
                 }
+                AuctionsStorage::insert(&token_identifier, auction);
             }
+            AuctionKind::Dutch { .. } => {
+                AuctionsStorage::remove(&token_identifier);
+                Self::settle_auction(token_identifier, auction).await;
+            }
+        }
+        Self::schedule_global_timer();
 
-            Ok(true)
+        Ok(())
+    }
+
+    fn check_bid(
+        auction: &Auction,
+        bidder: Principal,
+        amount: &Nat,
+        now: u64,
+    ) -> Result<(), NftError> {
+        if auction.seller == bidder {
+            Err(NftError::SelfTransfer)
+        } else if auction.has_ended(now) {
+            Err(NftError::Other("auction ended".to_string()))
+        } else if amount < &auction.min_bid(now) {
+            Err(NftError::Other("bid too low".to_string()))
         } else {
-            Err(NftError::Other("Not implemented".to_string()))
+            Ok(())
         }
     }
 
-    /// Sends the callers nft token_identifier to `to`` and returns a nat that represents a
-    /// transaction id that can be used at the transaction method.
-    async fn dip721_transfer(
-        to: Principal,
-        token_identifier: TokenIdentifier,
-    ) -> Result<Nat, NftError> {
-        Self::dip721_transfer_from(caller(), to, token_identifier).await
+    /// Settle the auctions ended by now
+    pub async fn settle_auctions() {
+        let now = crate::utils::time();
+        let ended = AuctionsStorage::ended(now)
+            .into_iter()
+            .filter_map(|token_identifier| {
+                AuctionsStorage::remove(&token_identifier)
+                    .map(|auction| (token_identifier, auction))
+            })
+            .collect::<Vec<_>>();
+        Self::schedule_global_timer();
+
+        for (token_identifier, auction) in ended {
+            Self::settle_auction(token_identifier, auction).await;
+        }
+    }
+
+    /// Transfer the token to the highest bidder, split the winning bid and refund the outbid bidders.
+    ///
+    /// The auction must have already been removed, so that the token is unlocked.
+    async fn settle_auction(token_identifier: TokenIdentifier, mut auction: Auction) {
+        let Some(winner) = auction.bids.pop() else {
+            return;
+        };
+        let mut refunds = auction.bids;
+        if TokensStorage::transfer(DEFAULT_COLLECTION_ID, &token_identifier, winner.bidder).is_ok()
+        {
+            let split = Marketplace::split(
+                &winner.amount,
+                auction.seller,
+                Self::royalty(&token_identifier).as_ref(),
+                Configuration::get_marketplace_settings()
+                    .platform_fee
+                    .as_ref(),
+            );
+            Marketplace::pay(auction.ledger, split).await;
+        } else {
+            refunds.push(winner);
+        }
+
+        for bid in refunds {
+            Marketplace::refund(auction.ledger, bid.bidder, bid.amount).await;
+        }
+    }
+
+    /// Returns an offer by id
+    pub fn get_offer(id: u64) -> Option<Offer> {
+        OffersStorage::get(id)
+    }
+
+    /// Returns the offers made on the token, by id
+    pub fn get_token_offers(token_identifier: TokenIdentifier) -> Vec<(u64, Offer)> {
+        OffersStorage::token_offers(&token_identifier)
+    }
+
+    /// Offer `amount` on `ledger` for the token until `expires_at`, escrowing it with an ICRC-2 `transfer_from`
+    /// from the caller to the canister.
+    ///
+    /// The caller must have approved the canister to spend the amount plus the ledger fee.
+    /// The offer is refunded minus the ledger fee of the refund once expired, or when the token is burned.
+    ///
+    /// Returns the offer id
+    pub async fn make_offer(
+        token_identifier: TokenIdentifier,
+        amount: Nat,
+        expires_at: u64,
+        ledger: Principal,
+    ) -> Result<u64, NftError> {
+        let bidder = caller();
+        Self::check_offer(&token_identifier, bidder)?;
+        Self::check_ledger(ledger)?;
+        if amount == 0_u64 {
+            return Err(NftError::Other("amount must be positive".to_string()));
+        }
+        if expires_at <= crate::utils::time() {
+            return Err(NftError::Other(
+                "offer must expire in the future".to_string(),
+            ));
+        }
+        Ledger::transfer_from(ledger, bidder, crate::utils::id(), amount.clone()).await?;
+
+        // the token may have been burned while awaiting the ledger
+        if let Err(err) = Self::check_offer(&token_identifier, bidder) {
+            Marketplace::refund(ledger, bidder, amount).await;
+            return Err(err);
+        }
+        let id = OffersStorage::insert(Offer {
+            token_identifier,
+            bidder,
+            ledger,
+            amount,
+            created_at: crate::utils::time(),
+            expires_at,
+        });
+        Self::schedule_global_timer();
+
+        Ok(id)
+    }
+
+    fn check_offer(token_identifier: &TokenIdentifier, bidder: Principal) -> Result<(), NftError> {
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, token_identifier)?;
+        if token.is_burned {
+            Err(NftError::ExistedNFT)
+        } else if token.owner == Some(bidder) {
+            Err(NftError::SelfTransfer)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Withdraw an offer, refunding it. Caller must be the bidder.
+    pub async fn withdraw_offer(id: u64) -> Result<(), NftError> {
+        match OffersStorage::get(id) {
+            None => Err(NftError::Other("offer not found".to_string())),
+            Some(offer) if offer.bidder != caller() => Err(NftError::UnauthorizedOperator),
+            Some(offer) => {
+                OffersStorage::remove(id);
+                Self::schedule_global_timer();
+                Marketplace::refund(offer.ledger, offer.bidder, offer.amount).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Accept an offer, transferring the token to the bidder and splitting the escrowed amount between
+    /// the platform, the royalty recipients and the caller. Caller must be the owner of the token.
+    ///
+    /// Returns the transfer transaction id
+    pub async fn accept_offer(id: u64) -> Result<Nat, NftError> {
+        let seller = caller();
+        let offer =
+            OffersStorage::get(id).ok_or_else(|| NftError::Other("offer not found".to_string()))?;
+        if offer.expires_at <= crate::utils::time() {
+            return Err(NftError::Other("offer expired".to_string()));
+        }
+        if !Self::is_owned_by(&offer.token_identifier, seller) {
+            return Err(NftError::UnauthorizedOwner);
+        }
+
+        let tx_id =
+            TokensStorage::transfer(DEFAULT_COLLECTION_ID, &offer.token_identifier, offer.bidder)?;
+        OffersStorage::remove(id);
+        Self::schedule_global_timer();
+
+        let split = Marketplace::split(
+            &offer.amount,
+            seller,
+            Self::royalty(&offer.token_identifier).as_ref(),
+            Configuration::get_marketplace_settings()
+                .platform_fee
+                .as_ref(),
+        );
+        Marketplace::pay(offer.ledger, split).await;
+
+        Ok(tx_id)
+    }
+
+    /// Refund the offers expired by now
+    pub async fn expire_offers() {
+        let expired = OffersStorage::remove_expired(crate::utils::time());
+        Self::schedule_global_timer();
+
+        for offer in expired {
+            Marketplace::refund(offer.ledger, offer.bidder, offer.amount).await;
+        }
+    }
+
+    /// Returns a swap by id
+    pub fn get_swap(id: u64) -> Option<Swap> {
+        SwapsStorage::get(id)
+    }
+
+    /// Returns the swaps proposed by or to the principal, by id
+    pub fn get_swaps(principal: Principal) -> Vec<(u64, Swap)> {
+        SwapsStorage::swaps_of(principal)
+    }
+
+    /// Propose to swap the offered tokens of the caller for the requested tokens of the counterparty,
+    /// plus an optional payment from the counterparty.
+    /// The offered tokens are delisted and locked until the swap is accepted, cancelled or expired.
+    /// Caller must be the owner of the offered tokens.
+    ///
+    /// Returns the swap id
+    pub fn propose_swap(args: ProposeSwapArgs) -> Result<u64, NftError> {
+        let proposer = caller();
+        if args.counterparty == proposer {
+            return Err(NftError::SelfTransfer);
+        }
+        if args.offered.is_empty() || args.requested.is_empty() {
+            return Err(NftError::Other(
+                "swap must offer and request tokens".to_string(),
+            ));
+        }
+        let mut tokens = BTreeSet::new();
+        if !args
+            .offered
+            .iter()
+            .chain(args.requested.iter())
+            .all(|token_identifier| tokens.insert(token_identifier))
+        {
+            return Err(NftError::Other("token is swapped twice".to_string()));
+        }
+        if let Some(payment) = &args.payment {
+            Self::check_ledger(payment.ledger)?;
+            if payment.amount == 0_u64 {
+                return Err(NftError::Other("amount must be positive".to_string()));
+            }
+        }
+        if args.expires_at <= crate::utils::time() {
+            return Err(NftError::Other(
+                "swap must expire in the future".to_string(),
+            ));
+        }
+        let swap = Swap {
+            proposer,
+            counterparty: args.counterparty,
+            offered: args.offered,
+            requested: args.requested,
+            payment: args.payment,
+            created_at: crate::utils::time(),
+            expires_at: args.expires_at,
+        };
+        Self::check_swap(None, &swap)?;
+
+        for token_identifier in &swap.offered {
+            ListingsStorage::remove(token_identifier);
+        }
+        let id = SwapsStorage::insert(swap);
+        Self::schedule_global_timer();
+
+        Ok(id)
+    }
+
+    /// Checks both parties still own the tokens of the swap, and that they can be transferred;
+    /// the tokens locked by the swap `id` itself, if any, are not checked against the locks
+    fn check_swap(id: Option<u64>, swap: &Swap) -> Result<(), NftError> {
+        for (owner, tokens) in [
+            (swap.proposer, &swap.offered),
+            (swap.counterparty, &swap.requested),
+        ] {
+            for token_identifier in tokens {
+                if !Self::is_owned_by(token_identifier, owner) {
+                    return Err(NftError::UnauthorizedOwner);
+                }
+                if id.is_none() || SwapsStorage::locked_by(token_identifier) != id {
+                    TokensStorage::check_transferable(DEFAULT_COLLECTION_ID, token_identifier)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a swap, unlocking the offered tokens. Caller must be the proposer or the counterparty.
+    pub fn cancel_swap(id: u64) -> Result<(), NftError> {
+        let caller = caller();
+        match SwapsStorage::get(id) {
+            None => Err(NftError::Other("swap not found".to_string())),
+            Some(swap) if swap.proposer != caller && swap.counterparty != caller => {
+                Err(NftError::UnauthorizedOperator)
+            }
+            Some(_) => {
+                SwapsStorage::remove(id);
+                Self::schedule_global_timer();
+                Ok(())
+            }
+        }
+    }
+
+    /// Accept a swap, paying its payment, if any, with an ICRC-2 `transfer_from` from the caller to the canister.
+    ///
+    /// `offered` and `payment` are the terms the caller expects: the swap is rejected if they don't match its own.
+    /// The caller must have approved the canister to spend the payment plus the ledger fee.
+    /// Once paid, all the tokens are transferred at once and the payment is paid out to the proposer.
+    /// Caller must be the counterparty.
+    ///
+    /// Returns the id of the transaction grouping the transfers of the swap
+    pub async fn accept_swap(
+        id: u64,
+        offered: Vec<TokenIdentifier>,
+        payment: Option<SwapPayment>,
+    ) -> Result<Nat, NftError> {
+        let counterparty = caller();
+        let _guard = CallerGuard::new(counterparty)?;
+        let swap =
+            SwapsStorage::get(id).ok_or_else(|| NftError::Other("swap not found".to_string()))?;
+        if swap.counterparty != counterparty {
+            return Err(NftError::UnauthorizedOperator);
+        }
+        if swap.offered != offered || swap.payment != payment {
+            return Err(NftError::Other(
+                "swap doesn't match the expected terms".to_string(),
+            ));
+        }
+        if swap.expires_at <= crate::utils::time() {
+            return Err(NftError::Other("swap expired".to_string()));
+        }
+        Self::check_swap(Some(id), &swap)?;
+
+        // the requested tokens are locked too while awaiting the ledger
+        SwapsStorage::lock(id, &swap.requested);
+        let paid = match &swap.payment {
+            None => Ok(None),
+            Some(payment) => match Ledger::fee(payment.ledger).await {
+                Ok(fee) => Ledger::transfer_from(
+                    payment.ledger,
+                    counterparty,
+                    crate::utils::id(),
+                    payment.amount.clone(),
+                )
+                .await
+                .map(|_| Some(fee)),
+                Err(err) => Err(err),
+            },
+        };
+        SwapsStorage::unlock(&swap.requested);
+        let ledger_fee = paid?;
+
+        // the swap may have been cancelled or expired while awaiting the ledger
+        let checked = match SwapsStorage::remove(id) {
+            Some(swap) => Self::check_swap(None, &swap),
+            None => Err(NftError::Other("swap not found".to_string())),
+        };
+        Self::schedule_global_timer();
+        if let Err(err) = checked {
+            // refund the counterparty
+            if let Some(payment) = &swap.payment {
+                Marketplace::refund(payment.ledger, counterparty, payment.amount.clone()).await;
+            }
+            return Err(err);
+        }
+
+        let transfers = swap
+            .offered
+            .iter()
+            .map(|token_identifier| (token_identifier, swap.counterparty))
+            .chain(
+                swap.requested
+                    .iter()
+                    .map(|token_identifier| (token_identifier, swap.proposer)),
+            );
+        let tx_ids = transfers
+            .map(|(token_identifier, to)| {
+                // every transfer has been checked, so a failure here must roll back the whole swap
+                TokensStorage::transfer(DEFAULT_COLLECTION_ID, token_identifier, to)
+                    .unwrap_or_else(|err| ic_cdk::trap(&format!("swap failed: {err:?}")))
+            })
+            .collect::<Vec<_>>();
+        let tx_id = TxHistory::register_swap(id, &tx_ids);
+
+        if let (Some(payment), Some(fee)) = (&swap.payment, &ledger_fee) {
+            Marketplace::payout(
+                payment.ledger,
+                fee,
+                vec![(swap.proposer, payment.amount.clone())],
+            )
+            .await;
+        }
+
+        Ok(tx_id)
+    }
+
+    /// Remove the swaps expired by now, unlocking their offered tokens
+    pub fn expire_swaps() {
+        SwapsStorage::remove_expired(crate::utils::time());
+        Self::schedule_global_timer();
+    }
+
+    /// Migrate a batch of the tokens stored by previous versions or give them a batch of the revealed metadata,
+    /// settle the ended auctions, refund the expired offers and remove the expired swaps; called by the canister
+    /// global timer
+    pub async fn on_global_timer() {
+        // the tokens stored by previous versions are migrated, then revealed, a batch at a time
+        if TokensStorage::has_legacy_tokens() {
+            TokensStorage::migrate_legacy_tokens();
+            Self::schedule_global_timer();
+        } else if TokensStorage::has_owner_index_to_migrate() {
+            TokensStorage::migrate_owner_index();
+            Self::schedule_global_timer();
+        } else if RevealStorage::is_pending() {
+            Self::reveal_batch();
+            Self::schedule_global_timer();
+        }
+        if Random::next_reseed_at() <= crate::utils::time() {
+            if let Err(err) = Random::reseed().await {
+                ic_cdk::println!("failed to reseed the CSPRNG: {err:?}");
+            }
+        }
+        Self::expire_swaps();
+        Self::settle_auctions().await;
+        Self::expire_offers().await;
+    }
+
+    /// Schedule the global timer at the first auction end, offer or swap expiration or CSPRNG reseed,
+    /// or straight away while tokens stored by previous versions are left to migrate or revealed metadata to give
+    fn schedule_global_timer() {
+        let next = [
+            (TokensStorage::has_legacy_tokens()
+                || TokensStorage::has_owner_index_to_migrate()
+                || RevealStorage::is_pending())
+            .then(crate::utils::time),
+            AuctionsStorage::next_end(),
+            OffersStorage::next_expiration(),
+            SwapsStorage::next_expiration(),
+            Some(Random::next_reseed_at()),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        crate::utils::set_global_timer(next.unwrap_or_default());
+    }
+
+    /// Returns the sale proceeds owed to `owner` on the ledger, whose payout failed
+    pub fn get_proceeds(ledger: Principal, owner: Principal) -> Nat {
+        ProceedsStorage::get(ledger, owner)
+    }
+
+    /// Withdraw the sale proceeds owed to the caller on the ledger; the ledger fee is taken from the amount.
+    ///
+    /// Returns the ledger block index
+    pub async fn withdraw_proceeds(ledger: Principal) -> Result<Nat, NftError> {
+        let owner = caller();
+        let amount = ProceedsStorage::take(ledger, owner);
+        let result = match Ledger::fee(ledger).await {
+            Ok(fee) if amount > fee => Ledger::transfer(ledger, owner, amount.clone() - fee).await,
+            Ok(_) => Err(NftError::Other(
+                "proceeds don't cover the ledger fee".to_string(),
+            )),
+            Err(err) => Err(err),
+        };
+        if result.is_err() {
+            ProceedsStorage::credit(ledger, owner, amount);
+        }
+
+        result
+    }
+
+    /// Returns the public sale of newly minted tokens, if any
+    pub fn get_mint_sale() -> Option<MintSale> {
+        Configuration::get_mint_sale()
+    }
+
+    /// Sets or closes the public sale of newly minted tokens.
+    /// The tokens already minted by the sale still count towards its allocation and the wallet limits.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_mint_sale(sale: Option<MintSale>) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        if let Some(sale) = &sale {
+            if sale.starts_at >= sale.ends_at {
+                return Err(NftError::Other("sale must end after it starts".to_string()));
+            }
+            if sale.allocation == 0 || sale.wallet_limit == 0 {
+                return Err(NftError::Other(
+                    "sale allocation and wallet limit must be positive".to_string(),
+                ));
+            }
+        }
+        Configuration::set_mint_sale(sale);
+
+        Ok(())
+    }
+
+    /// Returns the amount of tokens minted by the public sale
+    pub fn get_sale_minted() -> u64 {
+        SaleStorage::minted()
+    }
+
+    /// Returns the amount of tokens minted by `owner` in the public sale
+    pub fn get_sale_minted_by(owner: Principal) -> u64 {
+        SaleStorage::minted_by(owner)
+    }
+
+    /// Mint `quantity` tokens to the caller, paying the sale price with an ICRC-2 `transfer_from`
+    /// from the caller to the canister, which forwards it to the sale treasury once the tokens are minted.
+    ///
+    /// The caller must have approved the canister to spend the price of the tokens plus the ledger fee.
+    /// If the tokens can't be minted anymore once paid, the price is refunded minus the ledger fee of the refund.
+    /// Returns the identifiers of the minted tokens.
+    pub async fn public_mint(quantity: u64) -> Result<Vec<TokenIdentifier>, NftError> {
+        let buyer = caller();
+        let _guard = CallerGuard::new(buyer)?;
+        let sale = Configuration::get_mint_sale()
+            .filter(|sale| sale.is_open(crate::utils::time()))
+            .ok_or_else(|| NftError::Other("sale is not open".to_string()))?;
+        if !Configuration::has_interface(SupportedInterface::Mint) {
+            return Err(NftError::Other("Not implemented".to_string()));
+        }
+        if quantity == 0 {
+            return Err(NftError::Other("quantity must be positive".to_string()));
+        }
+
+        // the supply is reserved while awaiting the ledger, and released if the payment or the mint fails
+        SaleStorage::reserve(&sale, buyer, quantity)?;
+        let result = Self::mint_paid(
+            buyer,
+            quantity,
+            sale.ledger,
+            sale.treasury,
+            sale.price.clone() * quantity,
+        )
+        .await;
+        if result.is_err() {
+            SaleStorage::release(buyer, quantity);
+        }
+
+        result
+    }
+
+    /// Returns the allowlist minting phases
+    pub fn get_allowlist_phases() -> Vec<(u32, AllowlistPhase)> {
+        Configuration::get_allowlist_phases()
+    }
+
+    /// Sets or removes the allowlist minting phase `id`.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_allowlist_phase(id: u32, phase: Option<AllowlistPhase>) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        if let Some(phase) = &phase {
+            if phase.merkle_root.len() != 32 {
+                return Err(NftError::Other("invalid merkle root".to_string()));
+            }
+            if phase.starts_at >= phase.ends_at {
+                return Err(NftError::Other(
+                    "phase must end after it starts".to_string(),
+                ));
+            }
+        }
+        Configuration::set_allowlist_phase(id, phase);
+
+        Ok(())
+    }
+
+    /// Returns the amount of tokens minted by `owner` in the allowlist phase
+    pub fn get_allowlist_minted(phase_id: u32, owner: Principal) -> u64 {
+        AllowlistStorage::minted(phase_id, owner)
+    }
+
+    /// Mint `quantity` tokens to the caller in the allowlist phase, paying the phase price with an ICRC-2
+    /// `transfer_from` from the caller to the canister, which forwards it to the phase treasury once the tokens
+    /// are minted.
+    ///
+    /// `proof` proves that `(caller, max_quantity)` belongs to the allowlist of the phase;
+    /// the caller can mint up to `max_quantity` tokens in the phase.
+    /// Returns the identifiers of the minted tokens.
+    pub async fn allowlist_mint(
+        phase_id: u32,
+        quantity: u64,
+        max_quantity: u64,
+        proof: Vec<ByteBuf>,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        let minter = caller();
+        let _guard = CallerGuard::new(minter)?;
+        let phase = Configuration::get_allowlist_phase(phase_id)
+            .filter(|phase| phase.is_open(crate::utils::time()))
+            .ok_or_else(|| NftError::Other("phase is not open".to_string()))?;
+        if !Configuration::has_interface(SupportedInterface::Mint) {
+            return Err(NftError::Other("Not implemented".to_string()));
+        }
+        if quantity == 0 {
+            return Err(NftError::Other("quantity must be positive".to_string()));
+        }
+        if !Merkle::verify(
+            &phase.merkle_root,
+            Merkle::leaf(minter, max_quantity),
+            &proof,
+        ) {
+            return Err(NftError::Other("invalid allowlist proof".to_string()));
+        }
+
+        // the quantity is reserved while awaiting the ledger, and released if the payment or the mint fails
+        AllowlistStorage::reserve(phase_id, minter, quantity, max_quantity)?;
+        let result = Self::mint_paid(
+            minter,
+            quantity,
+            phase.ledger,
+            phase.treasury,
+            phase.price.clone() * quantity,
+        )
+        .await;
+        if result.is_err() {
+            AllowlistStorage::release(phase_id, minter, quantity);
+        }
+
+        result
+    }
+
+    /// Mint `quantity` tokens to `to` once `price` is paid, then forward it to `treasury`.
+    ///
+    /// The identifiers are picked and checked before charging `to`, and reserved while awaiting the ledger.
+    /// If the tokens can't be minted anymore once paid, nothing is minted and the price is refunded.
+    async fn mint_paid(
+        to: Principal,
+        quantity: u64,
+        ledger: Principal,
+        treasury: Principal,
+        price: Nat,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        let (guard, pool_identifiers) = Self::reserve_mint(quantity)?;
+        if price > 0_u64 {
+            if let Err(err) =
+                Ledger::transfer_from(ledger, to, crate::utils::id(), price.clone()).await
+            {
+                drop(guard);
+                Self::put_back_pool_identifiers(pool_identifiers);
+                return Err(err);
+            }
+        }
+
+        // the collection may have changed while awaiting the ledger
+        let token_identifiers = guard.release();
+        if let Err(err) = Self::check_mint_next(&token_identifiers) {
+            Self::put_back_pool_identifiers(pool_identifiers);
+            if price > 0_u64 {
+                Marketplace::refund(ledger, to, price).await;
+            }
+            return Err(err);
+        }
+        for token_identifier in &token_identifiers {
+            // every token has been checked, so a failure here must roll back the whole mint
+            Self::mint(DEFAULT_COLLECTION_ID, to, token_identifier.clone(), vec![])
+                .unwrap_or_else(|err| ic_cdk::trap(&format!("mint failed: {err:?}")));
+        }
+        if price > 0_u64 {
+            Marketplace::pay(ledger, vec![(treasury, price)]).await;
+        }
+
+        Ok(token_identifiers)
+    }
+
+    /// Pick the identifiers of `quantity` tokens to mint in the default collection and reserve them,
+    /// taking them from the random pool if any, or the next free identifiers otherwise.
+    ///
+    /// Returns the reservation and the identifiers taken from the random pool, if any
+    fn reserve_mint(quantity: u64) -> Result<(MintGuard, Option<Vec<u64>>), NftError> {
+        let pool_identifiers = Self::take_pool_identifiers(quantity)?;
+        let token_identifiers = match &pool_identifiers {
+            Some(identifiers) => identifiers
+                .iter()
+                .copied()
+                .map(TokenIdentifier::from)
+                .collect(),
+            None => {
+                // skip the identifiers reserved by the mints in progress
+                let mut token_identifier = TokensStorage::next_token_id(DEFAULT_COLLECTION_ID);
+                let mut token_identifiers = Vec::with_capacity(quantity as usize);
+                while (token_identifiers.len() as u64) < quantity {
+                    if !MintGuard::is_reserved(&token_identifier) {
+                        token_identifiers.push(token_identifier.clone());
+                    }
+                    token_identifier += 1_u64;
+                }
+                token_identifiers
+            }
+        };
+
+        match Self::check_mint_next(&token_identifiers)
+            .and_then(|_| MintGuard::new(token_identifiers))
+        {
+            Ok(guard) => Ok((guard, pool_identifiers)),
+            Err(err) => {
+                Self::put_back_pool_identifiers(pool_identifiers);
+                Err(err)
+            }
+        }
+    }
+
+    /// Check that the tokens can be minted in the default collection, along with the reserved ones
+    fn check_mint_next(token_identifiers: &[TokenIdentifier]) -> Result<(), NftError> {
+        if !Configuration::has_interface(SupportedInterface::Mint) {
+            return Err(NftError::Other("Not implemented".to_string()));
+        }
+        if Configuration::get_collection_max_supply(DEFAULT_COLLECTION_ID).is_some_and(
+            |max_supply| {
+                TokensStorage::total_supply(DEFAULT_COLLECTION_ID)
+                    + MintGuard::reserved()
+                    + token_identifiers.len() as u64
+                    > max_supply
+            },
+        ) {
+            return Err(NftError::Other("collection supply cap reached".to_string()));
+        }
+        if token_identifiers.iter().any(|token_identifier| {
+            MintGuard::is_reserved(token_identifier)
+                || TokensStorage::get_token(DEFAULT_COLLECTION_ID, token_identifier).is_ok()
+        }) {
+            return Err(NftError::ExistedNFT);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the pool of the token identifiers minted in random order, if any
+    pub fn get_random_pool() -> Option<RandomPool> {
+        RandomPoolStorage::get()
+    }
+
+    /// Sets the pool of the token identifiers `1..=size` the public and allowlist mints pick from at random.
+    /// It can be replaced until an identifier is picked from it; tokens minted by custodians can't use the identifiers left in it.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_random_pool(size: u64) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        RandomPoolStorage::init(size)
+    }
+
+    /// Fill `buf` with random bytes from the CSPRNG, failing if it's not seeded yet
+    pub fn random_fill(buf: &mut [u8]) -> Result<(), NftError> {
+        Random::fill(buf)
+    }
+
+    /// Take `quantity` random identifiers out of the random pool, if any
+    fn take_pool_identifiers(quantity: u64) -> Result<Option<Vec<u64>>, NftError> {
+        let Some(pool) = RandomPoolStorage::get() else {
+            return Ok(None);
+        };
+        if pool.remaining < quantity {
+            return Err(NftError::Other(
+                "not enough identifiers left in the pool".to_string(),
+            ));
+        }
+        let mut identifiers = Vec::with_capacity(quantity as usize);
+        for remaining in (pool.remaining - quantity + 1..=pool.remaining).rev() {
+            match Random::below(remaining).and_then(RandomPoolStorage::take) {
+                Ok(identifier) => identifiers.push(identifier),
+                Err(err) => {
+                    Self::put_back_pool_identifiers(Some(identifiers));
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(Some(identifiers))
+    }
+
+    /// Put back the identifiers taken out of the random pool
+    fn put_back_pool_identifiers(identifiers: Option<Vec<u64>>) {
+        for identifier in identifiers.into_iter().flatten().rev() {
+            RandomPoolStorage::put_back(identifier);
+        }
+    }
+
+    /// Count the call to `method` in the metrics
+    pub fn record_call(method: &'static str) {
+        Metrics::record_call(method);
+    }
+
+    /// Count the call to `method` in the metrics, along with its error if any
+    pub fn metered<T>(method: &'static str, result: Result<T, NftError>) -> Result<T, NftError> {
+        Metrics::record_call(method);
+        if let Err(err) = &result {
+            Metrics::record_error(method, err);
+        }
+
+        result
+    }
+
+    /// Returns the calls count by method since the last upgrade, as the counters live in the heap
+    pub fn call_metrics() -> Vec<(&'static str, u64)> {
+        Metrics::calls()
+    }
+
+    /// Returns the errors count by method and error variant since the last upgrade, as the counters live in the heap
+    pub fn error_metrics() -> Vec<(&'static str, &'static str, u64)> {
+        Metrics::errors()
+    }
+
+    /// Returns the stable memory pages used by each memory of the canister
+    pub fn memory_pages() -> Vec<(&'static str, u64)> {
+        memory::MEMORIES
+            .iter()
+            .map(|(name, memory_id)| {
+                let pages = memory::MEMORY_MANAGER.with(|mm| mm.get(*memory_id).size());
+                (*name, pages)
+            })
+            .collect()
+    }
+
+    /// Returns the amount of burned tokens
+    pub fn total_burned() -> u64 {
+        TokensStorage::total_burned(DEFAULT_COLLECTION_ID)
+    }
+
+    /// Returns the origins allowed to call the HTTP interface from a browser
+    pub fn get_allowed_origins() -> Vec<String> {
+        Configuration::get_allowed_origins()
+    }
+
+    /// Sets the origins allowed to call the HTTP interface from a browser; `*` allows any origin.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_allowed_origins(origins: Vec<String>) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_allowed_origins(origins);
+    }
+
+    /// Returns whether the origin is allowed to call the HTTP interface from a browser
+    pub fn is_allowed_origin(origin: &str) -> bool {
+        Configuration::is_allowed_origin(origin)
+    }
+
+    /// Returns the principals of the keys allowed to mint with signed HTTP requests
+    pub fn get_http_minters() -> Vec<Principal> {
+        Configuration::get_http_minters()
+    }
+
+    /// Sets the principals of the keys allowed to mint with signed HTTP requests.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_http_minters(minters: Vec<Principal>) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_http_minters(&minters);
+    }
+
+    /// Verify a payload signed outside of the IC and executes `f` on behalf of the signer.
+    ///
+    /// The payload must be addressed to this canister, not expired and its nonce must have never been used
+    /// by the signer before. The nonce is consumed only if `f` succeeds.
+    pub fn execute_signed_payload<F>(
+        public_key: &[u8],
+        payload: &[u8],
+        signature: &[u8],
+        canister_id: Principal,
+        nonce: u64,
+        expires_at: u64,
+        f: F,
+    ) -> Result<Nat, NftError>
+    where
+        F: FnOnce(Principal) -> Result<Nat, NftError>,
+    {
+        let signer = Signature::verify(public_key, payload, signature)?;
+        if canister_id != crate::utils::id() {
+            return Err(NftError::Other(
+                "payload signed for another canister".to_string(),
+            ));
+        }
+        if expires_at < crate::utils::time() {
+            return Err(NftError::Other("payload expired".to_string()));
+        }
+        if NoncesStorage::is_used(signer, nonce) {
+            return Err(NftError::Other("nonce already used".to_string()));
+        }
+
+        let tx_id = f(signer)?;
+        NoncesStorage::use_nonce(signer, nonce, expires_at)?;
+
+        Ok(tx_id)
+    }
+
+    /// Transfer the signer's token to `to`; the signer must be the owner or the operator of the token
+    pub fn signed_transfer(
+        signer: Principal,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        Self::transfer_from(DEFAULT_COLLECTION_ID, signer, signer, to, token_identifier)
+    }
+
+    /// Mint a token on behalf of the signer, whose key must be an HTTP minter
+    pub fn signed_mint(
+        signer: Principal,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        if !Configuration::is_http_minter(signer) {
+            return Err(NftError::UnauthorizedOperator);
+        }
+        Self::mint(DEFAULT_COLLECTION_ID, to, token_identifier, properties)
+    }
+
+    /// Returns the principals of the keys allowed to sign mint vouchers
+    pub fn get_voucher_signers() -> Vec<Principal> {
+        Configuration::get_voucher_signers()
+    }
+
+    /// Sets the principals of the keys allowed to sign mint vouchers.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_voucher_signers(signers: Vec<Principal>) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_voucher_signers(&signers);
+    }
+
+    /// Mint the token of a voucher signed by a voucher signer with the DER encoded `public_key`.
+    ///
+    /// `properties` must match the properties hash of the voucher; the voucher nonce is consumed on success.
+    /// Returns the mint transaction id.
+    pub fn redeem_voucher(
+        voucher: MintVoucher,
+        properties: Vec<(String, GenericValue)>,
+        public_key: ByteBuf,
+        signature: ByteBuf,
+    ) -> Result<Nat, NftError> {
+        Self::execute_signed_payload(
+            &public_key,
+            &voucher.message(),
+            &signature,
+            voucher.canister_id,
+            voucher.nonce,
+            voucher.expires_at,
+            |signer| {
+                if !Configuration::is_voucher_signer(signer) {
+                    return Err(NftError::UnauthorizedOperator);
+                }
+                if Self::properties_hash(&properties).as_slice()
+                    != voucher.properties_hash.as_slice()
+                {
+                    return Err(NftError::Other(
+                        "properties don't match the voucher".to_string(),
+                    ));
+                }
+
+                Self::mint(
+                    DEFAULT_COLLECTION_ID,
+                    voucher.to,
+                    voucher.token_identifier,
+                    properties,
+                )
+            },
+        )
+    }
+
+    /// Sets the user of the token until `expires_at`, without transferring its ownership (ERC-4907).
+    /// An `expires_at` in the past removes the user; the user is also removed when the token is transferred or burned.
+    /// Caller must be the owner or the operator of the token.
+    ///
+    /// Returns the transaction id
+    pub fn set_user(
+        token_identifier: TokenIdentifier,
+        user: Principal,
+        expires_at: u64,
+    ) -> Result<Nat, NftError> {
+        Inspect::inspect_transfer_from(caller(), DEFAULT_COLLECTION_ID, &token_identifier)?;
+        if expires_at > crate::utils::time() {
+            let user = TokenUser { user, expires_at };
+            UsersStorage::insert(&token_identifier, user.clone());
+            Ok(TxHistory::register_set_user(&token_identifier, Some(&user)))
+        } else {
+            UsersStorage::remove(&token_identifier);
+            Ok(TxHistory::register_set_user(&token_identifier, None))
+        }
+    }
+
+    /// Returns the user of the token, if any and not expired
+    pub fn user_of(token_identifier: TokenIdentifier) -> Result<Option<Principal>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Ok(UsersStorage::get(&token_identifier, crate::utils::time()).map(|user| user.user))
+    }
+
+    /// Returns the time the user of the token expires at, or 0 if it has no user
+    pub fn user_expires(token_identifier: TokenIdentifier) -> Result<u64, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Ok(UsersStorage::get(&token_identifier, crate::utils::time())
+            .map(|user| user.expires_at)
+            .unwrap_or_default())
+    }
+
+    /// Returns the edition templates, by id
+    pub fn get_edition_templates() -> Vec<(u64, EditionTemplate)> {
+        EditionsStorage::templates()
+    }
+
+    /// Creates a template holding the properties shared by up to `max_editions` tokens, or unlimited if none.
+    /// Caller must be a custodian.
+    ///
+    /// Returns the template id
+    pub fn create_edition_template(
+        properties: Vec<(String, GenericValue)>,
+        max_editions: Option<u64>,
+    ) -> Result<u64, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        if max_editions == Some(0) {
+            return Err(NftError::Other("max editions must be positive".to_string()));
+        }
+
+        Ok(EditionsStorage::create_template(properties, max_editions))
+    }
+
+    /// Mints the next edition of the template to `to`, with an identifier taken from the random pool if any,
+    /// or the next identifier neither minted nor reserved by a mint in progress otherwise.
+    /// The token metadata merges the template properties with its own `properties`.
+    /// Caller must be a custodian.
+    ///
+    /// Returns the identifier of the minted token
+    pub fn mint_edition(
+        to: Principal,
+        template_id: u64,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<TokenIdentifier, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        let edition = EditionsStorage::next_edition(template_id)?;
+        // picked like the sale mints, skipping the identifiers they reserved
+        let (guard, pool_identifiers) = Self::reserve_mint(1)?;
+        let token_identifier = guard.release().pop().expect("one identifier is reserved");
+        if let Err(err) = Self::mint(
+            DEFAULT_COLLECTION_ID,
+            to,
+            token_identifier.clone(),
+            properties,
+        ) {
+            Self::put_back_pool_identifiers(pool_identifiers);
+            return Err(err);
+        }
+        EditionsStorage::insert_edition(&token_identifier, edition);
+
+        Ok(token_identifier)
+    }
+
+    /// Returns the amount of editions minted from the template
+    pub fn get_edition_count(template_id: u64) -> Result<u64, NftError> {
+        EditionsStorage::get_template(template_id)
+            .map(|template| template.minted)
+            .ok_or_else(|| NftError::Other("template not found".to_string()))
+    }
+
+    /// Returns the edition the token was minted as, if any
+    pub fn get_token_edition(
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<TokenEdition>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Ok(EditionsStorage::get_edition(&token_identifier))
+    }
+
+    /// Returns the collection with the provided id, the default one having id 0
+    pub fn get_collection(collection_id: CollectionId) -> Option<Collection> {
+        Configuration::get_collection(collection_id)
+    }
+
+    /// Returns all the collections hosted by the canister, starting from the default one
+    pub fn get_collections() -> Vec<(CollectionId, Collection)> {
+        Configuration::get_collections()
+    }
+
+    /// Creates a collection with its own configuration and tokens, beside the default one.
+    /// Its tokens can be minted, transferred, approved and burned; listings, auctions, offers, swaps, users,
+    /// editions and children only exist in the default collection.
+    /// Caller must be a custodian of the canister.
+    ///
+    /// Returns the collection id
+    pub fn create_collection(args: CreateCollectionArgs) -> Result<CollectionId, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        let logo = args
+            .logo
+            .map(|logo| Logo::from_base64(&logo))
+            .transpose()
+            .map_err(|_| NftError::Other("invalid logo".to_string()))?;
+
+        Ok(Configuration::create_collection(Collection {
+            name: args.name,
+            symbol: args.symbol,
+            logo,
+            custodians: args.custodians,
+            supported_interfaces: args.supported_interfaces,
+            max_supply: args.max_supply,
+            created_at: crate::utils::time(),
+        }))
+    }
+
+    /// Mints a token in the collection, up to its supply cap.
+    /// Caller must be a custodian of the collection.
+    pub fn collection_mint(
+        collection_id: CollectionId,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        if !Inspect::inspect_is_collection_custodian(caller(), collection_id) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Self::mint(collection_id, to, token_identifier, properties)
+    }
+
+    /// Transfers a token of the collection owned by the caller to `to`
+    pub fn collection_transfer(
+        collection_id: CollectionId,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        Self::transfer_from(collection_id, caller(), caller(), to, token_identifier)
+    }
+
+    /// Burns a token of the collection.
+    /// Caller must be the owner or the operator of the token.
+    pub fn collection_burn(
+        collection_id: CollectionId,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return Self::dip721_burn(token_identifier);
+        }
+        Inspect::inspect_is_owner_or_operator(caller(), collection_id, &token_identifier)?;
+        if Configuration::collection_has_interface(collection_id, SupportedInterface::Burn) {
+            TokensStorage::burn(collection_id, &token_identifier)
+        } else {
+            Err(NftError::Other("Not implemented".to_string()))
+        }
+    }
+
+    /// Returns the metadata of a token of the collection
+    pub fn collection_token_metadata(
+        collection_id: CollectionId,
+        token_identifier: TokenIdentifier,
+    ) -> Result<TokenMetadata, NftError> {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return Self::dip721_token_metadata(token_identifier);
+        }
+        TokensStorage::get_token(collection_id, &token_identifier)
+    }
+
+    /// Returns the owner of a token of the collection
+    pub fn collection_owner_of(
+        collection_id: CollectionId,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Principal>, NftError> {
+        TokensStorage::get_token(collection_id, &token_identifier).map(|token| token.owner)
+    }
+
+    /// Returns the identifiers of the tokens of the collection owned by `owner`
+    pub fn collection_owner_token_identifiers(
+        collection_id: CollectionId,
+        owner: Principal,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        match TokensStorage::tokens_by_owner(collection_id, owner) {
+            tokens if tokens.is_empty() => Err(NftError::OwnerNotFound),
+            tokens => Ok(tokens),
+        }
+    }
+
+    /// Returns the amount of tokens of the collection owned by `owner`
+    pub fn collection_balance_of(
+        collection_id: CollectionId,
+        owner: Principal,
+    ) -> Result<Nat, NftError> {
+        Self::collection_owner_token_identifiers(collection_id, owner)
+            .map(|tokens| tokens.len().into())
+    }
+
+    /// Returns the total supply of the collection, burned tokens included
+    pub fn collection_total_supply(collection_id: CollectionId) -> Result<Nat, NftError> {
+        Configuration::get_collection(collection_id)
+            .ok_or_else(|| NftError::Other("collection not found".to_string()))?;
+        Ok(TokensStorage::total_supply(collection_id).into())
+    }
+
+    /// Attaches the child token to the parent token, so that it's transferred along with it (ERC-998).
+    /// The child is delisted and can't be transferred, listed or auctioned until it's detached.
+    /// Caller must be the owner or the operator of the child, which must be owned by the root owner of the parent.
+    ///
+    /// Returns the transaction id
+    pub fn attach_child(parent: TokenIdentifier, child: TokenIdentifier) -> Result<Nat, NftError> {
+        let child_token = Inspect::inspect_transfer_from(caller(), DEFAULT_COLLECTION_ID, &child)?;
+        let parent_token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &parent)?;
+        if parent_token.is_burned {
+            return Err(NftError::TokenNotFound);
+        }
+        if Self::root_owner(&parent)? != child_token.owner {
+            return Err(NftError::UnauthorizedOwner);
+        }
+        TokensStorage::check_transferable(DEFAULT_COLLECTION_ID, &child)?;
+        ChildrenStorage::attach(&parent, &child)?;
+        ListingsStorage::remove(&child);
+
+        Ok(TxHistory::register_attach_child(&parent, &child))
+    }
+
+    /// Detaches the child token from the parent token.
+    /// Caller must be the owner or the operator of the root token of the parent.
+    ///
+    /// Returns the transaction id
+    pub fn detach_child(parent: TokenIdentifier, child: TokenIdentifier) -> Result<Nat, NftError> {
+        let root = ChildrenStorage::root_of(&parent);
+        Inspect::inspect_transfer_from(caller(), DEFAULT_COLLECTION_ID, &root)?;
+        ChildrenStorage::detach(&parent, &child)?;
+
+        Ok(TxHistory::register_detach_child(&parent, &child))
+    }
+
+    /// Returns the tokens attached to the token
+    pub fn get_token_children(
+        token_identifier: TokenIdentifier,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Ok(ChildrenStorage::children_of(&token_identifier))
+    }
+
+    /// Returns the owner of the root token the token is attached to, or of the token itself if not attached
+    pub fn root_owner_of(token_identifier: TokenIdentifier) -> Result<Option<Principal>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        Self::root_owner(&token_identifier)
+    }
+
+    fn root_owner(token_identifier: &TokenIdentifier) -> Result<Option<Principal>, NftError> {
+        let root = ChildrenStorage::root_of(token_identifier);
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &root).map(|token| token.owner)
+    }
+
+    /// Transfers all the tokens of the batch, each `(from, to, token_identifier)`, or none of them
+    /// if any of the transfers is not allowed.
+    /// Caller must be the owner or the operator of every token.
+    ///
+    /// Returns the id of the transaction grouping the batch and the transaction ids of its transfers, in order
+    pub fn transfer_batch(
+        transfers: Vec<(Principal, Principal, TokenIdentifier)>,
+    ) -> Result<TransferBatch, NftError> {
+        if transfers.is_empty() {
+            return Err(NftError::Other("batch is empty".to_string()));
+        }
+        let caller = caller();
+        for (index, (from, to, token_identifier)) in transfers.iter().enumerate() {
+            if transfers[..index]
+                .iter()
+                .any(|(_, _, other)| other == token_identifier)
+            {
+                return Err(NftError::Other(
+                    "token is transferred twice in the batch".to_string(),
+                ));
+            }
+            Self::inspect_transfer(DEFAULT_COLLECTION_ID, caller, *from, *to, token_identifier)?;
+            TokensStorage::check_transferable(DEFAULT_COLLECTION_ID, token_identifier)?;
+        }
+
+        let tx_ids = transfers
+            .into_iter()
+            .map(|(_, to, token_identifier)| {
+                // every transfer has been checked, so a failure here must roll back the whole batch
+                TokensStorage::transfer(DEFAULT_COLLECTION_ID, &token_identifier, to)
+                    .unwrap_or_else(|err| ic_cdk::trap(&format!("batch transfer failed: {err:?}")))
+            })
+            .collect::<Vec<_>>();
+        let batch_id = TxHistory::register_transfer_batch(&tx_ids);
+
+        Ok(TransferBatch { batch_id, tx_ids })
+    }
+
+    /// Returns the number of tokens held by the account
+    pub fn account_balance_of(account: Account) -> Result<Nat, NftError> {
+        Self::account_token_identifiers(account).map(|tokens| tokens.len().into())
+    }
+
+    /// Returns the identifiers of the tokens held by the account
+    pub fn account_token_identifiers(account: Account) -> Result<Vec<TokenIdentifier>, NftError> {
+        Ok(TokensStorage::tokens_by_account(
+            DEFAULT_COLLECTION_ID,
+            account.owner,
+            account.effective_subaccount()?,
+        ))
+    }
+
+    /// Returns the account holding the token, if not burned
+    pub fn account_of(token_identifier: TokenIdentifier) -> Result<Option<Account>, NftError> {
+        TokensStorage::account_of(DEFAULT_COLLECTION_ID, &token_identifier)
+    }
+
+    /// Transfers a token held by the `from_subaccount` of the caller to the account `to`.
+    /// The default subaccount is used if `from_subaccount` is none.
+    pub fn account_transfer(
+        from_subaccount: Option<ByteBuf>,
+        to: Account,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        let from = Account {
+            owner: caller(),
+            subaccount: from_subaccount,
+        };
+        let from_subaccount = from.effective_subaccount()?;
+        let to_subaccount = to.effective_subaccount()?;
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        if !TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &token_identifier,
+            from.owner,
+            from_subaccount,
+        ) {
+            return Err(NftError::UnauthorizedOwner);
+        }
+        if from.owner == to.owner && from_subaccount == to_subaccount {
+            return Err(NftError::SelfTransfer);
+        }
+
+        TokensStorage::transfer_to_account(
+            DEFAULT_COLLECTION_ID,
+            &token_identifier,
+            to.owner,
+            to_subaccount,
+        )
+    }
+
+    /// Mints a token to the account.
+    /// Caller must be a custodian of NFT canister.
+    pub fn account_mint(
+        to: Account,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Self::mint_to_account(
+            DEFAULT_COLLECTION_ID,
+            to.owner,
+            to.effective_subaccount()?,
+            token_identifier,
+            properties,
+        )
+    }
+
+    /// Returns the EXT token identifier of the token, if its identifier fits an EXT token index
+    pub fn ext_token_identifier_of(
+        token_identifier: TokenIdentifier,
+    ) -> Result<ExtTokenIdentifier, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        let index = token_identifier
+            .0
+            .to_u32()
+            .ok_or_else(|| NftError::Other("token identifier exceeds EXT indices".to_string()))?;
+
+        Ok(Ext::token_identifier(crate::utils::id(), index))
+    }
+
+    /// Returns the account identifier of the holder of the EXT token
+    pub fn ext_bearer(
+        token: ExtTokenIdentifier,
+    ) -> ExtResult<ExtAccountIdentifier, ExtCommonError> {
+        let account = Self::ext_token_identifier(&token).and_then(|token_identifier| {
+            TokensStorage::account_of(DEFAULT_COLLECTION_ID, &token_identifier)
+                .ok()
+                .flatten()
+        });
+
+        match account {
+            Some(account) => ExtResult::Ok(Self::ext_account_identifier(&account)),
+            None => ExtResult::Err(ExtCommonError::InvalidToken(token)),
+        }
+    }
+
+    /// Returns the account identifiers of the holders of the EXT tokens
+    pub fn ext_get_registry() -> Vec<(ExtTokenIndex, ExtAccountIdentifier)> {
+        Self::ext_holders()
+            .into_iter()
+            .map(|(index, account)| (index, Self::ext_account_identifier(&account)))
+            .collect()
+    }
+
+    /// Returns the EXT metadata of the tokens; the token properties are served by the DIP721 methods
+    pub fn ext_get_tokens() -> Vec<(ExtTokenIndex, ExtMetadata)> {
+        Self::ext_holders()
+            .into_iter()
+            .map(|(index, _)| (index, ExtMetadata::Nonfungible { metadata: None }))
+            .collect()
+    }
+
+    /// Returns the EXT tokens held by the account identifier.
+    /// Listings aren't reported, as their price isn't necessarily in ICP.
+    pub fn ext_tokens(account_identifier: ExtAccountIdentifier) -> ExtTokensResult {
+        let tokens = Self::ext_holders()
+            .into_iter()
+            .filter(|(_, account)| {
+                Self::ext_account_identifier(account).eq_ignore_ascii_case(&account_identifier)
+            })
+            .map(|(index, _)| (index, None, None))
+            .collect::<Vec<_>>();
+
+        if tokens.is_empty() {
+            ExtResult::Err(ExtCommonError::Other("No tokens".to_string()))
+        } else {
+            ExtResult::Ok(tokens)
+        }
+    }
+
+    /// Transfers the EXT token from the `subaccount` of the caller, which `from` must identify,
+    /// to the default subaccount of the principal `to`.
+    /// Recipients can't be account identifiers, since the account can't be recovered from its hash,
+    /// and notifications aren't supported.
+    ///
+    /// Returns the transferred amount
+    pub fn ext_transfer(request: ExtTransferRequest) -> ExtResult<Nat, ExtTransferError> {
+        Self::ext_transfer_token(request).into()
+    }
+
+    fn ext_transfer_token(request: ExtTransferRequest) -> Result<Nat, ExtTransferError> {
+        let from = Account {
+            owner: caller(),
+            subaccount: request.subaccount.clone(),
+        };
+        let from_subaccount = from
+            .effective_subaccount()
+            .map_err(|err| ExtTransferError::Other(err.to_string()))?;
+        let from_identifier = Ext::account_identifier(from.owner, from_subaccount);
+        let user_identifier = match &request.from {
+            ExtUser::Address(address) => address.to_lowercase(),
+            ExtUser::Principal(principal) => Ext::account_identifier(*principal, None),
+        };
+        if user_identifier != from_identifier {
+            return Err(ExtTransferError::Unauthorized(user_identifier));
+        }
+        let to = match request.to {
+            ExtUser::Principal(principal) => principal,
+            ExtUser::Address(_) => {
+                return Err(ExtTransferError::Other(
+                    "transfers to account identifiers are not supported".to_string(),
+                ))
+            }
+        };
+        if request.amount != 1_u64 {
+            return Err(ExtTransferError::Other("amount must be 1".to_string()));
+        }
+        if request.notify {
+            return Err(ExtTransferError::CannotNotify(Ext::account_identifier(
+                to, None,
+            )));
+        }
+        let token_identifier = Self::ext_token_identifier(&request.token)
+            .ok_or_else(|| ExtTransferError::InvalidToken(request.token.clone()))?;
+
+        Self::account_transfer(request.subaccount, Account::from(to), token_identifier).map_err(
+            |err| match err {
+                NftError::TokenNotFound => ExtTransferError::InvalidToken(request.token),
+                NftError::UnauthorizedOwner => ExtTransferError::Unauthorized(from_identifier),
+                err => ExtTransferError::Other(err.to_string()),
+            },
+        )?;
+
+        Ok(request.amount)
+    }
+
+    /// Returns the identifier of the token of the default collection at the EXT token index, if valid
+    fn ext_token_identifier(token: &str) -> Option<TokenIdentifier> {
+        Ext::token_index(crate::utils::id(), token).map(TokenIdentifier::from)
+    }
+
+    /// Returns the accounts holding the tokens of the default collection whose identifier fits an EXT token index
+    fn ext_holders() -> Vec<(ExtTokenIndex, Account)> {
+        TokensStorage::accounts(DEFAULT_COLLECTION_ID)
+            .into_iter()
+            .filter_map(|(token_identifier, account)| Some((token_identifier.0.to_u32()?, account)))
+            .collect()
+    }
+
+    fn ext_account_identifier(account: &Account) -> ExtAccountIdentifier {
+        // subaccounts of stored accounts are always valid
+        Ext::account_identifier(account.owner, account.effective_subaccount().ok().flatten())
+    }
+
+    /// Returns the provenance of the final metadata and its reveal, if committed
+    pub fn get_provenance() -> Option<Provenance> {
+        Configuration::get_provenance()
+    }
+
+    /// Commits the provenance hash of the final metadata; it can be changed until the first token is minted.
+    /// Caller must be the custodian of NFT canister.
+    pub fn set_provenance_hash(hash: ByteBuf) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Self::commit_provenance(hash)
+    }
+
+    fn commit_provenance(hash: ByteBuf) -> Result<(), NftError> {
+        if hash.len() != 32 {
+            return Err(NftError::Other("invalid provenance hash".to_string()));
+        }
+        if TokensStorage::total_supply(DEFAULT_COLLECTION_ID) > 0 {
+            return Err(NftError::Other(
+                "provenance must be committed before mint".to_string(),
+            ));
+        }
+        Configuration::set_provenance(Provenance {
+            hash,
+            committed_at: crate::utils::time(),
+            reveal: None,
+        });
+
+        Ok(())
+    }
+
+    /// Reveal the final metadata of the tokens, replacing their placeholder properties.
+    ///
+    /// `metadata` must hash to the committed provenance hash and cover the identifiers of every token. The metadata
+    /// is shifted by an offset drawn from `raw_rand`, so that the token `k` is given
+    /// `metadata[(k - 1 + offset) % metadata.len()]`; the tokens are given their metadata by the global timer,
+    /// a batch at a time.
+    /// Caller must be the custodian of NFT canister.
+    pub async fn reveal(metadata: Vec<Vec<(String, GenericValue)>>) -> Result<(), NftError> {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        let metadata_hashes = metadata
+            .iter()
+            .map(|properties| ByteBuf::from(Self::properties_hash(properties).to_vec()))
+            .collect::<Vec<_>>();
+        Self::check_reveal(&metadata_hashes)?;
+
+        let seed = crate::utils::raw_rand().await.map_err(NftError::Other)?;
+        // a reveal or a mint may have happened while awaiting the randomness
+        let mut provenance = Self::check_reveal(&metadata_hashes)?;
+        let offset = u64::from_be_bytes(seed[..8].try_into().expect("raw_rand returns 32 bytes"))
+            % metadata.len() as u64;
+        RevealStorage::insert(metadata);
+        provenance.reveal = Some(Reveal {
+            seed: ByteBuf::from(seed),
+            offset,
+            metadata_hashes,
+            revealed_at: crate::utils::time(),
+        });
+        Configuration::set_provenance(provenance);
+        Self::schedule_global_timer();
+
+        Ok(())
+    }
+
+    /// Give a batch of the revealed metadata to the tokens it's assigned to by the reveal offset
+    fn reveal_batch() {
+        let Some(reveal) = Configuration::get_provenance().and_then(|provenance| provenance.reveal)
+        else {
+            return;
+        };
+        let len = reveal.metadata_hashes.len() as u64;
+        for (index, properties) in RevealStorage::take_batch() {
+            // inverse of `reveal_index`
+            let token_identifier =
+                TokenIdentifier::from((index + len - reveal.offset % len) % len + 1);
+            // identifiers not minted are left without metadata
+            let _ = TokensStorage::replace_properties(
+                DEFAULT_COLLECTION_ID,
+                &token_identifier,
+                properties,
+            );
+        }
+    }
+
+    /// Returns the provenance if the metadata with these hashes can be revealed
+    fn check_reveal(metadata_hashes: &[ByteBuf]) -> Result<Provenance, NftError> {
+        let provenance = Configuration::get_provenance()
+            .ok_or_else(|| NftError::Other("provenance not committed".to_string()))?;
+        if provenance.reveal.is_some() {
+            return Err(NftError::Other("metadata already revealed".to_string()));
+        }
+        // the token identifiers must be in `1..=metadata_hashes.len()`
+        if metadata_hashes.is_empty()
+            || TokensStorage::get_token(DEFAULT_COLLECTION_ID, &0_u64.into()).is_ok()
+            || TokensStorage::next_token_id(DEFAULT_COLLECTION_ID)
+                > metadata_hashes.len() as u64 + 1
+        {
+            return Err(NftError::Other(
+                "metadata must cover every token".to_string(),
+            ));
+        }
+        if Self::provenance_hash(metadata_hashes).as_slice() != provenance.hash.as_slice() {
+            return Err(NftError::Other(
+                "metadata doesn't match the provenance hash".to_string(),
+            ));
+        }
+
+        Ok(provenance)
+    }
+
+    /// Returns whether the revealed metadata matches the committed provenance hash,
+    /// and every token has the properties given by the reveal offset
+    pub fn verify_reveal() -> Result<bool, NftError> {
+        let provenance = Configuration::get_provenance()
+            .ok_or_else(|| NftError::Other("provenance not committed".to_string()))?;
+        let reveal = provenance
+            .reveal
+            .ok_or_else(|| NftError::Other("metadata not revealed".to_string()))?;
+        if RevealStorage::is_pending() {
+            return Err(NftError::Other("metadata still being revealed".to_string()));
+        }
+        if Self::provenance_hash(&reveal.metadata_hashes).as_slice() != provenance.hash.as_slice() {
+            return Ok(false);
+        }
+        let len = reveal.metadata_hashes.len() as u64;
+
+        Ok(TokensStorage::tokens_properties(DEFAULT_COLLECTION_ID)
+            .iter()
+            .all(|(token_identifier, properties)| {
+                Self::reveal_index(token_identifier, reveal.offset, len).is_some_and(|index| {
+                    reveal.metadata_hashes[index].as_slice()
+                        == Self::properties_hash(properties).as_slice()
+                })
+            }))
+    }
+
+    /// Returns the index of the revealed metadata of the token `k`, `(k - 1 + offset) % len`,
+    /// if `k` is in `1..=len`
+    fn reveal_index(token_identifier: &TokenIdentifier, offset: u64, len: u64) -> Option<usize> {
+        let position = token_identifier.0.to_u64()?.checked_sub(1)?;
+        (position < len).then(|| ((position + offset % len) % len) as usize)
+    }
+
+    /// Returns the SHA-256 of the candid encoding of the token properties
+    fn properties_hash(properties: &[(String, GenericValue)]) -> [u8; 32] {
+        Sha256::digest(candid::encode_one(properties).expect("failed to encode properties")).into()
+    }
+
+    /// Returns the SHA-256 of the concatenated metadata hashes
+    fn provenance_hash(metadata_hashes: &[ByteBuf]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for hash in metadata_hashes {
+            hasher.update(hash);
+        }
+        hasher.finalize().into()
+    }
+
+    fn transfer_from(
+        collection_id: CollectionId,
+        caller: Principal,
+        owner: Principal,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        Self::inspect_transfer(collection_id, caller, owner, to, &token_identifier)?;
+
+        // transfer token to the new owner
+        TokensStorage::transfer(collection_id, &token_identifier, to)
+    }
+
+    /// Checks the token can be transferred by caller from owner to `to`
+    fn inspect_transfer(
+        collection_id: CollectionId,
+        caller: Principal,
+        owner: Principal,
+        to: Principal,
+        token_identifier: &TokenIdentifier,
+    ) -> Result<(), NftError> {
+        let token = Inspect::inspect_transfer_from(caller, collection_id, token_identifier)?;
+        // verify that from owner is the same as the token's, holding it in the default subaccount
+        if !TokensStorage::is_held_by(collection_id, token_identifier, owner, None) {
+            return Err(NftError::OwnerNotFound);
+        }
+        // verify that owner is not the same as to
+        if token.owner == Some(to) {
+            return Err(NftError::SelfTransfer);
+        }
+
+        Ok(())
+    }
+
+    fn mint(
+        collection_id: CollectionId,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        Self::mint_to_account(collection_id, to, None, token_identifier, properties)
+    }
+
+    fn mint_to_account(
+        collection_id: CollectionId,
+        to: Principal,
+        to_subaccount: Option<Subaccount>,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        if !Configuration::collection_has_interface(collection_id, SupportedInterface::Mint) {
+            return Err(NftError::Other("Not implemented".to_string()));
+        }
+        if Configuration::get_collection_max_supply(collection_id)
+            .is_some_and(|max_supply| TokensStorage::total_supply(collection_id) >= max_supply)
+        {
+            return Err(NftError::Other("collection supply cap reached".to_string()));
+        }
+        // the identifiers reserved by the mints in progress are taken already
+        if collection_id == DEFAULT_COLLECTION_ID && MintGuard::is_reserved(&token_identifier) {
+            return Err(NftError::ExistedNFT);
+        }
+        // the identifiers left in the random pool are minted by the public and allowlist mints only
+        if collection_id == DEFAULT_COLLECTION_ID
+            && token_identifier
+                .0
+                .to_u64()
+                .is_some_and(RandomPoolStorage::contains)
+        {
+            return Err(NftError::Other(
+                "token identifier reserved by the random pool".to_string(),
+            ));
+        }
+
+        TokensStorage::mint_to_account(
+            collection_id,
+            to,
+            to_subaccount,
+            token_identifier,
+            properties,
+        )
+    }
+}
+
+#[async_trait]
+impl Dip721 for App {
+    /// Returns the Metadata of the NFT canister which includes custodians, logo, name, symbol.
+    /// The logo is returned as the URL it is served at, rather than its content.
+    fn dip721_metadata() -> Metadata {
+        Metadata {
+            created_at: Configuration::get_created_at(),
+            custodians: Self::dip721_custodians(),
+            logo: Self::logo_url(),
+            name: Self::dip721_name(),
+            symbol: Self::dip721_symbol(),
+            upgraded_at: Configuration::get_upgraded_at(),
+        }
+    }
+
+    /// Returns the Stats of the NFT canister which includes cycles, totalSupply, totalTransactions, totalUniqueHolders.
+    fn dip721_stats() -> Stats {
+        Stats {
+            cycles: Self::dip721_cycles(),
+            total_supply: Self::dip721_total_supply(),
+            total_transactions: Self::dip721_total_transactions(),
+            total_unique_holders: Self::dip721_total_unique_holders(),
+        }
+    }
+
+    /// Returns the logo of the NFT contract as the text it was set with: Base64 encoded text, a `data:` URL,
+    /// or any other text.
+    fn dip721_logo() -> Option<String> {
+        Configuration::get_logo().map(|logo| logo.to_text())
+    }
+
+    /// Sets the logo of the NFT canister from Base64 encoded text, optionally as a `data:` URL.
+    /// Any other text is kept as is.
+    /// Caller must be the custodian of NFT canister.
+    fn dip721_set_logo(logo: String) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_logo(Logo::from_text(&logo));
+    }
+
+    /// Returns the name of the NFT canister.
+    fn dip721_name() -> Option<String> {
+        Configuration::get_name()
+    }
+
+    /// Sets the name of the NFT contract.
+    /// Caller must be the custodian of NFT canister.
+    fn dip721_set_name(name: String) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_name(name);
+    }
+
+    /// Returns the symbol of the NFT contract.
+    fn dip721_symbol() -> Option<String> {
+        Configuration::get_symbol()
+    }
+
+    /// Set symbol
+    /// Caller must be the custodian of NFT canister.
+    fn dip721_set_symbol(symbol: String) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_symbol(symbol);
+    }
+
+    /// Returns a list of the canister custodians
+    fn dip721_custodians() -> Vec<Principal> {
+        Configuration::get_custodians()
+    }
+
+    /// Set canister custodians
+    /// Caller must be the custodian of NFT canister.
+    fn dip721_set_custodians(custodians: Vec<Principal>) {
+        if !Inspect::inspect_is_custodian(caller()) {
+            ic_cdk::trap("Unauthorized");
+        }
+        Configuration::set_custodians(&custodians);
+    }
+
+    /// Returns canister cycles
+    fn dip721_cycles() -> Nat {
+        crate::utils::cycles()
+    }
+
+    /// Returns total unique holders of tokens
+    fn dip721_total_unique_holders() -> Nat {
+        TokensStorage::total_unique_holders(DEFAULT_COLLECTION_ID).into()
+    }
+
+    /// Returns metadata for token.
+    /// Editions are returned with the properties of their template merged in.
+    fn dip721_token_metadata(token_identifier: TokenIdentifier) -> Result<TokenMetadata, NftError> {
+        let mut token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)?;
+        token.properties = EditionsStorage::merge_properties(&token_identifier, token.properties);
+
+        Ok(token)
+    }
+
+    /// Returns the count of NFTs owned by user.
+    /// If the user does not own any NFTs, returns an error containing NftError.
+    fn dip721_balance_of(owner: Principal) -> Result<Nat, NftError> {
+        match TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, owner) {
+            tokens if tokens.is_empty() => Err(NftError::OwnerNotFound),
+            tokens => Ok(tokens.len().into()),
+        }
+    }
+
+    /// Returns the owner of the token; for tokens attached to a parent, the owner of the root token.
+    /// Returns an error containing NftError if token_identifier is invalid.
+    fn dip721_owner_of(token_identifier: TokenIdentifier) -> Result<Option<Principal>, NftError> {
+        Self::root_owner_of(token_identifier)
+    }
+
+    /// Returns the list of the token_identifier of the NFT associated with owner.
+    /// Returns an error containing NftError if principal is invalid.
+    fn dip721_owner_token_identifiers(owner: Principal) -> Result<Vec<TokenIdentifier>, NftError> {
+        match TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, owner) {
+            tokens if tokens.is_empty() => Err(NftError::OwnerNotFound),
+            tokens => Ok(tokens),
+        }
+    }
+
+    /// Returns the list of the token_metadata of the NFT associated with owner.
+    /// Returns an error containing NftError if principal is invalid.
+    fn dip721_owner_token_metadata(owner: Principal) -> Result<Vec<TokenMetadata>, NftError> {
+        let tokens = Self::dip721_owner_token_identifiers(owner)?;
+        let mut metadata = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            metadata.push(Self::dip721_token_metadata(token)?);
+        }
+
+        if metadata.is_empty() {
+            return Err(NftError::OwnerNotFound);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Returns the Principal of the operator of the NFT associated with token_identifier.
+    fn dip721_operator_of(
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Principal>, NftError> {
+        TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token_identifier)
+            .map(|token| token.operator)
+    }
+
+    /// Returns the list of the token_identifier of the NFT associated with operator.
+    fn dip721_operator_token_identifiers(
+        operator: Principal,
+    ) -> Result<Vec<TokenIdentifier>, NftError> {
+        match TokensStorage::tokens_by_operator(DEFAULT_COLLECTION_ID, operator) {
+            tokens if tokens.is_empty() => Err(NftError::OperatorNotFound),
+            tokens => Ok(tokens),
+        }
+    }
+
+    /// Returns the list of the token_metadata of the NFT associated with operator.
+    fn dip721_operator_token_metadata(operator: Principal) -> Result<Vec<TokenMetadata>, NftError> {
+        let tokens = Self::dip721_operator_token_identifiers(operator)?;
+        let mut metadata = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            metadata.push(Self::dip721_token_metadata(token)?);
+        }
+
+        if metadata.is_empty() {
+            return Err(NftError::OperatorNotFound);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Returns the list of the interfaces supported by this canister
+    fn dip721_supported_interfaces() -> Vec<SupportedInterface> {
+        Configuration::get_supported_interfaces()
+    }
+
+    /// Returns the total supply of the NFT.
+    /// NFTs that are minted and later burned explicitly or sent to the zero address should also count towards totalSupply.
+    fn dip721_total_supply() -> Nat {
+        TokensStorage::total_supply(DEFAULT_COLLECTION_ID).into()
+    }
+
+    // Calling approve grants the operator the ability to make update calls to the specificied token_identifier.
+    // Approvals given by the approve function are independent from approvals given by the setApprovalForAll.
+    //
+    // If the approval goes through, returns a nat that represents the CAP History transaction ID that can be used at the transaction method.
+    /// Interface: approval
+    fn dip721_approve(
+        operator: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        if !Inspect::inspect_is_owner(caller(), DEFAULT_COLLECTION_ID, &token_identifier) {
+            return Err(NftError::UnauthorizedOwner);
+        }
+
+        if Configuration::has_interface(SupportedInterface::Approval) {
+            TokensStorage::approve(DEFAULT_COLLECTION_ID, operator, &token_identifier)
+        } else {
+            Err(NftError::Other("Not implemented".to_string()))
+        }
+    }
+
+    /// Enable or disable an operator to manage all of the tokens for the caller of this function. The contract allows multiple operators per owner.
+    /// Approvals granted by the approve function are independent from the approvals granted by setApprovalForAll function.
+    /// If the approval goes through, returns a nat that represents the CAP History transaction ID that can be used at the transaction method.
+    /// Interface: approval
+    fn dip721_set_approval_for_all(operator: Principal, approved: bool) -> Result<Nat, NftError> {
+        if Configuration::has_interface(SupportedInterface::Approval) {
+            let tokens_by_owner = Self::dip721_owner_token_identifiers(caller())?;
+            let mut tx_id = None;
+            for token in tokens_by_owner {
+                if approved {
+                    tx_id = Some(TokensStorage::approve(
+                        DEFAULT_COLLECTION_ID,
+                        operator,
+                        &token,
+                    )?);
+                } else {
+                    tx_id = Some(TokensStorage::revoke_approval(
+                        DEFAULT_COLLECTION_ID,
+                        operator,
+                        &token,
+                    )?);
+                }
+            }
+            if let Some(tx_id) = tx_id {
+                Ok(tx_id)
+            } else {
+                Err(NftError::TokenNotFound)
+            }
+        } else {
+            Err(NftError::Other("Not implemented".to_string()))
+        }
+    }
+
+    /// Returns true if the given operator is an approved operator for all the tokens owned by the caller through the use of the setApprovalForAll method, returns false otherwise.
+    /// Interface: approval
+    fn dip721_is_approved_for_all(owner: Principal, operator: Principal) -> Result<bool, NftError> {
+        if Configuration::has_interface(SupportedInterface::Approval) {
+            for token in Self::dip721_owner_token_identifiers(owner)? {
+                let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &token)?;
+                if token.operator != Some(operator) {
+                    return Ok(false);
+                }
+            }
+
+            Ok(true)
+        } else {
+            Err(NftError::Other("Not implemented".to_string()))
+        }
+    }
+
+    /// Sends the callers nft token_identifier to `to`` and returns a nat that represents a
+    /// transaction id that can be used at the transaction method.
+    async fn dip721_transfer(
+        to: Principal,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        Self::dip721_transfer_from(caller(), to, token_identifier).await
     }
 
     /// Caller of this method is able to transfer the NFT token_identifier that is in from's balance to to's balance
@@ -317,18 +2614,7 @@ impl Dip721 for App {
         to: Principal,
         token_identifier: TokenIdentifier,
     ) -> Result<Nat, NftError> {
-        let token = Inspect::inspect_transfer_from(caller(), &token_identifier)?;
-        // verify that from owner is the same as the token's
-        if token.owner != Some(owner) {
-            return Err(NftError::OwnerNotFound);
-        }
-        // verify that owner is not the same as to
-        if token.owner == Some(to) {
-            return Err(NftError::SelfTransfer);
-        }
-
-        // transfer token to the new owner
-        TokensStorage::transfer(&token_identifier, to)
+        Self::transfer_from(DEFAULT_COLLECTION_ID, caller(), owner, to, token_identifier)
     }
 
     fn dip721_mint(
@@ -339,11 +2625,7 @@ impl Dip721 for App {
         if !Inspect::inspect_is_custodian(caller()) {
             ic_cdk::trap("Unauthorized");
         }
-        if Configuration::has_interface(SupportedInterface::Mint) {
-            TokensStorage::mint(to, token_identifier, properties)
-        } else {
-            Err(NftError::Other("Not implemented".to_string()))
-        }
+        Self::mint(DEFAULT_COLLECTION_ID, to, token_identifier, properties)
     }
 
     /// Burn an NFT identified by token_identifier. Calling burn on a token sets the owner to None and
@@ -353,10 +2635,13 @@ impl Dip721 for App {
     ///
     /// The burn will also reduce the contract value by the token value
     fn dip721_burn(token_identifier: TokenIdentifier) -> Result<Nat, NftError> {
-        Inspect::inspect_is_owner_or_operator(caller(), &token_identifier)?;
+        Inspect::inspect_is_owner_or_operator(caller(), DEFAULT_COLLECTION_ID, &token_identifier)?;
 
         if Configuration::has_interface(SupportedInterface::Burn) {
-            TokensStorage::burn(&token_identifier)
+            let tx_id = TokensStorage::burn(DEFAULT_COLLECTION_ID, &token_identifier)?;
+            // offers on the token expired, so they're refunded by the timer
+            Self::schedule_global_timer();
+            Ok(tx_id)
         } else {
             Err(NftError::Other("Not implemented".to_string()))
         }
@@ -386,22 +2671,263 @@ mod test {
 
     use std::time::Duration;
 
-    use pretty_assertions::assert_eq;
-    use test::test_utils::{bob, store_mock_token, store_mock_token_with};
+    use pretty_assertions::assert_eq;
+    use test::test_utils::{alice, bob, store_mock_token, store_mock_token_with};
+
+    use super::*;
+    use crate::app::test_utils::mock_token;
+
+    #[test]
+    fn test_should_init_canister() {
+        init_canister();
+
+        assert_eq!(App::dip721_custodians(), vec![caller()]);
+        assert_eq!(App::dip721_logo().as_deref(), Some("logo"));
+        assert_eq!(Configuration::get_name().as_deref(), Some("nft"));
+        assert_eq!(Configuration::get_symbol().as_deref(), Some("NFT"));
+        assert_eq!(
+            Configuration::get_supported_interfaces(),
+            vec![
+                SupportedInterface::Burn,
+                SupportedInterface::TransactionHistory,
+                SupportedInterface::Mint,
+                SupportedInterface::Approval,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_set_upgrade_time_on_post_upgrade() {
+        init_canister();
+        let metadata: Metadata = App::dip721_metadata();
+        assert!(metadata.upgraded_at == metadata.created_at);
+        std::thread::sleep(Duration::from_millis(100));
+        App::post_upgrade();
+        let metadata = App::dip721_metadata();
+        assert!(metadata.upgraded_at > metadata.created_at);
+    }
+
+    #[test]
+    fn test_should_get_metadata() {
+        init_canister();
+        let metadata = App::dip721_metadata();
+        assert_eq!(metadata.custodians, vec![caller()]);
+        assert_eq!(
+            metadata.logo,
+            Some(format!("https://{}.raw.icp0.io/logo", crate::utils::id()))
+        );
+        assert_eq!(metadata.name.as_deref(), Some("nft"));
+        assert_eq!(metadata.symbol.as_deref(), Some("NFT"));
+    }
+
+    #[test]
+    fn test_should_get_stats() {
+        init_canister();
+        let stats = App::dip721_stats();
+        assert_eq!(stats.cycles, crate::utils::cycles());
+        assert_eq!(stats.total_supply, 0_u64);
+        assert_eq!(stats.total_transactions, 0_u64);
+        assert_eq!(stats.total_unique_holders, 0_u64);
+    }
+
+    #[test]
+    fn test_should_set_logo() {
+        init_canister();
+        for logo in ["logo", "data:image/png;base64,aGVsbG8=", "not base64!"] {
+            App::dip721_set_logo(logo.to_string());
+            assert_eq!(App::dip721_logo().as_deref(), Some(logo));
+        }
+        assert_eq!(App::get_logo().unwrap().content_type, "text/plain");
+    }
+
+    #[test]
+    fn test_should_set_http_base_url() {
+        init_canister();
+        assert!(App::set_http_base_url(Some("ftp://nft.example.com".to_string())).is_err());
+        assert!(App::set_http_base_url(Some("https://nft.example.com/".to_string())).is_ok());
+        assert_eq!(App::get_http_base_url(), "https://nft.example.com");
+        assert_eq!(
+            App::dip721_metadata().logo.as_deref(),
+            Some("https://nft.example.com/logo")
+        );
+
+        assert!(App::set_http_base_url(None).is_ok());
+        assert_eq!(
+            App::get_http_base_url(),
+            format!("https://{}.raw.icp0.io", crate::utils::id())
+        );
+    }
+
+    #[test]
+    fn test_should_set_binary_logo() {
+        init_canister();
+        App::set_logo(
+            "image/svg+xml".to_string(),
+            ByteBuf::from(b"<svg/>".to_vec()),
+        );
+        let logo = App::get_logo().unwrap();
+        assert_eq!(logo.content_type, "image/svg+xml");
+        assert_eq!(logo.data.as_slice(), b"<svg/>");
+        assert_eq!(App::dip721_logo().as_deref(), Some("PHN2Zy8+"));
+    }
+
+    #[test]
+    fn test_should_set_name() {
+        init_canister();
+        let name = "name";
+        App::dip721_set_name(name.to_string());
+        assert_eq!(App::dip721_name().as_deref(), Some(name));
+    }
+
+    #[test]
+    fn test_should_set_symbol() {
+        init_canister();
+        let symbol = "symbol";
+        App::dip721_set_symbol(symbol.to_string());
+        assert_eq!(App::dip721_symbol().as_deref(), Some(symbol));
+    }
+
+    #[test]
+    fn test_should_set_custodians() {
+        init_canister();
+        let custodians = vec![caller(), Principal::management_canister()];
+        App::dip721_set_custodians(custodians.clone());
+        assert_eq!(App::dip721_custodians().len(), custodians.len());
+    }
+
+    #[test]
+    fn test_should_get_cycles() {
+        init_canister();
+        assert_eq!(App::dip721_cycles(), crate::utils::cycles());
+    }
+
+    #[test]
+    fn test_should_get_unique_holders() {
+        init_canister();
+        store_mock_token(1);
+        assert_eq!(App::dip721_total_unique_holders(), Nat::from(1_u64));
+    }
+
+    #[test]
+    fn test_should_get_token_metadata() {
+        init_canister();
+        store_mock_token(1);
+        let metadata = App::dip721_token_metadata(1_u64.into()).unwrap();
+        assert_eq!(metadata.owner, Some(caller()));
+        assert_eq!(metadata.token_identifier, Nat::from(1_u64));
+
+        // unexisting token
+        assert!(App::dip721_token_metadata(5_u64.into()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_balance_of() {
+        init_canister();
+        store_mock_token(1);
+        assert_eq!(App::dip721_balance_of(caller()).unwrap(), Nat::from(1_u64));
+        assert!(App::dip721_balance_of(Principal::management_canister()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_owner_of() {
+        init_canister();
+        store_mock_token(1);
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(caller()));
+        assert!(App::dip721_owner_of(5_u64.into()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_owner_token_identifiers() {
+        init_canister();
+        store_mock_token(1);
+        store_mock_token(2);
+        assert_eq!(
+            App::dip721_owner_token_identifiers(caller()).unwrap(),
+            vec![Nat::from(1_u64), Nat::from(2_u64)]
+        );
+        assert!(App::dip721_owner_token_identifiers(Principal::management_canister()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_owner_token_metadata() {
+        init_canister();
+        store_mock_token(1);
+        store_mock_token(2);
+        let metadata = App::dip721_owner_token_metadata(caller()).unwrap();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata[0].owner, Some(caller()));
+        assert_eq!(metadata[0].token_identifier, Nat::from(1_u64));
+        assert_eq!(metadata[1].owner, Some(caller()));
+        assert_eq!(metadata[1].token_identifier, Nat::from(2_u64));
+
+        // unexisting owner
+        assert!(App::dip721_owner_token_metadata(Principal::management_canister()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_operator_of() {
+        init_canister();
+        store_mock_token(1);
+        assert_eq!(App::dip721_operator_of(1_u64.into()).unwrap(), None);
+        store_mock_token_with(2, |token| {
+            token.operator = Some(Principal::management_canister())
+        });
+
+        assert_eq!(
+            App::dip721_operator_of(2_u64.into()).unwrap(),
+            Some(Principal::management_canister())
+        );
+
+        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_operator_token_identifiers() {
+        init_canister();
+        // no owner
+        store_mock_token_with(1, |token| {
+            token.operator = None;
+        });
+        assert!(App::dip721_operator_token_identifiers(caller()).is_err());
+
+        // with operator
+        store_mock_token_with(2, |token| {
+            token.operator = Some(Principal::management_canister())
+        });
+        assert_eq!(
+            App::dip721_operator_token_identifiers(Principal::management_canister()).unwrap(),
+            vec![Nat::from(2_u64)]
+        );
+        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+    }
+
+    #[test]
+    fn test_should_get_operator_token_metadata() {
+        init_canister();
+        // no owner
+        store_mock_token_with(1, |token| {
+            token.operator = None;
+        });
+        assert!(App::dip721_operator_token_metadata(caller()).is_err());
+
+        // with operator
+        store_mock_token_with(2, |token| {
+            token.operator = Some(Principal::management_canister())
+        });
+        let metadata =
+            App::dip721_operator_token_metadata(Principal::management_canister()).unwrap();
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[0].owner, Some(caller()));
+        assert_eq!(metadata[0].token_identifier, Nat::from(2_u64));
 
-    use super::*;
-    use crate::app::test_utils::mock_token;
+        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+    }
 
     #[test]
-    fn test_should_init_canister() {
+    fn test_should_get_supported_interfaces() {
         init_canister();
-
-        assert_eq!(App::dip721_custodians(), vec![caller()]);
-        assert_eq!(Configuration::get_logo().as_deref(), Some("logo"));
-        assert_eq!(Configuration::get_name().as_deref(), Some("nft"));
-        assert_eq!(Configuration::get_symbol().as_deref(), Some("NFT"));
         assert_eq!(
-            Configuration::get_supported_interfaces(),
+            App::dip721_supported_interfaces(),
             vec![
                 SupportedInterface::Burn,
                 SupportedInterface::TransactionHistory,
@@ -412,329 +2938,1247 @@ mod test {
     }
 
     #[test]
-    fn test_should_set_upgrade_time_on_post_upgrade() {
+    fn test_should_get_total_supply() {
         init_canister();
-        let metadata: Metadata = App::dip721_metadata();
-        assert!(metadata.upgraded_at == metadata.created_at);
-        std::thread::sleep(Duration::from_millis(100));
-        App::post_upgrade();
-        let metadata = App::dip721_metadata();
-        assert!(metadata.upgraded_at > metadata.created_at);
+        store_mock_token(1);
+        store_mock_token(2);
+        assert_eq!(App::dip721_total_supply(), Nat::from(2_u64));
     }
 
-    #[test]
-    fn test_should_get_metadata() {
+    #[tokio::test]
+    async fn test_should_transfer() {
         init_canister();
-        let metadata = App::dip721_metadata();
-        assert_eq!(metadata.custodians, vec![caller()]);
-        assert_eq!(metadata.logo.as_deref(), Some("logo"));
-        assert_eq!(metadata.name.as_deref(), Some("nft"));
-        assert_eq!(metadata.symbol.as_deref(), Some("NFT"));
+        store_mock_token(1);
+        // self transfer
+        assert!(App::dip721_transfer(caller(), 1_u64.into()).await.is_err());
+
+        // transfer
+        assert!(
+            App::dip721_transfer(Principal::management_canister(), 1_u64.into())
+                .await
+                .is_ok()
+        );
+        assert!(App::dip721_balance_of(caller()).is_err());
+        assert_eq!(
+            App::dip721_balance_of(Principal::management_canister()).unwrap(),
+            Nat::from(1_u64)
+        );
+        // transfer unexisting
+        assert!(
+            App::dip721_transfer(Principal::management_canister(), 5_u64.into())
+                .await
+                .is_err()
+        );
     }
 
     #[test]
-    fn test_should_get_stats() {
+    fn test_should_burn() {
         init_canister();
-        let stats = App::dip721_stats();
-        assert_eq!(stats.cycles, crate::utils::cycles());
-        assert_eq!(stats.total_supply, 0_u64);
-        assert_eq!(stats.total_transactions, 0_u64);
-        assert_eq!(stats.total_unique_holders, 0_u64);
+        store_mock_token(1);
+        assert!(App::dip721_burn(1_u64.into()).is_ok());
+        assert!(App::dip721_balance_of(caller()).is_err());
+        assert!(App::dip721_burn(1_u64.into()).is_err());
+        assert!(App::dip721_burn(5_u64.into()).is_err());
     }
 
     #[test]
-    fn test_should_set_logo() {
+    fn test_should_approve() {
         init_canister();
-        let logo = "logo";
-        App::dip721_set_logo(logo.to_string());
-        assert_eq!(App::dip721_logo().as_deref(), Some(logo));
+        store_mock_token(1);
+        assert!(App::dip721_approve(bob(), 1_u64.into()).is_ok());
+
+        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(DEFAULT_COLLECTION_ID, bob());
+        assert_eq!(tokens_with_bob_as_op, vec![Nat::from(1_u64)]);
     }
 
     #[test]
-    fn test_should_set_name() {
+    fn test_should_approve_for_all() {
         init_canister();
-        let name = "name";
-        App::dip721_set_name(name.to_string());
-        assert_eq!(App::dip721_name().as_deref(), Some(name));
+        store_mock_token(1);
+        store_mock_token(2);
+        assert!(App::dip721_set_approval_for_all(bob(), true).is_ok());
+
+        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(DEFAULT_COLLECTION_ID, bob());
+        assert_eq!(
+            tokens_with_bob_as_op,
+            vec![Nat::from(1_u64), Nat::from(2_u64)]
+        );
+
+        assert!(App::dip721_set_approval_for_all(bob(), false).is_ok());
+
+        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(DEFAULT_COLLECTION_ID, bob());
+        assert!(tokens_with_bob_as_op.is_empty());
     }
 
     #[test]
-    fn test_should_set_symbol() {
+    fn test_should_tell_if_approved_for_all() {
         init_canister();
-        let symbol = "symbol";
-        App::dip721_set_symbol(symbol.to_string());
-        assert_eq!(App::dip721_symbol().as_deref(), Some(symbol));
+        store_mock_token(1);
+        store_mock_token(2);
+        assert!(App::dip721_set_approval_for_all(bob(), true).is_ok());
+        assert!(App::dip721_is_approved_for_all(caller(), bob()).unwrap());
+        assert!(
+            !App::dip721_is_approved_for_all(caller(), Principal::management_canister()).unwrap()
+        );
+
+        store_mock_token(3);
+        assert!(!App::dip721_is_approved_for_all(caller(), bob()).unwrap());
     }
 
     #[test]
-    fn test_should_set_custodians() {
+    fn test_should_get_tx() {
         init_canister();
-        let custodians = vec![caller(), Principal::management_canister()];
-        App::dip721_set_custodians(custodians.clone());
-        assert_eq!(App::dip721_custodians().len(), custodians.len());
+        assert!(App::dip721_transaction(Nat::from(1_u64)).is_err());
+        let id = TxHistory::register_token_mint(&mock_token(1));
+        assert!(App::dip721_transaction(id).is_ok());
     }
 
     #[test]
-    fn test_should_get_cycles() {
+    fn test_should_get_total_transactions() {
         init_canister();
-        assert_eq!(App::dip721_cycles(), crate::utils::cycles());
+        assert_eq!(App::dip721_total_transactions(), Nat::from(0_u64));
+        let _ = TxHistory::register_token_mint(&mock_token(1));
+        assert_eq!(App::dip721_total_transactions(), Nat::from(1_u64));
     }
 
     #[test]
-    fn test_should_get_unique_holders() {
+    fn test_should_set_token_property() {
         init_canister();
         store_mock_token(1);
-        assert_eq!(App::dip721_total_unique_holders(), Nat::from(1_u64));
+        assert!(App::set_token_property(
+            1_u64.into(),
+            "key".to_string(),
+            GenericValue::BoolContent(true)
+        )
+        .is_ok());
+
+        let metadata = App::dip721_token_metadata(1_u64.into()).unwrap();
+        assert_eq!(
+            metadata.properties,
+            vec![("key".to_string(), GenericValue::BoolContent(true))]
+        );
     }
 
     #[test]
-    fn test_should_get_token_metadata() {
+    fn test_should_upload_asset() {
         init_canister();
+        let content = b"hello world";
+        let sha256 = ByteBuf::from(vec![
+            0xb9, 0x4d, 0x27, 0xb9, 0x93, 0x4d, 0x3e, 0x08, 0xa5, 0x2e, 0x52, 0xd7, 0xda, 0x7d,
+            0xab, 0xfa, 0xc4, 0x84, 0xef, 0xe3, 0x7a, 0x53, 0x80, 0xee, 0x90, 0x88, 0xf7, 0xac,
+            0xe2, 0xef, 0xcd, 0xe9,
+        ]);
+        assert!(App::create_asset("text/plain".to_string(), ByteBuf::from(vec![0; 4])).is_err());
+        let id = App::create_asset("text/plain".to_string(), sha256).unwrap();
+        assert!(App::upload_asset_chunk(id, ByteBuf::from(content.to_vec())).is_ok());
+
+        // cannot set unfinalized asset
         store_mock_token(1);
+        assert!(App::set_token_asset(1_u64.into(), id).is_err());
+
+        let asset = App::finalize_asset(id).unwrap();
+        assert_eq!(asset.size, content.len() as u64);
+        assert_eq!(App::get_asset(id), Some(asset));
+
+        assert!(App::set_token_asset(1_u64.into(), id).is_ok());
         let metadata = App::dip721_token_metadata(1_u64.into()).unwrap();
-        assert_eq!(metadata.owner, Some(caller()));
-        assert_eq!(metadata.token_identifier, Nat::from(1_u64));
+        assert_eq!(
+            metadata.properties,
+            vec![(
+                TOKEN_ASSET_PROPERTY.to_string(),
+                GenericValue::Nat64Content(id)
+            )]
+        );
 
-        // unexisting token
-        assert!(App::dip721_token_metadata(5_u64.into()).is_err());
+        // referenced assets can't be deleted
+        assert!(App::delete_asset(id).is_err());
+        assert!(App::set_token_property(
+            1_u64.into(),
+            TOKEN_ASSET_PROPERTY.to_string(),
+            GenericValue::Nat64Content(id + 1)
+        )
+        .is_ok());
+        assert!(App::delete_asset(id).is_ok());
+        assert!(App::get_asset(id).is_none());
     }
 
     #[test]
-    fn test_should_get_balance_of() {
+    fn test_should_get_royalty_info() {
+        use crate::did::RoyaltyRecipient;
+
         init_canister();
         store_mock_token(1);
-        assert_eq!(App::dip721_balance_of(caller()).unwrap(), Nat::from(1_u64));
-        assert!(App::dip721_balance_of(Principal::management_canister()).is_err());
+        store_mock_token(2);
+        assert_eq!(
+            App::royalty_info(1_u64.into(), 1_000_u64.into()).unwrap(),
+            vec![]
+        );
+        assert!(App::royalty_info(3_u64.into(), 1_000_u64.into()).is_err());
+
+        // shares must sum to the whole royalty
+        let invalid = Royalty {
+            basis_points: 500,
+            recipients: vec![RoyaltyRecipient {
+                recipient: bob(),
+                share: 5_000,
+            }],
+        };
+        assert!(App::set_collection_royalty(Some(invalid)).is_err());
+
+        let collection_royalty = Royalty {
+            basis_points: 500,
+            recipients: vec![
+                RoyaltyRecipient {
+                    recipient: bob(),
+                    share: 3_333,
+                },
+                RoyaltyRecipient {
+                    recipient: caller(),
+                    share: 6_667,
+                },
+            ],
+        };
+        assert!(App::set_collection_royalty(Some(collection_royalty.clone())).is_ok());
+        assert_eq!(App::get_collection_royalty(), Some(collection_royalty));
+        assert_eq!(
+            App::royalty_info(1_u64.into(), 1_000_u64.into()).unwrap(),
+            vec![
+                RoyaltyPayment {
+                    recipient: bob(),
+                    amount: 17_u64.into()
+                },
+                RoyaltyPayment {
+                    recipient: caller(),
+                    amount: 33_u64.into()
+                }
+            ]
+        );
+
+        // token royalty overrides the collection one
+        let token_royalty = Royalty {
+            basis_points: 1_000,
+            recipients: vec![RoyaltyRecipient {
+                recipient: bob(),
+                share: 10_000,
+            }],
+        };
+        assert!(App::set_token_royalty(3_u64.into(), Some(token_royalty.clone())).is_err());
+        assert!(App::set_token_royalty(2_u64.into(), Some(token_royalty.clone())).is_ok());
+        assert_eq!(App::get_token_royalty(2_u64.into()), Some(token_royalty));
+        assert_eq!(
+            App::royalty_info(2_u64.into(), 1_000_u64.into()).unwrap(),
+            vec![RoyaltyPayment {
+                recipient: bob(),
+                amount: 100_u64.into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_list_and_buy_token() {
+        use crate::did::PlatformFee;
+
+        init_canister();
+        let ledger = Principal::management_canister();
+        store_mock_token(1);
+        store_mock_token_with(2, |token| {
+            token.owner = Some(bob());
+        });
+
+        // ledger must be accepted
+        assert!(App::list_token(1_u64.into(), 1_000_u64.into(), ledger).is_err());
+        assert!(App::set_marketplace_settings(MarketplaceSettings {
+            ledgers: vec![ledger],
+            platform_fee: Some(PlatformFee {
+                basis_points: 250,
+                recipient: bob(),
+            }),
+        })
+        .is_ok());
+        assert!(App::list_token(1_u64.into(), 0_u64.into(), ledger).is_err());
+        assert!(App::list_token(2_u64.into(), 1_000_u64.into(), ledger).is_err());
+        assert!(App::list_token(1_u64.into(), 1_000_u64.into(), ledger).is_ok());
+        assert_eq!(App::get_listing(1_u64.into()).unwrap().price, 1_000_u64);
+        assert!(App::cancel_listing(1_u64.into()).is_ok());
+        assert!(App::get_listing(1_u64.into()).is_none());
+        assert!(App::cancel_listing(1_u64.into()).is_err());
+
+        // can't buy own token
+        assert!(App::list_token(1_u64.into(), 1_000_u64.into(), ledger).is_ok());
+        assert_eq!(
+            App::buy(1_u64.into()).await.unwrap_err(),
+            NftError::SelfTransfer
+        );
+
+        ListingsStorage::insert(
+            &2_u64.into(),
+            Listing {
+                seller: bob(),
+                price: 1_000_u64.into(),
+                ledger,
+                listed_at: 0,
+            },
+        );
+        assert_eq!(App::get_listings().len(), 2);
+        assert!(App::buy(2_u64.into()).await.is_ok());
+        assert_eq!(App::dip721_owner_of(2_u64.into()).unwrap(), Some(caller()));
+        assert!(App::get_listing(2_u64.into()).is_none());
+        assert!(App::buy(2_u64.into()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_should_run_english_auction() {
+        init_canister();
+        let ledger = Principal::management_canister();
+        App::set_marketplace_settings(MarketplaceSettings {
+            ledgers: vec![ledger],
+            platform_fee: None,
+        })
+        .unwrap();
+        store_mock_token(1);
+        store_mock_token_with(2, |token| {
+            token.owner = Some(bob());
+        });
+
+        let now = crate::utils::time();
+        let english = AuctionKind::English {
+            reserve_price: 1_000_u64.into(),
+            min_increment: 100_u64.into(),
+            extension: 60_000_000_000,
+        };
+        assert!(App::create_auction(1_u64.into(), ledger, english.clone(), now).is_err());
+        assert!(App::create_auction(2_u64.into(), ledger, english.clone(), now + 1).is_err());
+        assert!(App::create_auction(
+            1_u64.into(),
+            ledger,
+            english.clone(),
+            now + 3_600_000_000_000
+        )
+        .is_ok());
+        // locked while under auction
+        assert!(App::list_token(1_u64.into(), 1_000_u64.into(), ledger).is_err());
+        assert!(App::dip721_burn(1_u64.into()).is_err());
+        assert!(App::cancel_auction(1_u64.into()).is_ok());
+
+        // bob's auction, ending in an hour
+        AuctionsStorage::insert(
+            &2_u64.into(),
+            Auction {
+                seller: bob(),
+                ledger,
+                kind: english,
+                starts_at: now,
+                ends_at: now + 3_600_000_000_000,
+                bids: vec![],
+            },
+        );
+        assert!(App::bid(2_u64.into(), 999_u64.into()).await.is_err());
+        assert!(App::bid(2_u64.into(), 1_000_u64.into()).await.is_ok());
+        assert!(App::bid(2_u64.into(), 1_050_u64.into()).await.is_err());
+        assert!(App::bid(2_u64.into(), 1_100_u64.into()).await.is_ok());
+        let mut auction = App::get_auction(2_u64.into()).unwrap();
+        assert_eq!(auction.bids.len(), 2);
+        assert_eq!(auction.highest_bid().unwrap().amount, 1_100_u64);
+        assert!(App::cancel_auction(2_u64.into()).is_err());
+
+        // nothing to settle yet
+        App::settle_auctions().await;
+        assert!(App::get_auction(2_u64.into()).is_some());
+
+        auction.ends_at = now;
+        AuctionsStorage::insert(&2_u64.into(), auction);
+        App::settle_auctions().await;
+        assert!(App::get_auction(2_u64.into()).is_none());
+        assert_eq!(App::dip721_owner_of(2_u64.into()).unwrap(), Some(caller()));
+    }
+
+    #[tokio::test]
+    async fn test_should_win_dutch_auction() {
+        init_canister();
+        let ledger = Principal::management_canister();
+        store_mock_token_with(1, |token| {
+            token.owner = Some(bob());
+        });
+        let now = crate::utils::time();
+        AuctionsStorage::insert(
+            &1_u64.into(),
+            Auction {
+                seller: bob(),
+                ledger,
+                kind: AuctionKind::Dutch {
+                    start_price: 2_000_u64.into(),
+                    end_price: 1_000_u64.into(),
+                },
+                starts_at: now,
+                ends_at: now + 3_600_000_000_000,
+                bids: vec![],
+            },
+        );
+
+        assert!(App::bid(1_u64.into(), 900_u64.into()).await.is_err());
+        assert!(App::bid(1_u64.into(), 2_000_u64.into()).await.is_ok());
+        assert!(App::get_auction(1_u64.into()).is_none());
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(caller()));
+    }
+
+    #[tokio::test]
+    async fn test_should_make_and_accept_offers() {
+        init_canister();
+        let ledger = Principal::management_canister();
+        App::set_marketplace_settings(MarketplaceSettings {
+            ledgers: vec![ledger],
+            platform_fee: None,
+        })
+        .unwrap();
+        store_mock_token(1);
+        store_mock_token_with(2, |token| {
+            token.owner = Some(bob());
+        });
+        let expires_at = crate::utils::time() + 3_600_000_000_000;
+
+        // can't make offers on own tokens
+        assert_eq!(
+            App::make_offer(1_u64.into(), 1_000_u64.into(), expires_at, ledger)
+                .await
+                .unwrap_err(),
+            NftError::SelfTransfer
+        );
+        let id = App::make_offer(2_u64.into(), 1_000_u64.into(), expires_at, ledger)
+            .await
+            .unwrap();
+        assert_eq!(App::get_token_offers(2_u64.into()).len(), 1);
+        assert_eq!(
+            App::accept_offer(id).await.unwrap_err(),
+            NftError::UnauthorizedOwner
+        );
+        assert!(App::withdraw_offer(id).await.is_ok());
+        assert!(App::get_offer(id).is_none());
+        // a new offer doesn't take the id of the withdrawn one
+        let new_id = App::make_offer(2_u64.into(), 1_u64.into(), expires_at, ledger)
+            .await
+            .unwrap();
+        assert_ne!(new_id, id);
+        assert!(App::get_offer(id).is_none());
+        assert!(App::withdraw_offer(new_id).await.is_ok());
+
+        // offer made by bob on the caller's token
+        let id = OffersStorage::insert(Offer {
+            token_identifier: 1_u64.into(),
+            bidder: bob(),
+            ledger,
+            amount: 1_000_u64.into(),
+            created_at: 0,
+            expires_at,
+        });
+        assert_eq!(
+            App::withdraw_offer(id).await.unwrap_err(),
+            NftError::UnauthorizedOperator
+        );
+        assert!(App::accept_offer(id).await.is_ok());
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(bob()));
+        assert!(App::get_offer(id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_should_refund_offers_on_burned_tokens() {
+        init_canister();
+        store_mock_token(1);
+        let id = OffersStorage::insert(Offer {
+            token_identifier: 1_u64.into(),
+            bidder: bob(),
+            ledger: Principal::management_canister(),
+            amount: 1_000_u64.into(),
+            created_at: 0,
+            expires_at: u64::MAX,
+        });
+
+        App::expire_offers().await;
+        assert!(App::get_offer(id).is_some());
+        assert!(App::dip721_burn(1_u64.into()).is_ok());
+        App::expire_offers().await;
+        assert!(App::get_offer(id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_should_propose_and_accept_swaps() {
+        use crate::did::SwapPayment;
+
+        init_canister();
+        let ledger = Principal::management_canister();
+        App::set_marketplace_settings(MarketplaceSettings {
+            ledgers: vec![ledger],
+            platform_fee: None,
+        })
+        .unwrap();
+        store_mock_token(1);
+        store_mock_token(2);
+        store_mock_token_with(3, |token| {
+            token.owner = Some(bob());
+        });
+        store_mock_token_with(4, |token| {
+            token.owner = Some(bob());
+        });
+        let expires_at = crate::utils::time() + 3_600_000_000_000;
+        let args = ProposeSwapArgs {
+            counterparty: bob(),
+            offered: vec![1_u64.into()],
+            requested: vec![3_u64.into()],
+            payment: None,
+            expires_at,
+        };
+
+        assert!(App::propose_swap(ProposeSwapArgs {
+            requested: vec![2_u64.into()],
+            ..args.clone()
+        })
+        .is_err());
+        assert!(App::propose_swap(ProposeSwapArgs {
+            offered: vec![1_u64.into(), 1_u64.into()],
+            ..args.clone()
+        })
+        .is_err());
+        let id = App::propose_swap(args.clone()).unwrap();
+        assert_eq!(App::get_swaps(bob()).len(), 1);
+
+        // offered tokens are locked until the swap is cancelled
+        assert!(App::propose_swap(args).is_err());
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1_u64.into(), alice()).is_err());
+        assert_eq!(
+            App::accept_swap(id, vec![1_u64.into()], None)
+                .await
+                .unwrap_err(),
+            NftError::UnauthorizedOperator
+        );
+        assert!(App::cancel_swap(id).is_ok());
+        assert!(App::get_swap(id).is_none());
+
+        // swap proposed by bob to the caller
+        let id = SwapsStorage::insert(Swap {
+            proposer: bob(),
+            counterparty: caller(),
+            offered: vec![3_u64.into(), 4_u64.into()],
+            requested: vec![2_u64.into()],
+            payment: Some(SwapPayment {
+                ledger,
+                amount: 1_000_u64.into(),
+            }),
+            created_at: 0,
+            expires_at,
+        });
+        let payment = Some(SwapPayment {
+            ledger,
+            amount: 1_000_u64.into(),
+        });
+        // the terms must be the ones of the swap
+        assert!(App::accept_swap(id, vec![3_u64.into()], payment.clone())
+            .await
+            .is_err());
+        assert!(App::accept_swap(
+            id,
+            vec![3_u64.into(), 4_u64.into()],
+            Some(SwapPayment {
+                ledger,
+                amount: 1_u64.into(),
+            })
+        )
+        .await
+        .is_err());
+        let tx_id = App::accept_swap(id, vec![3_u64.into(), 4_u64.into()], payment)
+            .await
+            .unwrap();
+        assert_eq!(App::dip721_transaction(tx_id).unwrap().operation, "swap");
+        assert!(App::get_swap(id).is_none());
+        assert_eq!(App::dip721_owner_of(2_u64.into()).unwrap(), Some(bob()));
+        assert_eq!(App::dip721_owner_of(3_u64.into()).unwrap(), Some(caller()));
+        assert_eq!(App::dip721_owner_of(4_u64.into()).unwrap(), Some(caller()));
     }
 
     #[test]
-    fn test_should_get_owner_of() {
+    fn test_should_expire_swaps() {
         init_canister();
         store_mock_token(1);
-        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(caller()));
-        assert!(App::dip721_owner_of(5_u64.into()).is_err());
+        store_mock_token_with(2, |token| {
+            token.owner = Some(bob());
+        });
+        let id = SwapsStorage::insert(Swap {
+            proposer: caller(),
+            counterparty: bob(),
+            offered: vec![1_u64.into()],
+            requested: vec![2_u64.into()],
+            payment: None,
+            created_at: 0,
+            expires_at: crate::utils::time(),
+        });
+
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1_u64.into(), alice()).is_err());
+        App::expire_swaps();
+        assert!(App::get_swap(id).is_none());
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1_u64.into(), alice()).is_ok());
     }
 
     #[test]
-    fn test_should_get_owner_token_identifiers() {
+    fn test_should_hold_tokens_in_subaccounts() {
         init_canister();
-        store_mock_token(1);
-        store_mock_token(2);
+        let subaccount = ByteBuf::from(vec![1_u8; 32]);
+        let account = Account {
+            owner: caller(),
+            subaccount: Some(subaccount.clone()),
+        };
+        assert!(App::account_mint(account.clone(), 1_u64.into(), vec![]).is_ok());
+        assert!(App::account_mint(
+            Account {
+                owner: caller(),
+                subaccount: Some(ByteBuf::from(vec![1_u8; 8])),
+            },
+            2_u64.into(),
+            vec![]
+        )
+        .is_err());
+
+        assert_eq!(
+            App::account_balance_of(account.clone()).unwrap(),
+            Nat::from(1_u64)
+        );
+        assert_eq!(
+            App::account_of(1_u64.into()).unwrap(),
+            Some(account.clone())
+        );
+        // DIP721 methods only operate on the default subaccount
+        assert!(App::dip721_balance_of(caller()).is_err());
+        assert!(App::dip721_burn(1_u64.into()).is_err());
+        assert!(App::transfer_from(
+            DEFAULT_COLLECTION_ID,
+            caller(),
+            caller(),
+            bob(),
+            1_u64.into()
+        )
+        .is_err());
+
+        assert_eq!(
+            App::account_transfer(None, Account::from(bob()), 1_u64.into()).unwrap_err(),
+            NftError::UnauthorizedOwner
+        );
+        assert_eq!(
+            App::account_transfer(Some(subaccount.clone()), account.clone(), 1_u64.into())
+                .unwrap_err(),
+            NftError::SelfTransfer
+        );
+        assert!(
+            App::account_transfer(Some(subaccount), Account::from(caller()), 1_u64.into()).is_ok()
+        );
+        assert!(App::account_token_identifiers(account).unwrap().is_empty());
         assert_eq!(
             App::dip721_owner_token_identifiers(caller()).unwrap(),
-            vec![Nat::from(1_u64), Nat::from(2_u64)]
+            vec![TokenIdentifier::from(1_u64)]
         );
-        assert!(App::dip721_owner_token_identifiers(Principal::management_canister()).is_err());
     }
 
     #[test]
-    fn test_should_get_owner_token_metadata() {
+    fn test_should_expose_tokens_through_ext() {
         init_canister();
         store_mock_token(1);
-        store_mock_token(2);
-        let metadata = App::dip721_owner_token_metadata(caller()).unwrap();
-        assert_eq!(metadata.len(), 2);
-        assert_eq!(metadata[0].owner, Some(caller()));
-        assert_eq!(metadata[0].token_identifier, Nat::from(1_u64));
-        assert_eq!(metadata[1].owner, Some(caller()));
-        assert_eq!(metadata[1].token_identifier, Nat::from(2_u64));
+        let subaccount = [1_u8; 32];
+        assert!(
+            App::account_mint(Account::new(bob(), Some(subaccount)), 2_u64.into(), vec![]).is_ok()
+        );
+        let token = App::ext_token_identifier_of(1_u64.into()).unwrap();
+        let caller_identifier = Ext::account_identifier(caller(), None);
+        let bob_identifier = Ext::account_identifier(bob(), Some(subaccount));
 
-        // unexisting owner
-        assert!(App::dip721_owner_token_metadata(Principal::management_canister()).is_err());
+        assert_eq!(
+            App::ext_bearer(token.clone()),
+            ExtResult::Ok(caller_identifier.clone())
+        );
+        assert_eq!(
+            App::ext_bearer("aaaaa-aa".to_string()),
+            ExtResult::Err(ExtCommonError::InvalidToken("aaaaa-aa".to_string()))
+        );
+        assert_eq!(
+            App::ext_get_registry(),
+            vec![(1, caller_identifier.clone()), (2, bob_identifier.clone())]
+        );
+        assert_eq!(App::ext_get_tokens().len(), 2);
+        assert_eq!(
+            App::ext_tokens(bob_identifier.to_uppercase()),
+            ExtResult::Ok(vec![(2, None, None)])
+        );
+
+        let request = ExtTransferRequest {
+            from: ExtUser::Principal(caller()),
+            to: ExtUser::Principal(alice()),
+            token: token.clone(),
+            amount: 1_u64.into(),
+            memo: ByteBuf::default(),
+            notify: false,
+            subaccount: None,
+        };
+        assert_eq!(
+            App::ext_transfer(ExtTransferRequest {
+                from: ExtUser::Principal(bob()),
+                ..request.clone()
+            }),
+            ExtResult::Err(ExtTransferError::Unauthorized(Ext::account_identifier(
+                bob(),
+                None
+            )))
+        );
+        assert!(matches!(
+            App::ext_transfer(ExtTransferRequest {
+                to: ExtUser::Address(bob_identifier),
+                ..request.clone()
+            }),
+            ExtResult::Err(ExtTransferError::Other(_))
+        ));
+        assert_eq!(
+            App::ext_transfer(ExtTransferRequest {
+                from: ExtUser::Address(caller_identifier),
+                ..request
+            }),
+            ExtResult::Ok(Nat::from(1_u64))
+        );
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(alice()));
+        assert_eq!(
+            App::ext_tokens(Ext::account_identifier(caller(), None)),
+            ExtResult::Err(ExtCommonError::Other("No tokens".to_string()))
+        );
     }
 
-    #[test]
-    fn test_should_get_operator_of() {
+    #[tokio::test]
+    async fn test_should_mint_from_public_sale() {
         init_canister();
         store_mock_token(1);
-        assert_eq!(App::dip721_operator_of(1_u64.into()).unwrap(), None);
-        store_mock_token_with(2, |token| {
-            token.operator = Some(Principal::management_canister())
-        });
+        let now = crate::utils::time();
+        let sale = MintSale {
+            price: 1_000_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: bob(),
+            wallet_limit: 3,
+            allocation: 10,
+            starts_at: now,
+            ends_at: now + 3_600_000_000_000,
+        };
 
+        assert!(App::public_mint(1).await.is_err());
+        assert!(App::set_mint_sale(Some(MintSale {
+            ends_at: now,
+            ..sale.clone()
+        }))
+        .is_err());
+        assert!(App::set_mint_sale(Some(sale.clone())).is_ok());
+        assert_eq!(App::get_mint_sale(), Some(sale));
+
+        assert!(App::public_mint(0).await.is_err());
         assert_eq!(
-            App::dip721_operator_of(2_u64.into()).unwrap(),
-            Some(Principal::management_canister())
+            App::public_mint(2).await.unwrap(),
+            vec![TokenIdentifier::from(2_u64), TokenIdentifier::from(3_u64)]
         );
+        assert_eq!(App::dip721_owner_of(3_u64.into()).unwrap(), Some(caller()));
+        assert!(App::public_mint(2).await.is_err());
+        assert_eq!(App::get_sale_minted(), 2);
+        assert_eq!(App::get_sale_minted_by(caller()), 2);
 
-        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+        // a concurrent call by the same caller is rejected
+        let _guard = CallerGuard::new(caller()).unwrap();
+        assert!(App::public_mint(1).await.is_err());
+        assert_eq!(App::get_sale_minted(), 2);
     }
 
-    #[test]
-    fn test_should_get_operator_token_identifiers() {
+    #[tokio::test]
+    async fn test_should_check_sale_mints_before_charging() {
         init_canister();
-        // no owner
-        store_mock_token_with(1, |token| {
-            token.operator = None;
-        });
-        assert!(App::dip721_operator_token_identifiers(caller()).is_err());
+        let now = crate::utils::time();
+        App::set_mint_sale(Some(MintSale {
+            price: 1_000_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: bob(),
+            wallet_limit: 10,
+            allocation: 10,
+            starts_at: now,
+            ends_at: now + 3_600_000_000_000,
+        }))
+        .unwrap();
 
-        // with operator
-        store_mock_token_with(2, |token| {
-            token.operator = Some(Principal::management_canister())
-        });
+        // the identifiers reserved by a mint in progress are skipped, and can't be minted by custodians
+        let guard = MintGuard::new(vec![1_u64.into()]).unwrap();
         assert_eq!(
-            App::dip721_operator_token_identifiers(Principal::management_canister()).unwrap(),
-            vec![Nat::from(2_u64)]
+            App::public_mint(1).await.unwrap(),
+            vec![TokenIdentifier::from(2_u64)]
         );
-        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+        assert_eq!(
+            App::dip721_mint(caller(), 1_u64.into(), vec![]).unwrap_err(),
+            NftError::ExistedNFT
+        );
+        drop(guard);
+
+        // pool identifiers minted already fail the mint, which is released
+        assert!(App::set_random_pool(1).is_ok());
+        App::on_global_timer().await;
+        store_mock_token(1);
+        assert_eq!(App::public_mint(1).await.unwrap_err(), NftError::ExistedNFT);
+        assert_eq!(App::get_sale_minted(), 1);
+        assert_eq!(App::get_random_pool().unwrap().remaining, 1);
     }
 
-    #[test]
-    fn test_should_get_operator_token_metadata() {
+    #[tokio::test]
+    async fn test_should_mint_from_allowlist_phase() {
         init_canister();
-        // no owner
-        store_mock_token_with(1, |token| {
-            token.operator = None;
-        });
-        assert!(App::dip721_operator_token_metadata(caller()).is_err());
+        let now = crate::utils::time();
+        let leaf = Merkle::leaf(caller(), 2);
+        let sibling = Merkle::leaf(bob(), 1);
+        let phase = AllowlistPhase {
+            merkle_root: ByteBuf::from(Merkle::node(&leaf, &sibling).to_vec()),
+            price: 1_000_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: bob(),
+            starts_at: now,
+            ends_at: now + 3_600_000_000_000,
+        };
+        let proof = vec![ByteBuf::from(sibling.to_vec())];
 
-        // with operator
-        store_mock_token_with(2, |token| {
-            token.operator = Some(Principal::management_canister())
-        });
-        let metadata =
-            App::dip721_operator_token_metadata(Principal::management_canister()).unwrap();
-        assert_eq!(metadata.len(), 1);
-        assert_eq!(metadata[0].owner, Some(caller()));
-        assert_eq!(metadata[0].token_identifier, Nat::from(2_u64));
+        assert!(App::allowlist_mint(1, 1, 2, proof.clone()).await.is_err());
+        assert!(App::set_allowlist_phase(
+            1,
+            Some(AllowlistPhase {
+                merkle_root: ByteBuf::from(vec![0; 31]),
+                ..phase.clone()
+            })
+        )
+        .is_err());
+        assert!(App::set_allowlist_phase(1, Some(phase.clone())).is_ok());
+        assert_eq!(App::get_allowlist_phases(), vec![(1, phase)]);
 
-        assert!(App::dip721_operator_of(5_u64.into()).is_err());
+        // the max quantity is part of the proven entry
+        assert!(App::allowlist_mint(1, 3, 3, proof.clone()).await.is_err());
+        assert!(App::allowlist_mint(1, 1, 2, vec![]).await.is_err());
+        assert_eq!(
+            App::allowlist_mint(1, 2, 2, proof.clone())
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(App::allowlist_mint(1, 1, 2, proof).await.is_err());
+        assert_eq!(App::get_allowlist_minted(1, caller()), 2);
     }
 
     #[test]
-    fn test_should_get_supported_interfaces() {
+    fn test_should_redeem_voucher() {
+        use crate::app::signature::test_utils::{ed25519_key, ed25519_sign};
+
         init_canister();
+        let (key, public_key) = ed25519_key(1);
+        let properties = vec![(
+            "name".to_string(),
+            GenericValue::TextContent("nft".to_string()),
+        )];
+        let voucher = MintVoucher {
+            token_identifier: 1_u64.into(),
+            to: bob(),
+            properties_hash: ByteBuf::from(
+                Sha256::digest(candid::encode_one(&properties).unwrap()).to_vec(),
+            ),
+            canister_id: crate::utils::id(),
+            expires_at: u64::MAX,
+            nonce: 1,
+        };
+        let signature = ByteBuf::from(ed25519_sign(&key, &voucher.message()));
+        let public_key = ByteBuf::from(public_key);
+
+        // the signer must be registered
         assert_eq!(
-            App::dip721_supported_interfaces(),
-            vec![
-                SupportedInterface::Burn,
-                SupportedInterface::TransactionHistory,
-                SupportedInterface::Mint,
-                SupportedInterface::Approval,
-            ]
+            App::redeem_voucher(
+                voucher.clone(),
+                properties.clone(),
+                public_key.clone(),
+                signature.clone()
+            )
+            .unwrap_err(),
+            NftError::UnauthorizedOperator
         );
+        App::set_voucher_signers(vec![Principal::self_authenticating(&public_key)]);
+        assert!(App::redeem_voucher(
+            voucher.clone(),
+            vec![],
+            public_key.clone(),
+            signature.clone()
+        )
+        .is_err());
+        assert!(App::redeem_voucher(
+            MintVoucher {
+                to: caller(),
+                ..voucher.clone()
+            },
+            properties.clone(),
+            public_key.clone(),
+            signature.clone()
+        )
+        .is_err());
+        assert!(App::redeem_voucher(
+            voucher.clone(),
+            properties.clone(),
+            public_key.clone(),
+            signature.clone()
+        )
+        .is_ok());
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(bob()));
+        // the nonce can't be used twice
+        assert!(App::redeem_voucher(voucher, properties, public_key, signature).is_err());
     }
 
-    #[test]
-    fn test_should_get_total_supply() {
+    #[tokio::test]
+    async fn test_should_reveal_metadata() {
         init_canister();
+        let metadata = (0..3)
+            .map(|index| vec![("index".to_string(), GenericValue::Nat64Content(index))])
+            .collect::<Vec<_>>();
+        let metadata_hashes = metadata
+            .iter()
+            .map(|properties| ByteBuf::from(App::properties_hash(properties).to_vec()))
+            .collect::<Vec<_>>();
+        let hash = ByteBuf::from(App::provenance_hash(&metadata_hashes).to_vec());
+
+        assert!(App::reveal(metadata.clone()).await.is_err());
+        assert!(App::set_provenance_hash(ByteBuf::from(vec![1; 31])).is_err());
+        assert!(App::set_provenance_hash(hash.clone()).is_ok());
         store_mock_token(1);
         store_mock_token(2);
-        assert_eq!(App::dip721_total_supply(), Nat::from(2_u64));
+        assert!(App::set_provenance_hash(hash).is_err());
+        assert!(App::verify_reveal().is_err());
+
+        assert!(App::reveal(metadata[..2].to_vec()).await.is_err());
+        assert!(App::reveal(metadata.clone()).await.is_ok());
+        assert!(App::reveal(metadata.clone()).await.is_err());
+        assert!(App::verify_reveal().is_err());
+        App::on_global_timer().await;
+        let reveal = App::get_provenance().unwrap().reveal.unwrap();
+        assert_eq!(
+            App::dip721_token_metadata(2_u64.into()).unwrap().properties,
+            metadata[((1 + reveal.offset) % 3) as usize]
+        );
+        assert!(App::verify_reveal().unwrap());
+
+        assert!(App::set_token_property(
+            1_u64.into(),
+            "index".to_string(),
+            GenericValue::Nat64Content(9)
+        )
+        .is_ok());
+        assert!(!App::verify_reveal().unwrap());
     }
 
     #[tokio::test]
-    async fn test_should_transfer() {
+    async fn test_should_reveal_metadata_by_token_identifier() {
         init_canister();
-        store_mock_token(1);
-        // self transfer
-        assert!(App::dip721_transfer(caller(), 1_u64.into()).await.is_err());
+        let metadata = (0..4)
+            .map(|index| vec![("index".to_string(), GenericValue::Nat64Content(index))])
+            .collect::<Vec<_>>();
+        let metadata_hashes = metadata
+            .iter()
+            .map(|properties| ByteBuf::from(App::properties_hash(properties).to_vec()))
+            .collect::<Vec<_>>();
+        let hash = ByteBuf::from(App::provenance_hash(&metadata_hashes).to_vec());
+        assert!(App::set_provenance_hash(hash).is_ok());
+        store_mock_token(2);
+        store_mock_token(4);
 
-        // transfer
-        assert!(
-            App::dip721_transfer(Principal::management_canister(), 1_u64.into())
-                .await
-                .is_ok()
-        );
-        assert!(App::dip721_balance_of(caller()).is_err());
+        assert!(App::reveal(metadata.clone()).await.is_ok());
+        App::on_global_timer().await;
+        let offset = App::get_provenance().unwrap().reveal.unwrap().offset;
+        for id in [2_u64, 4] {
+            assert_eq!(
+                App::dip721_token_metadata(id.into()).unwrap().properties,
+                metadata[((id - 1 + offset) % 4) as usize]
+            );
+        }
+        assert!(App::verify_reveal().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_should_mint_from_random_pool() {
+        init_canister();
+        let now = crate::utils::time();
+        App::set_mint_sale(Some(MintSale {
+            price: 0_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: bob(),
+            wallet_limit: 10,
+            allocation: 10,
+            starts_at: now,
+            ends_at: now + 3_600_000_000_000,
+        }))
+        .unwrap();
+        assert!(App::set_random_pool(4).is_ok());
+
+        // the CSPRNG is not seeded yet, and the reserved supply is released
+        assert!(App::public_mint(1).await.is_err());
+        assert_eq!(App::get_sale_minted(), 0);
+
+        App::on_global_timer().await;
+        let mut minted = App::public_mint(3).await.unwrap();
+        assert_eq!(App::get_random_pool().unwrap().remaining, 1);
+        assert!(App::set_random_pool(8).is_err());
+        assert!(App::public_mint(2).await.is_err());
+        assert_eq!(App::get_random_pool().unwrap().remaining, 1);
+
+        minted.extend(App::public_mint(1).await.unwrap());
+        minted.sort();
         assert_eq!(
-            App::dip721_balance_of(Principal::management_canister()).unwrap(),
-            Nat::from(1_u64)
-        );
-        // transfer unexisting
-        assert!(
-            App::dip721_transfer(Principal::management_canister(), 5_u64.into())
-                .await
-                .is_err()
+            minted,
+            (1_u64..=4).map(TokenIdentifier::from).collect::<Vec<_>>()
         );
     }
 
     #[test]
-    fn test_should_burn() {
+    fn test_should_not_mint_identifiers_left_in_random_pool() {
         init_canister();
-        store_mock_token(1);
-        assert!(App::dip721_burn(1_u64.into()).is_ok());
-        assert!(App::dip721_balance_of(caller()).is_err());
-        assert!(App::dip721_burn(1_u64.into()).is_err());
-        assert!(App::dip721_burn(5_u64.into()).is_err());
+        assert!(App::set_random_pool(4).is_ok());
+
+        assert!(App::dip721_mint(alice(), 2_u64.into(), vec![]).is_err());
+        assert!(App::dip721_mint(alice(), 5_u64.into(), vec![]).is_ok());
+        assert_eq!(App::get_random_pool().unwrap().remaining, 4);
     }
 
     #[test]
-    fn test_should_approve() {
+    fn test_should_set_user() {
         init_canister();
         store_mock_token(1);
-        assert!(App::dip721_approve(bob(), 1_u64.into()).is_ok());
+        store_mock_token_with(2, |token| {
+            token.owner = Some(bob());
+        });
+        let expires_at = crate::utils::time() + 3_600_000_000_000;
 
-        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(bob());
-        assert_eq!(tokens_with_bob_as_op, vec![Nat::from(1_u64)]);
+        assert!(App::set_user(2_u64.into(), bob(), expires_at).is_err());
+        assert!(App::user_of(3_u64.into()).is_err());
+        assert!(App::set_user(1_u64.into(), bob(), expires_at).is_ok());
+        assert_eq!(App::user_of(1_u64.into()).unwrap(), Some(bob()));
+        assert_eq!(App::user_expires(1_u64.into()).unwrap(), expires_at);
+        let tx_id = App::set_user(1_u64.into(), bob(), 0).unwrap();
+        assert_eq!(App::user_of(1_u64.into()).unwrap(), None);
+        assert_eq!(
+            App::dip721_transaction(tx_id).unwrap().operation,
+            "set_user"
+        );
+
+        // the user is removed on transfer
+        assert!(App::set_user(1_u64.into(), bob(), expires_at).is_ok());
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1_u64.into(), bob()).is_ok());
+        assert_eq!(App::user_of(1_u64.into()).unwrap(), None);
+        assert_eq!(App::user_expires(1_u64.into()).unwrap(), 0);
     }
 
     #[test]
-    fn test_should_approve_for_all() {
+    fn test_should_mint_in_collections() {
         init_canister();
         store_mock_token(1);
-        store_mock_token(2);
-        assert!(App::dip721_set_approval_for_all(bob(), true).is_ok());
-
-        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(bob());
+        let args = CreateCollectionArgs {
+            name: "collection".to_string(),
+            symbol: "COL".to_string(),
+            logo: Some("data:image/png;base64,aGVsbG8=".to_string()),
+            custodians: vec![caller()],
+            supported_interfaces: vec![SupportedInterface::Mint, SupportedInterface::Burn],
+            max_supply: Some(2),
+        };
+        let collection_id = App::create_collection(args.clone()).unwrap();
+        assert!(App::create_collection(CreateCollectionArgs {
+            logo: Some("not base64!".to_string()),
+            ..args
+        })
+        .is_err());
         assert_eq!(
-            tokens_with_bob_as_op,
-            vec![Nat::from(1_u64), Nat::from(2_u64)]
+            App::get_collection(collection_id)
+                .unwrap()
+                .logo
+                .unwrap()
+                .data
+                .as_slice(),
+            b"hello"
         );
+        assert_eq!(App::get_collections().len(), 2);
 
-        assert!(App::dip721_set_approval_for_all(bob(), false).is_ok());
+        // identifiers are scoped by collection
+        assert!(App::collection_mint(collection_id, caller(), 1_u64.into(), vec![]).is_ok());
+        assert!(App::collection_mint(collection_id, alice(), 2_u64.into(), vec![]).is_ok());
+        assert!(App::collection_mint(collection_id, alice(), 3_u64.into(), vec![]).is_err());
+        assert_eq!(
+            App::collection_total_supply(collection_id).unwrap(),
+            Nat::from(2_u64)
+        );
+        assert_eq!(App::dip721_total_supply(), Nat::from(1_u64));
+        assert!(App::collection_total_supply(collection_id + 1).is_err());
+        assert_eq!(
+            App::collection_balance_of(collection_id, alice()).unwrap(),
+            Nat::from(1_u64)
+        );
 
-        let tokens_with_bob_as_op = TokensStorage::tokens_by_operator(bob());
-        assert!(tokens_with_bob_as_op.is_empty());
+        assert!(App::collection_transfer(collection_id, bob(), 1_u64.into()).is_ok());
+        assert!(App::collection_transfer(collection_id, bob(), 2_u64.into()).is_err());
+        assert_eq!(
+            App::collection_owner_of(collection_id, 1_u64.into()).unwrap(),
+            Some(bob())
+        );
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(caller()));
+        assert!(App::collection_burn(collection_id, 2_u64.into()).is_err());
+        assert_eq!(
+            App::collection_owner_token_identifiers(collection_id, bob()).unwrap(),
+            vec![TokenIdentifier::from(1_u64)]
+        );
     }
 
     #[test]
-    fn test_should_tell_if_approved_for_all() {
+    fn test_should_attach_children() {
         init_canister();
         store_mock_token(1);
         store_mock_token(2);
-        assert!(App::dip721_set_approval_for_all(bob(), true).is_ok());
-        assert!(App::dip721_is_approved_for_all(caller(), bob()).unwrap());
-        assert!(
-            !App::dip721_is_approved_for_all(caller(), Principal::management_canister()).unwrap()
+        store_mock_token_with(3, |token| {
+            token.owner = Some(bob());
+        });
+
+        assert!(App::attach_child(1_u64.into(), 3_u64.into()).is_err());
+        assert!(App::attach_child(3_u64.into(), 2_u64.into()).is_err());
+        assert!(App::attach_child(4_u64.into(), 2_u64.into()).is_err());
+        let tx_id = App::attach_child(1_u64.into(), 2_u64.into()).unwrap();
+        assert_eq!(
+            App::dip721_transaction(tx_id).unwrap().operation,
+            "attach_child"
+        );
+        assert_eq!(
+            App::get_token_children(1_u64.into()).unwrap(),
+            vec![TokenIdentifier::from(2_u64)]
         );
+        assert_eq!(App::root_owner_of(2_u64.into()).unwrap(), Some(caller()));
 
-        store_mock_token(3);
-        assert!(!App::dip721_is_approved_for_all(caller(), bob()).unwrap());
+        // attached tokens can't be transferred on their own
+        assert!(App::transfer_from(
+            DEFAULT_COLLECTION_ID,
+            caller(),
+            caller(),
+            bob(),
+            2_u64.into()
+        )
+        .is_err());
+        assert!(App::transfer_from(
+            DEFAULT_COLLECTION_ID,
+            caller(),
+            caller(),
+            bob(),
+            1_u64.into()
+        )
+        .is_ok());
+        assert_eq!(App::dip721_owner_of(2_u64.into()).unwrap(), Some(bob()));
+        assert!(App::detach_child(1_u64.into(), 2_u64.into()).is_err());
     }
 
     #[test]
-    fn test_should_get_tx() {
+    fn test_should_transfer_batch() {
         init_canister();
-        assert!(App::dip721_transaction(Nat::from(1_u64)).is_err());
-        let id = TxHistory::register_token_mint(&mock_token(1));
-        assert!(App::dip721_transaction(id).is_ok());
-    }
+        store_mock_token(1);
+        store_mock_token(2);
+        store_mock_token_with(3, |token| {
+            token.owner = Some(bob());
+        });
 
-    #[test]
-    fn test_should_get_total_transactions() {
-        init_canister();
-        assert_eq!(App::dip721_total_transactions(), Nat::from(0_u64));
-        let _ = TxHistory::register_token_mint(&mock_token(1));
-        assert_eq!(App::dip721_total_transactions(), Nat::from(1_u64));
+        // a single invalid transfer fails the whole batch
+        assert!(App::transfer_batch(vec![]).is_err());
+        assert!(App::transfer_batch(vec![
+            (caller(), alice(), 1_u64.into()),
+            (bob(), alice(), 3_u64.into()),
+        ])
+        .is_err());
+        assert!(App::transfer_batch(vec![
+            (caller(), alice(), 1_u64.into()),
+            (caller(), bob(), 1_u64.into()),
+        ])
+        .is_err());
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(caller()));
+
+        let batch = App::transfer_batch(vec![
+            (caller(), alice(), 1_u64.into()),
+            (caller(), bob(), 2_u64.into()),
+        ])
+        .unwrap();
+        assert_eq!(batch.tx_ids.len(), 2);
+        assert_eq!(App::dip721_owner_of(1_u64.into()).unwrap(), Some(alice()));
+        assert_eq!(App::dip721_owner_of(2_u64.into()).unwrap(), Some(bob()));
+        let event = App::dip721_transaction(batch.batch_id).unwrap();
+        assert_eq!(event.operation, "transfer_batch");
+        assert_eq!(
+            event.details,
+            batch
+                .tx_ids
+                .into_iter()
+                .map(|tx_id| ("tx_id".to_string(), GenericValue::NatContent(tx_id)))
+                .collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn test_should_set_token_property() {
+    fn test_should_mint_editions() {
         init_canister();
         store_mock_token(1);
-        assert!(App::set_token_property(
-            1_u64.into(),
-            "key".to_string(),
-            GenericValue::BoolContent(true)
+        let template_id = App::create_edition_template(
+            vec![(
+                "name".to_string(),
+                GenericValue::TextContent("artwork".to_string()),
+            )],
+            Some(2),
         )
-        .is_ok());
+        .unwrap();
+        assert!(App::create_edition_template(vec![], Some(0)).is_err());
 
-        let metadata = App::dip721_token_metadata(1_u64.into()).unwrap();
+        assert_eq!(
+            App::mint_edition(alice(), template_id, vec![]).unwrap(),
+            TokenIdentifier::from(2_u64)
+        );
+        assert_eq!(
+            App::mint_edition(bob(), template_id, vec![]).unwrap(),
+            TokenIdentifier::from(3_u64)
+        );
+        assert!(App::mint_edition(bob(), template_id, vec![]).is_err());
+        assert!(App::mint_edition(bob(), template_id + 1, vec![]).is_err());
+        assert_eq!(App::get_edition_count(template_id).unwrap(), 2);
+        assert!(App::get_edition_count(template_id + 1).is_err());
+
+        assert_eq!(
+            App::get_token_edition(3_u64.into()).unwrap(),
+            Some(TokenEdition {
+                template_id,
+                edition: 2
+            })
+        );
+        assert_eq!(App::get_token_edition(1_u64.into()).unwrap(), None);
+        let metadata = App::dip721_token_metadata(3_u64.into()).unwrap();
+        assert_eq!(metadata.owner, Some(bob()));
         assert_eq!(
             metadata.properties,
-            vec![("key".to_string(), GenericValue::BoolContent(true))]
+            vec![
+                (
+                    "name".to_string(),
+                    GenericValue::TextContent("artwork".to_string())
+                ),
+                (
+                    "edition_template".to_string(),
+                    GenericValue::Nat64Content(template_id)
+                ),
+                ("edition".to_string(), GenericValue::Nat64Content(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_not_mint_edition_with_reserved_identifier() {
+        init_canister();
+        let template_id = App::create_edition_template(vec![], None).unwrap();
+        // a paid mint awaiting its payment holds the next identifier
+        let guard = MintGuard::new(vec![TokenIdentifier::from(1_u64)]).unwrap();
+        assert_eq!(
+            App::mint_edition(alice(), template_id, vec![]).unwrap(),
+            TokenIdentifier::from(2_u64)
+        );
+        drop(guard);
+        assert_eq!(
+            App::mint_edition(alice(), template_id, vec![]).unwrap(),
+            TokenIdentifier::from(3_u64)
         );
     }
 
@@ -750,6 +4194,7 @@ mod test {
             logo: Some("logo".to_string()),
             name: "nft".to_string(),
             symbol: "NFT".to_string(),
+            provenance_hash: None,
         });
     }
 }
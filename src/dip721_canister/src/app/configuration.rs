@@ -1,66 +1,147 @@
 use std::cell::RefCell;
 
-use crate::storable::{StorablePrincipal, StorableSupportedInterface};
+use crate::did::{
+    AllowlistPhase, Collection, CollectionId, Logo, MarketplaceSettings, MintSale, Provenance,
+    Royalty, TokenJsonMapping, DEFAULT_COLLECTION_ID,
+};
+use crate::storable::{StorablePrincipal, StorableStrings, StorableSupportedInterface};
 use candid::Principal;
 use dip721_rs::SupportedInterface;
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{DefaultMemoryImpl, StableCell, StableVec};
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell, StableVec};
 
 use crate::app::memory::{
-    CREATED_AT_MEMORY_ID, CUSTODIANS_MEMORY_ID, LOGO_MEMORY_ID, MEMORY_MANAGER, NAME_MEMORY_ID,
-    SUPPORTED_INTERFACES_MEMORY_ID, SYMBOL_MEMORY_ID, UPGRADED_AT_MEMORY_ID,
+    ALLOWED_ORIGINS_MEMORY_ID, ALLOWLIST_PHASES_MEMORY_ID, BINARY_LOGO_MEMORY_ID,
+    COLLECTIONS_MEMORY_ID, COLLECTION_ROYALTY_MEMORY_ID, CREATED_AT_MEMORY_ID,
+    CUSTODIANS_MEMORY_ID, HTTP_BASE_URL_MEMORY_ID, HTTP_MINTERS_MEMORY_ID, LOGO_MEMORY_ID,
+    MARKETPLACE_SETTINGS_MEMORY_ID, MEMORY_MANAGER, MINT_SALE_MEMORY_ID, NAME_MEMORY_ID,
+    PROVENANCE_MEMORY_ID, SUPPORTED_INTERFACES_MEMORY_ID, SYMBOL_MEMORY_ID,
+    TOKEN_JSON_MAPPING_MEMORY_ID, UPGRADED_AT_MEMORY_ID, VOUCHER_SIGNERS_MEMORY_ID,
 };
 
 thread_local! {
+    /// Contract logo as base64 text; superseded by `BINARY_LOGO` and only read to migrate it
+    static LEGACY_LOGO: RefCell<StableCell<Option<String>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(LOGO_MEMORY_ID)), None).unwrap()
+    );
+
     /// Contract logo
-    static LOGO: RefCell<StableCell<Option<String>, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(LOGO_MEMORY_ID)), None).unwrap()
+    static BINARY_LOGO: RefCell<StableCell<Option<Logo>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(BINARY_LOGO_MEMORY_ID)), None).unwrap()
     );
 
     /// Contract name
     static NAME: RefCell<StableCell<Option<String>, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NAME_MEMORY_ID)), None).unwrap()
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(NAME_MEMORY_ID)), None).unwrap()
     );
 
     /// Contract symbol
     static SYMBOL: RefCell<StableCell<Option<String>, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(SYMBOL_MEMORY_ID)), None).unwrap()
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(SYMBOL_MEMORY_ID)), None).unwrap()
     );
 
     /// Contract creation timestamp
     static CREATED_AT: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(CREATED_AT_MEMORY_ID)), crate::utils::time()).unwrap()
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(CREATED_AT_MEMORY_ID)), crate::utils::time()).unwrap()
     );
 
     /// Contract last upgrade timestamp
     static UPGRADED_AT: RefCell<StableCell<Option<u64>, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(UPGRADED_AT_MEMORY_ID)), None).unwrap()
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(UPGRADED_AT_MEMORY_ID)), None).unwrap()
     );
 
     /// Canister custodians
     static CUSTODIANS: RefCell<StableVec<StorablePrincipal, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableVec::new(MEMORY_MANAGER.with(|mm| mm.get(CUSTODIANS_MEMORY_ID))).unwrap()
+        RefCell::new(StableVec::init(MEMORY_MANAGER.with(|mm| mm.get(CUSTODIANS_MEMORY_ID))).unwrap()
     );
 
     /// Canister custodians
     static SUPPORTED_INTERFACES: RefCell<StableVec<StorableSupportedInterface, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(StableVec::new(MEMORY_MANAGER.with(|mm| mm.get(SUPPORTED_INTERFACES_MEMORY_ID))).unwrap()
+        RefCell::new(StableVec::init(MEMORY_MANAGER.with(|mm| mm.get(SUPPORTED_INTERFACES_MEMORY_ID))).unwrap()
+    );
+
+    /// Principals of the keys allowed to mint with signed HTTP requests
+    static HTTP_MINTERS: RefCell<StableVec<StorablePrincipal, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableVec::init(MEMORY_MANAGER.with(|mm| mm.get(HTTP_MINTERS_MEMORY_ID))).unwrap()
+    );
+
+    /// Principals of the keys allowed to sign mint vouchers
+    static VOUCHER_SIGNERS: RefCell<StableVec<StorablePrincipal, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableVec::init(MEMORY_MANAGER.with(|mm| mm.get(VOUCHER_SIGNERS_MEMORY_ID))).unwrap()
+    );
+
+    /// Base URL the canister is served at over HTTP, if not the default one
+    static HTTP_BASE_URL: RefCell<StableCell<Option<String>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(HTTP_BASE_URL_MEMORY_ID)), None).unwrap()
+    );
+
+    /// Origins allowed to call the HTTP interface from a browser
+    static ALLOWED_ORIGINS: RefCell<StableCell<StorableStrings, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(ALLOWED_ORIGINS_MEMORY_ID)), StorableStrings::default()).unwrap()
+    );
+
+    /// Royalty of the tokens which don't have their own
+    static COLLECTION_ROYALTY: RefCell<StableCell<Option<Royalty>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(COLLECTION_ROYALTY_MEMORY_ID)), None).unwrap()
+    );
+
+    /// Settings of the canister marketplace
+    static MARKETPLACE_SETTINGS: RefCell<StableCell<MarketplaceSettings, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(MARKETPLACE_SETTINGS_MEMORY_ID)), MarketplaceSettings::default()).unwrap()
+    );
+
+    /// Public sale of newly minted tokens
+    static MINT_SALE: RefCell<StableCell<Option<MintSale>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(MINT_SALE_MEMORY_ID)), None).unwrap()
+    );
+
+    /// Allowlist minting phases, by id
+    static ALLOWLIST_PHASES: RefCell<BTreeMap<u32, AllowlistPhase, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(ALLOWLIST_PHASES_MEMORY_ID)))
+    );
+
+    /// Provenance of the final metadata of a delayed reveal drop
+    static PROVENANCE: RefCell<StableCell<Option<Provenance>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(PROVENANCE_MEMORY_ID)), None).unwrap()
+    );
+
+    /// Collections hosted beside the default one, by id
+    static COLLECTIONS: RefCell<BTreeMap<CollectionId, Collection, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(COLLECTIONS_MEMORY_ID)))
     );
 
+    /// Mapping of token properties to the marketplace metadata JSON
+    static TOKEN_JSON_MAPPING: RefCell<StableCell<TokenJsonMapping, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_JSON_MAPPING_MEMORY_ID)), TokenJsonMapping::default()).unwrap()
+    );
 }
 
 pub struct Configuration;
 
 impl Configuration {
-    pub fn get_logo() -> Option<String> {
-        LOGO.with_borrow(|logo| logo.get().clone())
+    pub fn get_logo() -> Option<Logo> {
+        BINARY_LOGO.with_borrow(|logo| logo.get().clone())
     }
 
-    pub fn set_logo(logo: String) {
-        LOGO.with_borrow_mut(|cell| cell.set(Some(logo)))
+    pub fn set_logo(logo: Logo) {
+        BINARY_LOGO
+            .with_borrow_mut(|cell| cell.set(Some(logo)))
             .expect("failed to set logo");
     }
 
+    /// Move the logo stored as base64 text by previous versions to the binary logo
+    pub fn migrate_legacy_logo() {
+        let Some(legacy_logo) = LEGACY_LOGO.with_borrow(|cell| cell.get().clone()) else {
+            return;
+        };
+        if Self::get_logo().is_none() {
+            Self::set_logo(Logo::from_text(&legacy_logo));
+        }
+        LEGACY_LOGO
+            .with_borrow_mut(|cell| cell.set(None))
+            .expect("failed to clear legacy logo");
+    }
+
     pub fn get_name() -> Option<String> {
         NAME.with_borrow(|name| name.get().clone())
     }
@@ -96,6 +177,72 @@ impl Configuration {
             .expect("failed to set upgraded_at");
     }
 
+    pub fn get_token_json_mapping() -> TokenJsonMapping {
+        TOKEN_JSON_MAPPING.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_token_json_mapping(mapping: TokenJsonMapping) {
+        TOKEN_JSON_MAPPING
+            .with_borrow_mut(|cell| cell.set(mapping))
+            .expect("failed to set token json mapping");
+    }
+
+    pub fn get_collection_royalty() -> Option<Royalty> {
+        COLLECTION_ROYALTY.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_collection_royalty(royalty: Option<Royalty>) {
+        COLLECTION_ROYALTY
+            .with_borrow_mut(|cell| cell.set(royalty))
+            .expect("failed to set collection royalty");
+    }
+
+    pub fn get_marketplace_settings() -> MarketplaceSettings {
+        MARKETPLACE_SETTINGS.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_marketplace_settings(settings: MarketplaceSettings) {
+        MARKETPLACE_SETTINGS
+            .with_borrow_mut(|cell| cell.set(settings))
+            .expect("failed to set marketplace settings");
+    }
+
+    pub fn get_mint_sale() -> Option<MintSale> {
+        MINT_SALE.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_mint_sale(sale: Option<MintSale>) {
+        MINT_SALE
+            .with_borrow_mut(|cell| cell.set(sale))
+            .expect("failed to set mint sale");
+    }
+
+    pub fn get_allowlist_phase(id: u32) -> Option<AllowlistPhase> {
+        ALLOWLIST_PHASES.with_borrow(|phases| phases.get(&id))
+    }
+
+    pub fn get_allowlist_phases() -> Vec<(u32, AllowlistPhase)> {
+        ALLOWLIST_PHASES.with_borrow(|phases| phases.iter().collect())
+    }
+
+    /// Set the allowlist phase, or remove it if `phase` is `None`
+    pub fn set_allowlist_phase(id: u32, phase: Option<AllowlistPhase>) {
+        ALLOWLIST_PHASES.with_borrow_mut(|phases| match phase {
+            Some(phase) => phases.insert(id, phase),
+            None => phases.remove(&id),
+        });
+    }
+
+    pub fn get_provenance() -> Option<Provenance> {
+        PROVENANCE.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_provenance(provenance: Provenance) {
+        PROVENANCE
+            .with_borrow_mut(|cell| cell.set(Some(provenance)))
+            .expect("failed to set provenance");
+    }
+
     pub fn set_custodians(custodians: &[Principal]) {
         CUSTODIANS.with_borrow_mut(|cell| {
             for _ in 0..cell.len() {
@@ -125,6 +272,87 @@ impl Configuration {
         })
     }
 
+    pub fn set_http_minters(minters: &[Principal]) {
+        HTTP_MINTERS.with_borrow_mut(|cell| {
+            for _ in 0..cell.len() {
+                cell.pop();
+            }
+            for minter in minters
+                .iter()
+                .map(|principal| StorablePrincipal::from(*principal))
+            {
+                cell.push(&minter).expect("failed to push");
+            }
+        });
+    }
+
+    pub fn get_http_minters() -> Vec<Principal> {
+        HTTP_MINTERS.with_borrow(|cell| cell.iter().map(|minter| *minter.as_principal()).collect())
+    }
+
+    pub fn is_http_minter(principal: Principal) -> bool {
+        HTTP_MINTERS.with_borrow(|cell| {
+            cell.iter()
+                .any(|minter| minter.as_principal() == &principal)
+        })
+    }
+
+    pub fn set_voucher_signers(signers: &[Principal]) {
+        VOUCHER_SIGNERS.with_borrow_mut(|cell| {
+            for _ in 0..cell.len() {
+                cell.pop();
+            }
+            for signer in signers
+                .iter()
+                .map(|principal| StorablePrincipal::from(*principal))
+            {
+                cell.push(&signer).expect("failed to push");
+            }
+        });
+    }
+
+    pub fn get_voucher_signers() -> Vec<Principal> {
+        VOUCHER_SIGNERS
+            .with_borrow(|cell| cell.iter().map(|signer| *signer.as_principal()).collect())
+    }
+
+    pub fn is_voucher_signer(principal: Principal) -> bool {
+        VOUCHER_SIGNERS.with_borrow(|cell| {
+            cell.iter()
+                .any(|signer| signer.as_principal() == &principal)
+        })
+    }
+
+    pub fn get_http_base_url() -> Option<String> {
+        HTTP_BASE_URL.with_borrow(|cell| cell.get().clone())
+    }
+
+    pub fn set_http_base_url(url: Option<String>) {
+        HTTP_BASE_URL
+            .with_borrow_mut(|cell| cell.set(url))
+            .expect("failed to set http base url");
+    }
+
+    pub fn get_allowed_origins() -> Vec<String> {
+        ALLOWED_ORIGINS.with_borrow(|cell| cell.get().0.clone())
+    }
+
+    pub fn set_allowed_origins(origins: Vec<String>) {
+        ALLOWED_ORIGINS
+            .with_borrow_mut(|cell| cell.set(StorableStrings(origins)))
+            .expect("failed to set allowed origins");
+    }
+
+    /// Returns whether the origin is allowed; the `*` wildcard allows any origin
+    pub fn is_allowed_origin(origin: &str) -> bool {
+        ALLOWED_ORIGINS.with_borrow(|cell| {
+            cell.get()
+                .0
+                .iter()
+                .any(|allowed| allowed == "*" || allowed == origin)
+        })
+    }
+
     pub fn set_supported_interfaces(supported_interfaces: &[SupportedInterface]) {
         SUPPORTED_INTERFACES.with_borrow_mut(|cell| {
             for _ in 0..cell.len() {
@@ -154,6 +382,74 @@ impl Configuration {
             })
         })
     }
+
+    /// Insert a new collection and returns its id; ids start from 1, as 0 is the default collection
+    pub fn create_collection(collection: Collection) -> CollectionId {
+        COLLECTIONS.with_borrow_mut(|collections| {
+            let id = collections
+                .last_key_value()
+                .map(|(id, _)| id + 1)
+                .unwrap_or(DEFAULT_COLLECTION_ID + 1);
+            collections.insert(id, collection);
+
+            id
+        })
+    }
+
+    /// Get the collection with the provided id; the default collection is the canister configuration
+    pub fn get_collection(collection_id: CollectionId) -> Option<Collection> {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return Some(Collection {
+                name: Self::get_name().unwrap_or_default(),
+                symbol: Self::get_symbol().unwrap_or_default(),
+                logo: Self::get_logo(),
+                custodians: Self::get_custodians(),
+                supported_interfaces: Self::get_supported_interfaces(),
+                max_supply: None,
+                created_at: Self::get_created_at(),
+            });
+        }
+        COLLECTIONS.with_borrow(|collections| collections.get(&collection_id))
+    }
+
+    /// Get all the collections, starting from the default one
+    pub fn get_collections() -> Vec<(CollectionId, Collection)> {
+        let default_collection = Self::get_collection(DEFAULT_COLLECTION_ID)
+            .map(|collection| (DEFAULT_COLLECTION_ID, collection));
+        COLLECTIONS.with_borrow(|collections| {
+            default_collection
+                .into_iter()
+                .chain(collections.iter())
+                .collect()
+        })
+    }
+
+    /// Get the supply cap of the collection; the default collection has none
+    pub fn get_collection_max_supply(collection_id: CollectionId) -> Option<u64> {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return None;
+        }
+        Self::get_collection(collection_id).and_then(|collection| collection.max_supply)
+    }
+
+    pub fn is_collection_custodian(collection_id: CollectionId, principal: Principal) -> bool {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return Self::is_custodian(principal);
+        }
+        Self::get_collection(collection_id)
+            .is_some_and(|collection| collection.custodians.contains(&principal))
+    }
+
+    pub fn collection_has_interface(
+        collection_id: CollectionId,
+        interface: SupportedInterface,
+    ) -> bool {
+        if collection_id == DEFAULT_COLLECTION_ID {
+            return Self::has_interface(interface);
+        }
+        Self::get_collection(collection_id)
+            .is_some_and(|collection| collection.supported_interfaces.contains(&interface))
+    }
 }
 
 #[cfg(test)]
@@ -162,14 +458,34 @@ mod test {
     use std::time::Duration;
 
     use pretty_assertions::assert_eq;
+    use serde_bytes::ByteBuf;
 
     use super::*;
+    use crate::did::{PlatformFee, RoyaltyRecipient};
 
     #[test]
     fn test_should_get_and_set_logo() {
         assert!(Configuration::get_logo().is_none());
-        Configuration::set_logo("new logo".to_string());
-        assert_eq!(Configuration::get_logo().unwrap().as_str(), "new logo");
+        let logo = Logo {
+            content_type: "image/png".to_string(),
+            data: ByteBuf::from(vec![0x89, b'P', b'N', b'G']),
+            encoding: None,
+        };
+        Configuration::set_logo(logo.clone());
+        assert_eq!(Configuration::get_logo().unwrap(), logo);
+    }
+
+    #[test]
+    fn test_should_migrate_legacy_logo() {
+        LEGACY_LOGO
+            .with_borrow_mut(|cell| cell.set(Some("data:image/png;base64,aGVsbG8=".to_string())))
+            .unwrap();
+        Configuration::migrate_legacy_logo();
+
+        let logo = Configuration::get_logo().unwrap();
+        assert_eq!(logo.content_type, "image/png");
+        assert_eq!(logo.data.as_slice(), b"hello");
+        assert!(LEGACY_LOGO.with_borrow(|cell| cell.get().is_none()));
     }
 
     #[test]
@@ -186,6 +502,50 @@ mod test {
         assert_eq!(Configuration::get_symbol().unwrap().as_str(), "NFTT");
     }
 
+    #[test]
+    fn test_should_create_collections() {
+        Configuration::set_name("default".to_string());
+        Configuration::set_custodians(&[Principal::anonymous()]);
+        let collection = Collection {
+            name: "collection".to_string(),
+            symbol: "COL".to_string(),
+            logo: None,
+            custodians: vec![Principal::management_canister()],
+            supported_interfaces: vec![SupportedInterface::Mint],
+            max_supply: Some(10),
+            created_at: 0,
+        };
+        assert_eq!(Configuration::create_collection(collection.clone()), 1);
+        assert_eq!(Configuration::get_collection(1).unwrap(), collection);
+        assert!(Configuration::get_collection(2).is_none());
+
+        let collections = Configuration::get_collections();
+        assert_eq!(collections.len(), 2);
+        assert_eq!(collections[0].0, DEFAULT_COLLECTION_ID);
+        assert_eq!(collections[0].1.name, "default");
+
+        assert!(Configuration::is_collection_custodian(
+            DEFAULT_COLLECTION_ID,
+            Principal::anonymous()
+        ));
+        assert!(!Configuration::is_collection_custodian(
+            1,
+            Principal::anonymous()
+        ));
+        assert!(Configuration::is_collection_custodian(
+            1,
+            Principal::management_canister()
+        ));
+        assert!(Configuration::collection_has_interface(
+            1,
+            SupportedInterface::Mint
+        ));
+        assert!(!Configuration::collection_has_interface(
+            1,
+            SupportedInterface::Burn
+        ));
+    }
+
     #[test]
     fn test_should_get_created_at() {
         assert!(Configuration::get_created_at() <= crate::utils::time());
@@ -200,6 +560,99 @@ mod test {
         assert!(Configuration::get_upgraded_at() > last_upgrade);
     }
 
+    #[test]
+    fn test_should_get_and_set_token_json_mapping() {
+        assert_eq!(
+            Configuration::get_token_json_mapping(),
+            TokenJsonMapping::default()
+        );
+        let mapping = TokenJsonMapping {
+            name: Some("title".to_string()),
+            excluded_attributes: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        Configuration::set_token_json_mapping(mapping.clone());
+        assert_eq!(Configuration::get_token_json_mapping(), mapping);
+    }
+
+    #[test]
+    fn test_should_get_and_set_collection_royalty() {
+        assert!(Configuration::get_collection_royalty().is_none());
+        let royalty = Royalty {
+            basis_points: 500,
+            recipients: vec![RoyaltyRecipient {
+                recipient: Principal::anonymous(),
+                share: 10_000,
+            }],
+        };
+        Configuration::set_collection_royalty(Some(royalty.clone()));
+        assert_eq!(Configuration::get_collection_royalty(), Some(royalty));
+        Configuration::set_collection_royalty(None);
+        assert!(Configuration::get_collection_royalty().is_none());
+    }
+
+    #[test]
+    fn test_should_get_and_set_marketplace_settings() {
+        assert_eq!(
+            Configuration::get_marketplace_settings(),
+            MarketplaceSettings::default()
+        );
+        let settings = MarketplaceSettings {
+            ledgers: vec![Principal::from_slice(&[1; 29])],
+            platform_fee: Some(PlatformFee {
+                basis_points: 250,
+                recipient: Principal::anonymous(),
+            }),
+        };
+        Configuration::set_marketplace_settings(settings.clone());
+        assert_eq!(Configuration::get_marketplace_settings(), settings);
+    }
+
+    #[test]
+    fn test_should_get_and_set_mint_sale() {
+        assert!(Configuration::get_mint_sale().is_none());
+        let sale = MintSale {
+            price: 100_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: Principal::anonymous(),
+            wallet_limit: 2,
+            allocation: 100,
+            starts_at: 0,
+            ends_at: 1,
+        };
+        Configuration::set_mint_sale(Some(sale.clone()));
+        assert_eq!(Configuration::get_mint_sale(), Some(sale));
+    }
+
+    #[test]
+    fn test_should_get_and_set_allowlist_phases() {
+        let phase = AllowlistPhase {
+            merkle_root: ByteBuf::from(vec![0; 32]),
+            price: 100_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: Principal::anonymous(),
+            starts_at: 0,
+            ends_at: 1,
+        };
+        Configuration::set_allowlist_phase(1, Some(phase.clone()));
+        assert_eq!(Configuration::get_allowlist_phase(1), Some(phase.clone()));
+        assert_eq!(Configuration::get_allowlist_phases(), vec![(1, phase)]);
+        Configuration::set_allowlist_phase(1, None);
+        assert!(Configuration::get_allowlist_phase(1).is_none());
+    }
+
+    #[test]
+    fn test_should_get_and_set_provenance() {
+        assert!(Configuration::get_provenance().is_none());
+        let provenance = Provenance {
+            hash: ByteBuf::from(vec![1; 32]),
+            committed_at: 0,
+            reveal: None,
+        };
+        Configuration::set_provenance(provenance.clone());
+        assert_eq!(Configuration::get_provenance(), Some(provenance));
+    }
+
     #[test]
     fn test_should_get_and_set_custodians() {
         let custodians = vec![
@@ -219,6 +672,43 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_should_get_and_set_voucher_signers() {
+        assert!(Configuration::get_voucher_signers().is_empty());
+        let signers = vec![Principal::from_slice(&[1; 29])];
+        Configuration::set_voucher_signers(&signers);
+        assert_eq!(Configuration::get_voucher_signers(), signers);
+        assert!(Configuration::is_voucher_signer(signers[0]));
+        assert!(!Configuration::is_voucher_signer(Principal::anonymous()));
+    }
+
+    #[test]
+    fn test_should_get_and_set_http_minters() {
+        assert!(Configuration::get_http_minters().is_empty());
+        let minters = vec![Principal::from_slice(&[1; 29])];
+        Configuration::set_http_minters(&minters);
+        assert_eq!(Configuration::get_http_minters(), minters);
+        assert!(Configuration::is_http_minter(Principal::from_slice(
+            &[1; 29]
+        )));
+        assert!(!Configuration::is_http_minter(Principal::anonymous()));
+    }
+
+    #[test]
+    fn test_should_get_and_set_allowed_origins() {
+        assert!(!Configuration::is_allowed_origin("https://example.com"));
+        Configuration::set_allowed_origins(vec!["https://example.com".to_string()]);
+        assert_eq!(
+            Configuration::get_allowed_origins(),
+            vec!["https://example.com".to_string()]
+        );
+        assert!(Configuration::is_allowed_origin("https://example.com"));
+        assert!(!Configuration::is_allowed_origin("https://example.org"));
+
+        Configuration::set_allowed_origins(vec!["*".to_string()]);
+        assert!(Configuration::is_allowed_origin("https://example.org"));
+    }
+
     #[test]
     fn test_should_get_and_set_supported_interfaces() {
         let supported_interfaces = vec![
@@ -0,0 +1,93 @@
+//! # EXT
+//!
+//! Token and account identifiers of the EXT standard, used by legacy wallets
+
+use candid::Principal;
+use sha2::{Digest as _, Sha224};
+
+use crate::did::{ExtAccountIdentifier, ExtTokenIdentifier, ExtTokenIndex, Subaccount};
+
+/// Domain separator of the token identifiers
+const TOKEN_ID_DOMAIN: &[u8] = b"\x0atid";
+/// Domain separator of the account identifiers
+const ACCOUNT_ID_DOMAIN: &[u8] = b"\x0aaccount-id";
+
+pub struct Ext;
+
+impl Ext {
+    /// Returns the identifier of the token of the canister at `index`: the textual principal of
+    /// the domain separator, the canister id and the big-endian index
+    pub fn token_identifier(canister_id: Principal, index: ExtTokenIndex) -> ExtTokenIdentifier {
+        let mut bytes = TOKEN_ID_DOMAIN.to_vec();
+        bytes.extend_from_slice(canister_id.as_slice());
+        bytes.extend_from_slice(&index.to_be_bytes());
+
+        Principal::from_slice(&bytes).to_text()
+    }
+
+    /// Returns the index of the token, if the identifier is one of a token of the canister
+    pub fn token_index(canister_id: Principal, token_identifier: &str) -> Option<ExtTokenIndex> {
+        let principal = Principal::from_text(token_identifier).ok()?;
+        let index = principal
+            .as_slice()
+            .strip_prefix(TOKEN_ID_DOMAIN)?
+            .strip_prefix(canister_id.as_slice())?;
+
+        Some(ExtTokenIndex::from_be_bytes(index.try_into().ok()?))
+    }
+
+    /// Returns the identifier of the account, as the ICP ledger computes it: the big-endian CRC32 of
+    /// the SHA-224 of the domain separator, the owner and the subaccount, followed by this hash, hex encoded
+    pub fn account_identifier(
+        owner: Principal,
+        subaccount: Option<Subaccount>,
+    ) -> ExtAccountIdentifier {
+        let mut hasher = Sha224::new();
+        hasher.update(ACCOUNT_ID_DOMAIN);
+        hasher.update(owner.as_slice());
+        hasher.update(subaccount.unwrap_or_default());
+        let hash = hasher.finalize();
+
+        let mut identifier = crc32fast::hash(&hash).to_be_bytes().to_vec();
+        identifier.extend_from_slice(&hash);
+        hex::encode(identifier)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_token_identifier() {
+        let canister_id = Principal::from_text("bkyz2-fmaaa-aaaaa-qaaaq-cai").unwrap();
+        let token_identifier = Ext::token_identifier(canister_id, 42);
+
+        assert_eq!(Ext::token_index(canister_id, &token_identifier), Some(42));
+        assert_eq!(
+            Ext::token_index(Principal::management_canister(), &token_identifier),
+            None
+        );
+        assert_eq!(Ext::token_index(canister_id, &canister_id.to_text()), None);
+        assert_eq!(Ext::token_index(canister_id, "not a token"), None);
+    }
+
+    #[test]
+    fn test_should_compute_account_identifier() {
+        assert_eq!(
+            Ext::account_identifier(Principal::anonymous(), None),
+            "1c7a48ba6a562aa9eaa2481a9049cdf0433b9738c992d698c31d8abf89cadc79"
+        );
+        assert_eq!(
+            Ext::account_identifier(Principal::anonymous(), Some([0; 32])),
+            Ext::account_identifier(Principal::anonymous(), None)
+        );
+        assert_ne!(
+            Ext::account_identifier(Principal::anonymous(), Some([1; 32])),
+            Ext::account_identifier(Principal::anonymous(), None)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! # Guard
+//!
+//! Locks held across the await points of an update call.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use dip721_rs::{NftError, TokenIdentifier};
+
+thread_local! {
+    /// Callers with a call in progress
+    static LOCKED: RefCell<BTreeSet<Principal>> = RefCell::default();
+    /// Token identifiers of the default collection reserved by the mints in progress
+    static RESERVED: RefCell<BTreeSet<TokenIdentifier>> = RefCell::default();
+}
+
+/// Prevents a caller from running concurrent calls; the lock is released when the guard is dropped
+pub struct CallerGuard {
+    caller: Principal,
+}
+
+impl CallerGuard {
+    /// Lock the caller, failing if it already has a call in progress
+    pub fn new(caller: Principal) -> Result<Self, NftError> {
+        if LOCKED.with_borrow_mut(|locked| locked.insert(caller)) {
+            Ok(Self { caller })
+        } else {
+            Err(NftError::Other(
+                "a call by the caller is already in progress".to_string(),
+            ))
+        }
+    }
+}
+
+impl Drop for CallerGuard {
+    fn drop(&mut self) {
+        LOCKED.with_borrow_mut(|locked| locked.remove(&self.caller));
+    }
+}
+
+/// Reserves token identifiers while a mint awaits its payment; the reservation is released when the guard is dropped
+pub struct MintGuard {
+    token_identifiers: Vec<TokenIdentifier>,
+}
+
+impl MintGuard {
+    /// Reserve the token identifiers, failing if any of them is already reserved
+    pub fn new(token_identifiers: Vec<TokenIdentifier>) -> Result<Self, NftError> {
+        RESERVED.with_borrow_mut(|reserved| {
+            if token_identifiers.iter().any(|id| reserved.contains(id)) {
+                return Err(NftError::ExistedNFT);
+            }
+            reserved.extend(token_identifiers.iter().cloned());
+
+            Ok(Self { token_identifiers })
+        })
+    }
+
+    /// Returns whether the token identifier is reserved by a mint in progress
+    pub fn is_reserved(token_identifier: &TokenIdentifier) -> bool {
+        RESERVED.with_borrow(|reserved| reserved.contains(token_identifier))
+    }
+
+    /// Returns the amount of token identifiers reserved by the mints in progress
+    pub fn reserved() -> u64 {
+        RESERVED.with_borrow(|reserved| reserved.len() as u64)
+    }
+
+    /// Release the reservation, returning the reserved token identifiers
+    pub fn release(self) -> Vec<TokenIdentifier> {
+        self.token_identifiers.clone()
+    }
+}
+
+impl Drop for MintGuard {
+    fn drop(&mut self) {
+        RESERVED.with_borrow_mut(|reserved| {
+            for token_identifier in &self.token_identifiers {
+                reserved.remove(token_identifier);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_should_lock_caller() {
+        let guard = CallerGuard::new(Principal::anonymous()).unwrap();
+        assert!(CallerGuard::new(Principal::anonymous()).is_err());
+        assert!(CallerGuard::new(Principal::management_canister()).is_ok());
+        drop(guard);
+        assert!(CallerGuard::new(Principal::anonymous()).is_ok());
+    }
+
+    #[test]
+    fn test_should_reserve_token_identifiers() {
+        let guard = MintGuard::new(vec![1_u64.into(), 2_u64.into()]).unwrap();
+        assert!(MintGuard::is_reserved(&2_u64.into()));
+        assert_eq!(MintGuard::reserved(), 2);
+        assert!(MintGuard::new(vec![2_u64.into(), 3_u64.into()]).is_err());
+        assert!(!MintGuard::is_reserved(&3_u64.into()));
+
+        assert_eq!(
+            guard.release(),
+            vec![TokenIdentifier::from(1_u64), TokenIdentifier::from(2_u64)]
+        );
+        assert_eq!(MintGuard::reserved(), 0);
+        assert!(MintGuard::new(vec![2_u64.into()]).is_ok());
+    }
+}
@@ -6,6 +6,7 @@ use candid::{Nat, Principal};
 use dip721_rs::{NftError, TokenMetadata};
 
 use super::{configuration::Configuration, storage::TokensStorage};
+use crate::did::CollectionId;
 
 pub struct Inspect;
 
@@ -15,25 +16,37 @@ impl Inspect {
         Configuration::is_custodian(caller)
     }
 
+    /// Returns whether caller is custodian of the collection
+    pub fn inspect_is_collection_custodian(caller: Principal, collection_id: CollectionId) -> bool {
+        Configuration::is_collection_custodian(collection_id, caller)
+    }
+
     /// Returns whether caller is owner of the token
-    pub fn inspect_is_owner(caller: Principal, token_identifier: &Nat) -> bool {
-        let token = TokensStorage::get_token(token_identifier).unwrap();
-        token.owner == Some(caller)
+    pub fn inspect_is_owner(
+        caller: Principal,
+        collection_id: CollectionId,
+        token_identifier: &Nat,
+    ) -> bool {
+        TokensStorage::is_held_by(collection_id, token_identifier, caller, None)
     }
 
     /// Returns whether caller is owner or operator of the token
     pub fn inspect_is_owner_or_operator(
         caller: Principal,
+        collection_id: CollectionId,
         token_identifier: &Nat,
     ) -> Result<TokenMetadata, NftError> {
-        let token = TokensStorage::get_token(token_identifier)?;
+        let token = TokensStorage::get_token(collection_id, token_identifier)?;
 
         let owner = match token.owner {
             Some(owner) => owner,
             None => return Err(NftError::UnauthorizedOwner),
         };
 
-        if caller != owner && Some(caller) != token.operator {
+        // owners act on behalf of their default subaccount only
+        let is_owner = caller == owner
+            && TokensStorage::is_held_by(collection_id, token_identifier, owner, None);
+        if !is_owner && Some(caller) != token.operator {
             return Err(NftError::UnauthorizedOperator);
         }
 
@@ -43,9 +56,10 @@ impl Inspect {
     /// Inspect whether the caller is owner or operator of the token and the token is not burned.
     pub fn inspect_transfer_from(
         caller: Principal,
+        collection_id: CollectionId,
         token_identifier: &Nat,
     ) -> Result<TokenMetadata, NftError> {
-        let token = Self::inspect_is_owner_or_operator(caller, token_identifier)?;
+        let token = Self::inspect_is_owner_or_operator(caller, collection_id, token_identifier)?;
         if token.is_burned {
             return Err(NftError::ExistedNFT);
         }
@@ -61,6 +75,7 @@ mod test {
 
     use super::*;
     use crate::app::test_utils::{self};
+    use crate::did::DEFAULT_COLLECTION_ID;
     use crate::utils::caller;
 
     #[test]
@@ -83,21 +98,46 @@ mod test {
             token.owner = Some(caller);
             token.operator = None;
         });
-        assert!(Inspect::inspect_is_owner_or_operator(caller, &1_u64.into()).is_ok());
+        assert!(Inspect::inspect_is_owner_or_operator(
+            caller,
+            DEFAULT_COLLECTION_ID,
+            &1_u64.into()
+        )
+        .is_ok());
 
         // with operator
         test_utils::store_mock_token_with(2, |token| {
             token.operator = Some(caller);
         });
-        assert!(TokensStorage::transfer(&2_u64.into(), Principal::management_canister()).is_ok());
-        assert!(Inspect::inspect_is_owner_or_operator(caller, &2_u64.into()).is_err());
+        assert!(TokensStorage::transfer(
+            DEFAULT_COLLECTION_ID,
+            &2_u64.into(),
+            Principal::management_canister()
+        )
+        .is_ok());
+        assert!(Inspect::inspect_is_owner_or_operator(
+            caller,
+            DEFAULT_COLLECTION_ID,
+            &2_u64.into()
+        )
+        .is_err());
 
         // no operator, no owner
         test_utils::store_mock_token_with(3, |token| {
             token.operator = Some(Principal::management_canister());
         });
-        assert!(TokensStorage::transfer(&3_u64.into(), Principal::management_canister()).is_ok());
-        assert!(Inspect::inspect_is_owner_or_operator(caller, &3_u64.into()).is_err());
+        assert!(TokensStorage::transfer(
+            DEFAULT_COLLECTION_ID,
+            &3_u64.into(),
+            Principal::management_canister()
+        )
+        .is_ok());
+        assert!(Inspect::inspect_is_owner_or_operator(
+            caller,
+            DEFAULT_COLLECTION_ID,
+            &3_u64.into()
+        )
+        .is_err());
     }
 
     #[test]
@@ -107,27 +147,45 @@ mod test {
             token.owner = Some(caller);
             token.operator = None;
         });
-        assert!(Inspect::inspect_transfer_from(caller, &1_u64.into()).is_ok());
+        assert!(
+            Inspect::inspect_transfer_from(caller, DEFAULT_COLLECTION_ID, &1_u64.into()).is_ok()
+        );
 
         // with operator
         test_utils::store_mock_token_with(2, |token| {
             token.operator = Some(caller);
         });
-        assert!(TokensStorage::transfer(&2_u64.into(), Principal::management_canister()).is_ok());
-        assert!(Inspect::inspect_transfer_from(caller, &2_u64.into()).is_err());
+        assert!(TokensStorage::transfer(
+            DEFAULT_COLLECTION_ID,
+            &2_u64.into(),
+            Principal::management_canister()
+        )
+        .is_ok());
+        assert!(
+            Inspect::inspect_transfer_from(caller, DEFAULT_COLLECTION_ID, &2_u64.into()).is_err()
+        );
 
         // no operator, no owner
         test_utils::store_mock_token_with(3, |token| {
             token.operator = Some(Principal::management_canister());
         });
-        assert!(TokensStorage::transfer(&3_u64.into(), Principal::management_canister()).is_ok());
-        assert!(Inspect::inspect_transfer_from(caller, &3_u64.into()).is_err());
+        assert!(TokensStorage::transfer(
+            DEFAULT_COLLECTION_ID,
+            &3_u64.into(),
+            Principal::management_canister()
+        )
+        .is_ok());
+        assert!(
+            Inspect::inspect_transfer_from(caller, DEFAULT_COLLECTION_ID, &3_u64.into()).is_err()
+        );
 
         test_utils::store_mock_token_with(4, |token| {
             token.owner = Some(caller);
             token.operator = None;
         });
-        assert!(TokensStorage::burn(&4_u64.into()).is_ok());
-        assert!(Inspect::inspect_transfer_from(caller, &4_u64.into()).is_err());
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &4_u64.into()).is_ok());
+        assert!(
+            Inspect::inspect_transfer_from(caller, DEFAULT_COLLECTION_ID, &4_u64.into()).is_err()
+        );
     }
 }
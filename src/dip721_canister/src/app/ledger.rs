@@ -0,0 +1,137 @@
+//! # Ledger
+//!
+//! Client of the ICRC-2 ledgers the marketplace is settled on.
+
+use candid::{CandidType, Nat, Principal};
+use dip721_rs::NftError;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+use crate::did::Account;
+
+/// Arguments of `icrc1_transfer`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+struct TransferArg {
+    from_subaccount: Option<ByteBuf>,
+    to: Account,
+    fee: Option<Nat>,
+    created_at_time: Option<u64>,
+    memo: Option<ByteBuf>,
+    amount: Nat,
+}
+
+/// Arguments of `icrc2_transfer_from`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+struct TransferFromArgs {
+    spender_subaccount: Option<ByteBuf>,
+    from: Account,
+    to: Account,
+    amount: Nat,
+    fee: Option<Nat>,
+    memo: Option<ByteBuf>,
+    created_at_time: Option<u64>,
+}
+
+/// Errors of both `icrc1_transfer` and `icrc2_transfer_from`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+enum TransferError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+pub struct Ledger;
+
+impl Ledger {
+    /// Returns the fee charged by the ledger on transfers
+    pub async fn fee(ledger: Principal) -> Result<Nat, NftError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = ledger;
+            Ok(Nat::from(10_000_u64))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let (fee,): (Nat,) = ic_cdk::call(ledger, "icrc1_fee", ())
+                .await
+                .map_err(|(code, message)| Self::call_error(code, message))?;
+            Ok(fee)
+        }
+    }
+
+    /// Transfer `amount` from the canister account to `to`; the ledger fee is paid on top of `amount`.
+    ///
+    /// Returns the ledger block index
+    pub async fn transfer(ledger: Principal, to: Principal, amount: Nat) -> Result<Nat, NftError> {
+        let args = TransferArg {
+            from_subaccount: None,
+            to: to.into(),
+            fee: None,
+            created_at_time: None,
+            memo: None,
+            amount,
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = (ledger, args);
+            Ok(Nat::from(0_u64))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let (result,): (Result<Nat, TransferError>,) =
+                ic_cdk::call(ledger, "icrc1_transfer", (args,))
+                    .await
+                    .map_err(|(code, message)| Self::call_error(code, message))?;
+            result.map_err(Self::transfer_error)
+        }
+    }
+
+    /// Transfer `amount` from `from` to `to`, spending the allowance `from` gave to the canister.
+    ///
+    /// Returns the ledger block index
+    pub async fn transfer_from(
+        ledger: Principal,
+        from: Principal,
+        to: Principal,
+        amount: Nat,
+    ) -> Result<Nat, NftError> {
+        let args = TransferFromArgs {
+            spender_subaccount: None,
+            from: from.into(),
+            to: to.into(),
+            amount,
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = (ledger, args);
+            Ok(Nat::from(0_u64))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let (result,): (Result<Nat, TransferError>,) =
+                ic_cdk::call(ledger, "icrc2_transfer_from", (args,))
+                    .await
+                    .map_err(|(code, message)| Self::call_error(code, message))?;
+            result.map_err(Self::transfer_error)
+        }
+    }
+
+    #[allow(dead_code)]
+    fn call_error(code: ic_cdk::api::call::RejectionCode, message: String) -> NftError {
+        NftError::Other(format!("ledger call rejected: {code:?}: {message}"))
+    }
+
+    #[allow(dead_code)]
+    fn transfer_error(error: TransferError) -> NftError {
+        NftError::Other(format!("ledger transfer failed: {error:?}"))
+    }
+}
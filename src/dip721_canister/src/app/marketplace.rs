@@ -0,0 +1,173 @@
+//! # Marketplace
+//!
+//! Settlement of the sales of the canister marketplace.
+
+use candid::{Nat, Principal};
+
+use super::ledger::Ledger;
+use super::storage::ProceedsStorage;
+use crate::did::{PlatformFee, Royalty, BASIS_POINTS};
+
+pub struct Marketplace;
+
+impl Marketplace {
+    /// Split the sale price between the platform, the royalty recipients and the seller, in this order.
+    ///
+    /// Each cut is taken from what is left of the price, so the amounts always sum to the price.
+    pub fn split(
+        price: &Nat,
+        seller: Principal,
+        royalty: Option<&Royalty>,
+        platform_fee: Option<&PlatformFee>,
+    ) -> Vec<(Principal, Nat)> {
+        let mut cuts = Vec::new();
+        if let Some(fee) = platform_fee {
+            cuts.push((
+                fee.recipient,
+                price.clone() * fee.basis_points / BASIS_POINTS,
+            ));
+        }
+        if let Some(royalty) = royalty {
+            cuts.extend(
+                royalty
+                    .payments(price)
+                    .into_iter()
+                    .map(|payment| (payment.recipient, payment.amount)),
+            );
+        }
+
+        let mut left = price.clone();
+        let mut split = Vec::with_capacity(cuts.len() + 1);
+        for (recipient, amount) in cuts {
+            let amount = amount.min(left.clone());
+            left -= amount.clone();
+            split.push((recipient, amount));
+        }
+        split.push((seller, left));
+
+        split
+    }
+
+    /// Pay out the split of a sale from the canister account, at the ledger's current fee.
+    ///
+    /// Everything is credited to the recipients if the fee can't be fetched.
+    pub async fn pay(ledger: Principal, split: Vec<(Principal, Nat)>) {
+        match Ledger::fee(ledger).await {
+            Ok(fee) => Self::payout(ledger, &fee, split).await,
+            Err(_) => {
+                for (recipient, amount) in split {
+                    ProceedsStorage::credit(ledger, recipient, amount);
+                }
+            }
+        }
+    }
+
+    /// Refund `amount` paid by `recipient` to the canister account.
+    ///
+    /// The canister only holds what was paid, so the ledger fee of the refund is taken from the amount.
+    /// Refunds which fail, which don't cover the fee, or whose fee can't be fetched are credited to the
+    /// recipient instead, who can withdraw them later.
+    pub async fn refund(ledger: Principal, recipient: Principal, amount: Nat) {
+        let refunded = match Ledger::fee(ledger).await {
+            Ok(fee) if amount > fee => Ledger::transfer(ledger, recipient, amount.clone() - fee)
+                .await
+                .is_ok(),
+            _ => false,
+        };
+        if !refunded {
+            ProceedsStorage::credit(ledger, recipient, amount);
+        }
+    }
+
+    /// Pay out the split of a sale from the canister account.
+    ///
+    /// The ledger fee of each transfer is taken from the paid amount, and amounts not covering it are kept by
+    /// the canister. Payouts failing are credited to the recipient, who can withdraw them later.
+    pub async fn payout(ledger: Principal, ledger_fee: &Nat, split: Vec<(Principal, Nat)>) {
+        for (recipient, amount) in split {
+            if &amount <= ledger_fee {
+                continue;
+            }
+            if Ledger::transfer(ledger, recipient, amount.clone() - ledger_fee.clone())
+                .await
+                .is_err()
+            {
+                ProceedsStorage::credit(ledger, recipient, amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob};
+    use crate::did::RoyaltyRecipient;
+
+    #[test]
+    fn test_should_split_sale() {
+        let royalty = Royalty {
+            basis_points: 1_000,
+            recipients: vec![RoyaltyRecipient {
+                recipient: bob(),
+                share: BASIS_POINTS,
+            }],
+        };
+        let fee = PlatformFee {
+            basis_points: 250,
+            recipient: Principal::anonymous(),
+        };
+
+        assert_eq!(
+            Marketplace::split(&1_000_u64.into(), alice(), Some(&royalty), Some(&fee)),
+            vec![
+                (Principal::anonymous(), 25_u64.into()),
+                (bob(), 100_u64.into()),
+                (alice(), 875_u64.into()),
+            ]
+        );
+        assert_eq!(
+            Marketplace::split(&1_000_u64.into(), alice(), None, None),
+            vec![(alice(), 1_000_u64.into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_credit_refunds_not_covering_the_fee() {
+        let ledger = Principal::management_canister();
+        let fee = Ledger::fee(ledger).await.unwrap();
+
+        Marketplace::refund(ledger, alice(), fee.clone()).await;
+        assert_eq!(ProceedsStorage::get(ledger, alice()), fee);
+
+        Marketplace::refund(ledger, bob(), fee + 1_u64).await;
+        assert_eq!(ProceedsStorage::get(ledger, bob()), 0_u64);
+    }
+
+    #[test]
+    fn test_should_never_split_more_than_price() {
+        let royalty = Royalty {
+            basis_points: BASIS_POINTS,
+            recipients: vec![RoyaltyRecipient {
+                recipient: bob(),
+                share: BASIS_POINTS,
+            }],
+        };
+        let fee = PlatformFee {
+            basis_points: 500,
+            recipient: Principal::anonymous(),
+        };
+
+        assert_eq!(
+            Marketplace::split(&1_000_u64.into(), alice(), Some(&royalty), Some(&fee)),
+            vec![
+                (Principal::anonymous(), 50_u64.into()),
+                (bob(), 950_u64.into()),
+                (alice(), 0_u64.into()),
+            ]
+        );
+    }
+}
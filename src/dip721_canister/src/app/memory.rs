@@ -1,8 +1,16 @@
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager as IcMemoryManager};
 use ic_stable_structures::DefaultMemoryImpl;
 
-pub const TOKENS_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const LEGACY_TOKENS_MEMORY_ID: MemoryId = MemoryId::new(10);
 pub const TRANSACTIONS_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const TOKEN_USERS_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const EDITION_TEMPLATES_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const TOKEN_EDITIONS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const TOKENS_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const COLLECTIONS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const TOKEN_PARENTS_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const TOKEN_CHILDREN_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const OWNER_INDEX_CURSOR_MEMORY_ID: MemoryId = MemoryId::new(19);
 
 pub const LOGO_MEMORY_ID: MemoryId = MemoryId::new(20);
 pub const NAME_MEMORY_ID: MemoryId = MemoryId::new(21);
@@ -11,6 +19,104 @@ pub const CREATED_AT_MEMORY_ID: MemoryId = MemoryId::new(23);
 pub const UPGRADED_AT_MEMORY_ID: MemoryId = MemoryId::new(24);
 pub const CUSTODIANS_MEMORY_ID: MemoryId = MemoryId::new(25);
 pub const SUPPORTED_INTERFACES_MEMORY_ID: MemoryId = MemoryId::new(26);
+pub const BINARY_LOGO_MEMORY_ID: MemoryId = MemoryId::new(27);
+pub const TOKEN_JSON_MAPPING_MEMORY_ID: MemoryId = MemoryId::new(28);
+pub const HTTP_MINTERS_MEMORY_ID: MemoryId = MemoryId::new(29);
+
+pub const ASSETS_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub const ASSET_CHUNKS_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub const NEXT_ASSET_ID_MEMORY_ID: MemoryId = MemoryId::new(32);
+
+pub const SIGNED_NONCES_MEMORY_ID: MemoryId = MemoryId::new(40);
+pub const ALLOWED_ORIGINS_MEMORY_ID: MemoryId = MemoryId::new(41);
+pub const VOUCHER_SIGNERS_MEMORY_ID: MemoryId = MemoryId::new(42);
+pub const HTTP_BASE_URL_MEMORY_ID: MemoryId = MemoryId::new(43);
+
+pub const COLLECTION_ROYALTY_MEMORY_ID: MemoryId = MemoryId::new(50);
+pub const TOKEN_ROYALTIES_MEMORY_ID: MemoryId = MemoryId::new(51);
+
+pub const LISTINGS_MEMORY_ID: MemoryId = MemoryId::new(60);
+pub const MARKETPLACE_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(61);
+pub const PROCEEDS_MEMORY_ID: MemoryId = MemoryId::new(62);
+pub const AUCTIONS_MEMORY_ID: MemoryId = MemoryId::new(63);
+pub const OFFERS_MEMORY_ID: MemoryId = MemoryId::new(64);
+pub const SWAPS_MEMORY_ID: MemoryId = MemoryId::new(65);
+pub const SWAP_LOCKS_MEMORY_ID: MemoryId = MemoryId::new(66);
+pub const NEXT_OFFER_ID_MEMORY_ID: MemoryId = MemoryId::new(67);
+pub const NEXT_SWAP_ID_MEMORY_ID: MemoryId = MemoryId::new(68);
+
+pub const MINT_SALE_MEMORY_ID: MemoryId = MemoryId::new(70);
+pub const SALE_MINTED_MEMORY_ID: MemoryId = MemoryId::new(71);
+pub const SALE_WALLET_MINTS_MEMORY_ID: MemoryId = MemoryId::new(72);
+pub const ALLOWLIST_PHASES_MEMORY_ID: MemoryId = MemoryId::new(73);
+pub const ALLOWLIST_MINTS_MEMORY_ID: MemoryId = MemoryId::new(74);
+pub const PROVENANCE_MEMORY_ID: MemoryId = MemoryId::new(75);
+pub const RANDOM_POOL_MEMORY_ID: MemoryId = MemoryId::new(76);
+pub const RANDOM_POOL_SWAPS_MEMORY_ID: MemoryId = MemoryId::new(77);
+pub const RANDOM_POOL_POSITIONS_MEMORY_ID: MemoryId = MemoryId::new(78);
+pub const REVEAL_METADATA_MEMORY_ID: MemoryId = MemoryId::new(79);
+
+pub const ACCOUNT_TOKENS_MEMORY_ID: MemoryId = MemoryId::new(80);
+pub const TOKEN_SUBACCOUNTS_MEMORY_ID: MemoryId = MemoryId::new(81);
+
+pub const BURNED_TOKENS_MEMORY_ID: MemoryId = MemoryId::new(90);
+pub const TOKEN_SUPPLY_MEMORY_ID: MemoryId = MemoryId::new(91);
+
+/// Memories used by the canister, by name
+pub const MEMORIES: [(&str, MemoryId); 52] = [
+    ("legacy_tokens", LEGACY_TOKENS_MEMORY_ID),
+    ("transactions", TRANSACTIONS_MEMORY_ID),
+    ("token_users", TOKEN_USERS_MEMORY_ID),
+    ("edition_templates", EDITION_TEMPLATES_MEMORY_ID),
+    ("token_editions", TOKEN_EDITIONS_MEMORY_ID),
+    ("tokens", TOKENS_MEMORY_ID),
+    ("collections", COLLECTIONS_MEMORY_ID),
+    ("token_parents", TOKEN_PARENTS_MEMORY_ID),
+    ("token_children", TOKEN_CHILDREN_MEMORY_ID),
+    ("owner_index_cursor", OWNER_INDEX_CURSOR_MEMORY_ID),
+    ("logo", LOGO_MEMORY_ID),
+    ("name", NAME_MEMORY_ID),
+    ("symbol", SYMBOL_MEMORY_ID),
+    ("created_at", CREATED_AT_MEMORY_ID),
+    ("upgraded_at", UPGRADED_AT_MEMORY_ID),
+    ("custodians", CUSTODIANS_MEMORY_ID),
+    ("supported_interfaces", SUPPORTED_INTERFACES_MEMORY_ID),
+    ("binary_logo", BINARY_LOGO_MEMORY_ID),
+    ("token_json_mapping", TOKEN_JSON_MAPPING_MEMORY_ID),
+    ("http_minters", HTTP_MINTERS_MEMORY_ID),
+    ("assets", ASSETS_MEMORY_ID),
+    ("asset_chunks", ASSET_CHUNKS_MEMORY_ID),
+    ("next_asset_id", NEXT_ASSET_ID_MEMORY_ID),
+    ("signed_nonces", SIGNED_NONCES_MEMORY_ID),
+    ("allowed_origins", ALLOWED_ORIGINS_MEMORY_ID),
+    ("voucher_signers", VOUCHER_SIGNERS_MEMORY_ID),
+    ("http_base_url", HTTP_BASE_URL_MEMORY_ID),
+    ("collection_royalty", COLLECTION_ROYALTY_MEMORY_ID),
+    ("token_royalties", TOKEN_ROYALTIES_MEMORY_ID),
+    ("listings", LISTINGS_MEMORY_ID),
+    ("marketplace_settings", MARKETPLACE_SETTINGS_MEMORY_ID),
+    ("proceeds", PROCEEDS_MEMORY_ID),
+    ("auctions", AUCTIONS_MEMORY_ID),
+    ("offers", OFFERS_MEMORY_ID),
+    ("swaps", SWAPS_MEMORY_ID),
+    ("swap_locks", SWAP_LOCKS_MEMORY_ID),
+    ("next_offer_id", NEXT_OFFER_ID_MEMORY_ID),
+    ("next_swap_id", NEXT_SWAP_ID_MEMORY_ID),
+    ("mint_sale", MINT_SALE_MEMORY_ID),
+    ("sale_minted", SALE_MINTED_MEMORY_ID),
+    ("sale_wallet_mints", SALE_WALLET_MINTS_MEMORY_ID),
+    ("allowlist_phases", ALLOWLIST_PHASES_MEMORY_ID),
+    ("allowlist_mints", ALLOWLIST_MINTS_MEMORY_ID),
+    ("provenance", PROVENANCE_MEMORY_ID),
+    ("random_pool", RANDOM_POOL_MEMORY_ID),
+    ("random_pool_swaps", RANDOM_POOL_SWAPS_MEMORY_ID),
+    ("random_pool_positions", RANDOM_POOL_POSITIONS_MEMORY_ID),
+    ("reveal_metadata", REVEAL_METADATA_MEMORY_ID),
+    ("account_tokens", ACCOUNT_TOKENS_MEMORY_ID),
+    ("token_subaccounts", TOKEN_SUBACCOUNTS_MEMORY_ID),
+    ("burned_tokens", BURNED_TOKENS_MEMORY_ID),
+    ("token_supply", TOKEN_SUPPLY_MEMORY_ID),
+];
 
 thread_local! {
     /// Memory manager
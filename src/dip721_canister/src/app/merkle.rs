@@ -0,0 +1,73 @@
+//! # Merkle
+//!
+//! Verification of allowlist entries against a committed Merkle root
+
+use candid::Principal;
+use sha2::{Digest as _, Sha256};
+
+pub struct Merkle;
+
+impl Merkle {
+    /// Returns the leaf of an allowlist entry: the SHA-256 of the principal bytes followed by
+    /// `max_quantity` as big-endian u64.
+    ///
+    /// Leaves are hashed from at most 37 bytes, so they can't collide with the 64 bytes of a node.
+    pub fn leaf(principal: Principal, max_quantity: u64) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(principal.as_slice());
+        hasher.update(max_quantity.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Returns the parent of two nodes: the SHA-256 of the nodes concatenated in ascending order
+    pub fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        let mut hasher = Sha256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+
+    /// Verify that `leaf` belongs to the tree of `root`, `proof` being the siblings from the leaf up to the root
+    pub fn verify(root: &[u8], leaf: [u8; 32], proof: &[impl AsRef<[u8]>]) -> bool {
+        let mut hash = leaf;
+        for sibling in proof {
+            let Ok(sibling) = <[u8; 32]>::try_from(sibling.as_ref()) else {
+                return false;
+            };
+            hash = Self::node(&hash, &sibling);
+        }
+
+        hash.as_slice() == root
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_should_verify_proof() {
+        let alice = Merkle::leaf(Principal::anonymous(), 2);
+        let bob = Merkle::leaf(Principal::management_canister(), 1);
+        let charlie = Merkle::leaf(Principal::from_slice(&[1; 29]), 5);
+        // charlie is promoted to the upper level
+        let root = Merkle::node(&Merkle::node(&alice, &bob), &charlie);
+
+        assert!(Merkle::verify(&root, alice, &[bob, charlie]));
+        assert!(Merkle::verify(&root, bob, &[alice, charlie]));
+        assert!(Merkle::verify(
+            &root,
+            charlie,
+            &[Merkle::node(&alice, &bob)]
+        ));
+        assert!(!Merkle::verify(
+            &root,
+            Merkle::leaf(Principal::anonymous(), 3),
+            &[bob, charlie]
+        ));
+        assert!(!Merkle::verify(&root, alice, &[bob]));
+        assert!(!Merkle::verify(&root, alice, &[vec![0_u8; 31]]));
+    }
+}
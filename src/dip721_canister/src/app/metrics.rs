@@ -0,0 +1,102 @@
+//! # Metrics
+//!
+//! Counters of the calls served by the canister.
+//!
+//! Counters live in the heap, so they are reset on upgrade; changes made by query calls are discarded,
+//! so only update calls are counted.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use dip721_rs::NftError;
+
+thread_local! {
+    /// Calls by method
+    static CALLS: RefCell<BTreeMap<&'static str, u64>> = RefCell::default();
+
+    /// Errors by method and [`NftError`] variant
+    static ERRORS: RefCell<BTreeMap<(&'static str, &'static str), u64>> = RefCell::default();
+}
+
+pub struct Metrics;
+
+impl Metrics {
+    /// Count a call to `method`
+    pub fn record_call(method: &'static str) {
+        CALLS.with_borrow_mut(|calls| *calls.entry(method).or_default() += 1);
+    }
+
+    /// Count an error returned by `method`
+    pub fn record_error(method: &'static str, error: &NftError) {
+        ERRORS.with_borrow_mut(|errors| {
+            *errors
+                .entry((method, Self::error_variant(error)))
+                .or_default() += 1
+        });
+    }
+
+    /// Returns the calls count by method
+    pub fn calls() -> Vec<(&'static str, u64)> {
+        CALLS.with_borrow(|calls| {
+            calls
+                .iter()
+                .map(|(method, count)| (*method, *count))
+                .collect()
+        })
+    }
+
+    /// Returns the errors count by method and error variant
+    pub fn errors() -> Vec<(&'static str, &'static str, u64)> {
+        ERRORS.with_borrow(|errors| {
+            errors
+                .iter()
+                .map(|((method, error), count)| (*method, *error, *count))
+                .collect()
+        })
+    }
+
+    fn error_variant(error: &NftError) -> &'static str {
+        match error {
+            NftError::SelfTransfer => "SelfTransfer",
+            NftError::TokenNotFound => "TokenNotFound",
+            NftError::TxNotFound => "TxNotFound",
+            NftError::SelfApprove => "SelfApprove",
+            NftError::OperatorNotFound => "OperatorNotFound",
+            NftError::UnauthorizedOwner => "UnauthorizedOwner",
+            NftError::UnauthorizedOperator => "UnauthorizedOperator",
+            NftError::ExistedNFT => "ExistedNFT",
+            NftError::OwnerNotFound => "OwnerNotFound",
+            NftError::Other(_) => "Other",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_count_calls_and_errors() {
+        Metrics::record_call("dip721_mint");
+        Metrics::record_call("dip721_mint");
+        Metrics::record_call("dip721_burn");
+        Metrics::record_error("dip721_mint", &NftError::ExistedNFT);
+        Metrics::record_error("dip721_mint", &NftError::Other("foo".to_string()));
+        Metrics::record_error("dip721_mint", &NftError::Other("bar".to_string()));
+
+        assert_eq!(
+            Metrics::calls(),
+            vec![("dip721_burn", 1), ("dip721_mint", 2)]
+        );
+        assert_eq!(
+            Metrics::errors(),
+            vec![
+                ("dip721_mint", "ExistedNFT", 1),
+                ("dip721_mint", "Other", 2)
+            ]
+        );
+    }
+}
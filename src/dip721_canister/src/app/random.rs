@@ -0,0 +1,158 @@
+//! # Random
+//!
+//! CSPRNG seeded with the randomness of the management canister `raw_rand`
+
+use std::cell::RefCell;
+
+use dip721_rs::NftError;
+use rand_chacha::rand_core::{RngCore as _, SeedableRng as _};
+use rand_chacha::ChaCha20Rng;
+
+/// Interval after which the CSPRNG is reseeded, in nanoseconds
+const RESEED_INTERVAL: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Delay before retrying a failed reseed, doubled after each failure, in nanoseconds
+const RESEED_RETRY_DELAY: u64 = 60 * 1_000_000_000;
+
+thread_local! {
+    /// CSPRNG and the time it was seeded at; lost on upgrade, then seeded again by the global timer
+    static RNG: RefCell<Option<(ChaCha20Rng, u64)>> = const { RefCell::new(None) };
+
+    /// Time the failed reseed is retried at and the delay it was retried after, if the last reseed failed
+    static RESEED_BACKOFF: RefCell<Option<(u64, u64)>> = const { RefCell::new(None) };
+}
+
+pub struct Random;
+
+impl Random {
+    /// Seed the CSPRNG with the randomness of `raw_rand`; on failure, the reseed is retried after a backoff
+    pub async fn reseed() -> Result<(), NftError> {
+        let seed = crate::utils::raw_rand()
+            .await
+            .map_err(NftError::Other)
+            .and_then(|seed| {
+                <[u8; 32]>::try_from(seed.as_slice())
+                    .map_err(|_| NftError::Other("raw_rand didn't return 32 bytes".to_string()))
+            });
+        match seed {
+            Ok(seed) => {
+                Self::seed(seed);
+                Ok(())
+            }
+            Err(err) => {
+                Self::back_off();
+                Err(err)
+            }
+        }
+    }
+
+    /// Seed the CSPRNG
+    pub fn seed(seed: [u8; 32]) {
+        RNG.with_borrow_mut(|rng| {
+            *rng = Some((ChaCha20Rng::from_seed(seed), crate::utils::time()));
+        });
+        RESEED_BACKOFF.with_borrow_mut(|backoff| *backoff = None);
+    }
+
+    /// Returns the time the CSPRNG must be reseeded at; if it's not seeded, that's now,
+    /// unless the last reseed failed and is retried later
+    pub fn next_reseed_at() -> u64 {
+        if let Some((retry_at, _)) = RESEED_BACKOFF.with_borrow(|backoff| *backoff) {
+            return retry_at;
+        }
+        RNG.with_borrow(|rng| match rng {
+            Some((_, seeded_at)) => seeded_at.saturating_add(RESEED_INTERVAL),
+            None => crate::utils::time(),
+        })
+    }
+
+    /// Retry the reseed after [`RESEED_RETRY_DELAY`], doubled after each failure up to [`RESEED_INTERVAL`]
+    fn back_off() {
+        RESEED_BACKOFF.with_borrow_mut(|backoff| {
+            let delay = match *backoff {
+                Some((_, delay)) => delay.saturating_mul(2).min(RESEED_INTERVAL),
+                None => RESEED_RETRY_DELAY,
+            };
+            *backoff = Some((crate::utils::time().saturating_add(delay), delay));
+        });
+    }
+
+    /// Fill `buf` with random bytes, failing if the CSPRNG is not seeded yet
+    pub fn fill(buf: &mut [u8]) -> Result<(), NftError> {
+        RNG.with_borrow_mut(|rng| match rng {
+            Some((rng, _)) => {
+                rng.fill_bytes(buf);
+                Ok(())
+            }
+            None => Err(NftError::Other("randomness not available yet".to_string())),
+        })
+    }
+
+    /// Returns a uniformly distributed random number in `0..bound`
+    pub fn below(bound: u64) -> Result<u64, NftError> {
+        if bound == 0 {
+            return Err(NftError::Other("empty range".to_string()));
+        }
+        // reject the values of the last incomplete range to avoid a modulo bias
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let mut buf = [0; 8];
+            Self::fill(&mut buf)?;
+            let value = u64::from_le_bytes(buf);
+            if value <= zone {
+                return Ok(value % bound);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_not_generate_before_seed() {
+        assert!(Random::below(10).is_err());
+        assert!(Random::next_reseed_at() <= crate::utils::time());
+    }
+
+    #[test]
+    fn test_should_generate_deterministic_sequence() {
+        Random::seed([1; 32]);
+        let first = (0..8)
+            .map(|_| Random::below(10).unwrap())
+            .collect::<Vec<_>>();
+        assert!(first.iter().all(|value| *value < 10));
+        assert!(Random::next_reseed_at() > crate::utils::time());
+
+        Random::seed([1; 32]);
+        let second = (0..8)
+            .map(|_| Random::below(10).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(first, second);
+        assert!(Random::below(0).is_err());
+    }
+
+    #[test]
+    fn test_should_back_off_failed_reseeds() {
+        let now = crate::utils::time();
+        Random::back_off();
+        let first = Random::next_reseed_at();
+        assert!(first >= now + RESEED_RETRY_DELAY);
+        Random::back_off();
+        assert!(Random::next_reseed_at() >= now + 2 * RESEED_RETRY_DELAY);
+        assert!(Random::next_reseed_at() > first);
+
+        Random::seed([1; 32]);
+        assert!(Random::next_reseed_at() >= now + RESEED_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn test_should_reseed_from_raw_rand() {
+        assert!(Random::reseed().await.is_ok());
+        assert!(Random::below(u64::MAX).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+//! # Signature
+//!
+//! Verification of payloads signed with keys held outside of the IC
+
+use candid::Principal;
+use dip721_rs::NftError;
+use k256::ecdsa::signature::Verifier as _;
+
+/// DER prefix of an Ed25519 subject public key info
+const ED25519_DER_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+/// DER prefix of a secp256k1 subject public key info, with an uncompressed point
+const SECP256K1_DER_PREFIX: [u8; 23] = [
+    0x30, 0x56, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b,
+    0x81, 0x04, 0x00, 0x0a, 0x03, 0x42, 0x00,
+];
+
+pub struct Signature;
+
+impl Signature {
+    /// Verify the signature of `message` with the DER encoded `public_key`.
+    ///
+    /// Both Ed25519 and secp256k1 (ECDSA over the SHA-256 of the message) keys are supported.
+    /// Returns the self-authenticating principal of the key, the same one the key would have as an IC identity.
+    pub fn verify(
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<Principal, NftError> {
+        if let Some(key) = public_key.strip_prefix(ED25519_DER_PREFIX.as_slice()) {
+            Self::verify_ed25519(key, message, signature)?;
+        } else if let Some(key) = public_key.strip_prefix(SECP256K1_DER_PREFIX.as_slice()) {
+            Self::verify_secp256k1(key, message, signature)?;
+        } else {
+            return Err(NftError::Other("unsupported public key".to_string()));
+        }
+
+        Ok(Principal::self_authenticating(public_key))
+    }
+
+    fn verify_ed25519(key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), NftError> {
+        let key = key
+            .try_into()
+            .ok()
+            .and_then(|key| ed25519_dalek::VerifyingKey::from_bytes(key).ok())
+            .ok_or_else(|| NftError::Other("invalid public key".to_string()))?;
+        let signature = ed25519_dalek::Signature::from_slice(signature)
+            .map_err(|_| Self::invalid_signature())?;
+
+        key.verify_strict(message, &signature)
+            .map_err(|_| Self::invalid_signature())
+    }
+
+    fn verify_secp256k1(key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), NftError> {
+        let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+            .map_err(|_| NftError::Other("invalid public key".to_string()))?;
+        let signature =
+            k256::ecdsa::Signature::from_slice(signature).map_err(|_| Self::invalid_signature())?;
+
+        key.verify(message, &signature)
+            .map_err(|_| Self::invalid_signature())
+    }
+
+    fn invalid_signature() -> NftError {
+        NftError::Other("invalid signature".to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+
+    use ed25519_dalek::Signer as _;
+
+    use super::*;
+
+    /// Ed25519 test key, returned along with its DER encoded public key
+    pub fn ed25519_key(seed: u8) -> (ed25519_dalek::SigningKey, Vec<u8>) {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+        let der = [
+            ED25519_DER_PREFIX.as_slice(),
+            key.verifying_key().as_bytes().as_slice(),
+        ]
+        .concat();
+
+        (key, der)
+    }
+
+    pub fn ed25519_sign(key: &ed25519_dalek::SigningKey, message: &[u8]) -> Vec<u8> {
+        key.sign(message).to_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use k256::ecdsa::signature::Signer as _;
+    use pretty_assertions::assert_eq;
+
+    use super::test_utils::{ed25519_key, ed25519_sign};
+    use super::*;
+
+    #[test]
+    fn test_should_verify_ed25519_signature() {
+        let (key, der) = ed25519_key(1);
+        let signature = ed25519_sign(&key, b"hello");
+
+        assert_eq!(
+            Signature::verify(&der, b"hello", &signature).unwrap(),
+            Principal::self_authenticating(&der)
+        );
+        assert!(Signature::verify(&der, b"hell0", &signature).is_err());
+        assert!(Signature::verify(&der[1..], b"hello", &signature).is_err());
+    }
+
+    #[test]
+    fn test_should_verify_secp256k1_signature() {
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let der = [
+            SECP256K1_DER_PREFIX.as_slice(),
+            key.verifying_key().to_encoded_point(false).as_bytes(),
+        ]
+        .concat();
+        let signature: k256::ecdsa::Signature = key.sign(b"hello");
+
+        assert_eq!(
+            Signature::verify(&der, b"hello", &signature.to_bytes()).unwrap(),
+            Principal::self_authenticating(&der)
+        );
+        assert!(Signature::verify(&der, b"hell0", &signature.to_bytes()).is_err());
+    }
+}
@@ -1,50 +1,255 @@
+mod allowlist;
+mod assets;
+mod auctions;
+mod children;
+mod editions;
+mod listings;
+mod nonces;
+mod offers;
+mod proceeds;
+mod random_pool;
+mod reveal;
+mod royalties;
+mod sale;
+mod swaps;
 mod tokens;
 mod tx_history;
+mod users;
 
 use std::cell::RefCell;
 
 use dip721_rs::{NftError, TokenIdentifier, TokenMetadata, TxEvent};
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{BTreeMap, DefaultMemoryImpl};
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
 
-use crate::app::memory::{MEMORY_MANAGER, TOKENS_MEMORY_ID, TRANSACTIONS_MEMORY_ID};
-use crate::storable::StorableNat;
+use crate::app::memory::{
+    ACCOUNT_TOKENS_MEMORY_ID, ALLOWLIST_MINTS_MEMORY_ID, ASSETS_MEMORY_ID, ASSET_CHUNKS_MEMORY_ID,
+    AUCTIONS_MEMORY_ID, BURNED_TOKENS_MEMORY_ID, EDITION_TEMPLATES_MEMORY_ID,
+    LEGACY_TOKENS_MEMORY_ID, LISTINGS_MEMORY_ID, MEMORY_MANAGER, NEXT_ASSET_ID_MEMORY_ID,
+    NEXT_OFFER_ID_MEMORY_ID, NEXT_SWAP_ID_MEMORY_ID, OFFERS_MEMORY_ID,
+    OWNER_INDEX_CURSOR_MEMORY_ID, PROCEEDS_MEMORY_ID, RANDOM_POOL_MEMORY_ID,
+    RANDOM_POOL_POSITIONS_MEMORY_ID, RANDOM_POOL_SWAPS_MEMORY_ID, REVEAL_METADATA_MEMORY_ID,
+    SALE_MINTED_MEMORY_ID, SALE_WALLET_MINTS_MEMORY_ID, SIGNED_NONCES_MEMORY_ID, SWAPS_MEMORY_ID,
+    SWAP_LOCKS_MEMORY_ID, TOKENS_MEMORY_ID, TOKEN_CHILDREN_MEMORY_ID, TOKEN_EDITIONS_MEMORY_ID,
+    TOKEN_PARENTS_MEMORY_ID, TOKEN_ROYALTIES_MEMORY_ID, TOKEN_SUBACCOUNTS_MEMORY_ID,
+    TOKEN_SUPPLY_MEMORY_ID, TOKEN_USERS_MEMORY_ID, TRANSACTIONS_MEMORY_ID,
+};
+use crate::did::{
+    AssetMetadata, Auction, CollectionId, EditionTemplate, Listing, Offer, RandomPool, Royalty,
+    Subaccount, Swap, TokenEdition, TokenUser, DEFAULT_COLLECTION_ID,
+};
+use crate::storable::{StorableNat, StorablePrincipal, StorableProperties};
 
+pub use allowlist::AllowlistStorage;
+pub use assets::AssetsStorage;
+pub use auctions::AuctionsStorage;
+pub use children::ChildrenStorage;
+pub use editions::EditionsStorage;
+pub use listings::ListingsStorage;
+pub use nonces::NoncesStorage;
+pub use offers::OffersStorage;
+pub use proceeds::ProceedsStorage;
+pub use random_pool::RandomPoolStorage;
+pub use reveal::RevealStorage;
+pub use royalties::RoyaltiesStorage;
+pub use sale::SaleStorage;
+pub use swaps::SwapsStorage;
 pub use tokens::TokensStorage;
 pub use tx_history::TxHistory;
+pub use users::UsersStorage;
+
+/// Key of a token: collection id and token identifier
+type TokenKey = (CollectionId, StorableNat);
+
+/// Key of an account in the owner index: owner and subaccount, the default subaccount being all zeros
+type AccountKey = (StorablePrincipal, Subaccount);
+
+/// Key of a token attached to a parent: parent and child token identifiers
+type TokenChildKey = (StorableNat, StorableNat);
+
+/// Key of an asset chunk: asset id and chunk index
+type AssetChunkKey = (u64, u32);
+
+/// Key of a signed request nonce: signer and nonce
+type SignedNonceKey = (StorablePrincipal, u64);
+
+/// Key of the tokens minted by a principal in an allowlist phase: phase id and principal
+type AllowlistMintKey = (u32, StorablePrincipal);
+
+/// Key of the proceeds owed to a recipient: ledger and recipient
+type ProceedsKey = (StorablePrincipal, StorablePrincipal);
 
 thread_local! {
-    /// Tokens storage (NFTs)
-    static TOKENS: RefCell<BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(TOKENS_MEMORY_ID))));
+    /// Tokens storage (NFTs), by collection
+    static TOKENS: RefCell<BTreeMap<TokenKey, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKENS_MEMORY_ID))));
+
+    /// Owner index: tokens held by each account
+    static ACCOUNT_TOKENS: RefCell<BTreeMap<(AccountKey, TokenKey), (), VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(ACCOUNT_TOKENS_MEMORY_ID))));
+
+    /// Subaccounts holding the tokens, for the tokens not held by the default subaccount of their owner
+    static TOKEN_SUBACCOUNTS: RefCell<BTreeMap<TokenKey, Subaccount, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_SUBACCOUNTS_MEMORY_ID))));
+
+    /// Amount of burned tokens, by collection, so that it's not counted over all the tokens
+    static BURNED_TOKENS: RefCell<BTreeMap<CollectionId, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(BURNED_TOKENS_MEMORY_ID))));
+
+    /// Amount of tokens, by collection, so that the supply is not counted over all the tokens
+    static TOKEN_SUPPLY: RefCell<BTreeMap<CollectionId, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_SUPPLY_MEMORY_ID))));
+
+    /// Next token to index in the owner index, while the tokens stored by previous versions are being indexed
+    static OWNER_INDEX_CURSOR: RefCell<StableCell<Option<TokenKey>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(OWNER_INDEX_CURSOR_MEMORY_ID)), None).unwrap());
+
+    /// Tokens of the default collection stored by previous versions; only read to migrate them
+    static LEGACY_TOKENS: RefCell<BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(LEGACY_TOKENS_MEMORY_ID))));
 
     /// Transactions history
     static TX_HISTORY: RefCell<BTreeMap<StorableNat, TxEvent, VirtualMemory<DefaultMemoryImpl>>> =
-        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(TRANSACTIONS_MEMORY_ID))));
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TRANSACTIONS_MEMORY_ID))));
+
+    /// Parents the tokens are attached to, by child (ERC-998)
+    static TOKEN_PARENTS: RefCell<BTreeMap<StorableNat, StorableNat, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_PARENTS_MEMORY_ID))));
+
+    /// Tokens attached to the parents, by parent (ERC-998)
+    static TOKEN_CHILDREN: RefCell<BTreeMap<TokenChildKey, (), VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_CHILDREN_MEMORY_ID))));
+
+    /// Users of the tokens (ERC-4907)
+    static TOKEN_USERS: RefCell<BTreeMap<StorableNat, TokenUser, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_USERS_MEMORY_ID))));
+
+    /// Edition templates, by id
+    static EDITION_TEMPLATES: RefCell<BTreeMap<u64, EditionTemplate, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(EDITION_TEMPLATES_MEMORY_ID))));
+
+    /// Editions the tokens were minted as
+    static TOKEN_EDITIONS: RefCell<BTreeMap<StorableNat, TokenEdition, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_EDITIONS_MEMORY_ID))));
+
+    /// Assets metadata
+    static ASSETS: RefCell<BTreeMap<u64, AssetMetadata, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(ASSETS_MEMORY_ID))));
+
+    /// Assets content
+    static ASSET_CHUNKS: RefCell<BTreeMap<AssetChunkKey, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(ASSET_CHUNKS_MEMORY_ID))));
+
+    /// Id of the next asset, never reused, kept across upgrades
+    static NEXT_ASSET_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(NEXT_ASSET_ID_MEMORY_ID)), 0).unwrap());
+
+    /// Nonces of the signed requests already executed, with their expiration
+    static SIGNED_NONCES: RefCell<BTreeMap<SignedNonceKey, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(SIGNED_NONCES_MEMORY_ID))));
+
+    /// Royalties of the tokens overriding the collection one
+    static TOKEN_ROYALTIES: RefCell<BTreeMap<StorableNat, Royalty, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(TOKEN_ROYALTIES_MEMORY_ID))));
+
+    /// Tokens listed on the marketplace
+    static LISTINGS: RefCell<BTreeMap<StorableNat, Listing, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(LISTINGS_MEMORY_ID))));
+
+    /// Auctions of the tokens, with their escrowed bids
+    static AUCTIONS: RefCell<BTreeMap<StorableNat, Auction, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(AUCTIONS_MEMORY_ID))));
+
+    /// Offers on tokens, by id
+    static OFFERS: RefCell<BTreeMap<u64, Offer, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(OFFERS_MEMORY_ID))));
+
+    /// Id of the next offer, never reused, kept across upgrades
+    static NEXT_OFFER_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(NEXT_OFFER_ID_MEMORY_ID)), 0).unwrap());
+
+    /// Swap proposals, by id
+    static SWAPS: RefCell<BTreeMap<u64, Swap, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(SWAPS_MEMORY_ID))));
+
+    /// Id of the next swap, never reused, kept across upgrades
+    static NEXT_SWAP_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(NEXT_SWAP_ID_MEMORY_ID)), 0).unwrap());
+
+    /// Tokens locked by a swap, with the id of the swap
+    static SWAP_LOCKS: RefCell<BTreeMap<StorableNat, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(SWAP_LOCKS_MEMORY_ID))));
+
+    /// Proceeds of the sales which couldn't be paid out
+    static PROCEEDS: RefCell<BTreeMap<ProceedsKey, StorableNat, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(PROCEEDS_MEMORY_ID))));
+
+    /// Amount of tokens minted by the public sale
+    static SALE_MINTED: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(SALE_MINTED_MEMORY_ID)), 0).unwrap());
+
+    /// Amount of tokens minted by each wallet in the public sale
+    static SALE_WALLET_MINTS: RefCell<BTreeMap<StorablePrincipal, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(SALE_WALLET_MINTS_MEMORY_ID))));
+
+    /// Pool of the token identifiers minted in random order
+    static RANDOM_POOL: RefCell<StableCell<Option<RandomPool>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::init(MEMORY_MANAGER.with(|mm| mm.get(RANDOM_POOL_MEMORY_ID)), None).unwrap());
+
+    /// Positions of the random pool whose value was swapped, with their value
+    static RANDOM_POOL_SWAPS: RefCell<BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(RANDOM_POOL_SWAPS_MEMORY_ID))));
+
+    /// Positions of the random pool holding a swapped value, by value
+    static RANDOM_POOL_POSITIONS: RefCell<BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(RANDOM_POOL_POSITIONS_MEMORY_ID))));
+
+    /// Revealed metadata left to give to the tokens, by index in the committed order
+    static REVEAL_METADATA: RefCell<BTreeMap<u64, StorableProperties, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(REVEAL_METADATA_MEMORY_ID))));
+
+    /// Amount of tokens minted by each principal in the allowlist phases
+    static ALLOWLIST_MINTS: RefCell<BTreeMap<AllowlistMintKey, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::init(MEMORY_MANAGER.with(|mm| mm.get(ALLOWLIST_MINTS_MEMORY_ID))));
 }
 
-fn with_token<T, F>(id: &TokenIdentifier, f: F) -> Result<T, NftError>
+/// Runs `f` on the token; tokens of the default collection not migrated yet are read from the legacy tokens
+fn with_token<T, F>(collection_id: CollectionId, id: &TokenIdentifier, f: F) -> Result<T, NftError>
 where
     F: FnOnce(&TokenMetadata) -> Result<T, NftError>,
 {
-    TOKENS.with_borrow(|tokens| {
-        if let Some(token) = tokens.get(&StorableNat::from(id.clone())) {
-            f(&token)
-        } else {
-            Err(NftError::TokenNotFound)
-        }
-    })
+    let id = StorableNat::from(id.clone());
+    let token = TOKENS
+        .with_borrow(|tokens| tokens.get(&(collection_id, id.clone())))
+        .or_else(|| {
+            (collection_id == DEFAULT_COLLECTION_ID)
+                .then(|| LEGACY_TOKENS.with_borrow(|tokens| tokens.get(&id)))
+                .flatten()
+        });
+    match token {
+        Some(token) => f(&token),
+        None => Err(NftError::TokenNotFound),
+    }
 }
 
-fn with_token_mut<T, F>(id: &TokenIdentifier, f: F) -> Result<T, NftError>
+fn with_token_mut<T, F>(
+    collection_id: CollectionId,
+    id: &TokenIdentifier,
+    f: F,
+) -> Result<T, NftError>
 where
     F: FnOnce(&mut TokenMetadata) -> Result<T, NftError>,
 {
+    // tokens of the default collection not migrated yet are migrated before being changed
+    if collection_id == DEFAULT_COLLECTION_ID {
+        migrate_legacy_token(StorableNat::from(id.clone()));
+    }
     TOKENS.with_borrow_mut(|tokens| {
-        if let Some(mut token) = tokens.get(&StorableNat::from(id.clone())) {
+        let key = (collection_id, StorableNat::from(id.clone()));
+        if let Some(mut token) = tokens.get(&key) {
             let res = f(&mut token)?;
             // update token
-            tokens.insert(StorableNat::from(id.clone()), token.clone());
+            tokens.insert(key, token.clone());
 
             Ok(res)
         } else {
@@ -55,18 +260,134 @@ where
 
 fn with_tokens<T, F>(f: F) -> T
 where
-    F: FnOnce(&BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+    F: FnOnce(&BTreeMap<TokenKey, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
 {
     TOKENS.with_borrow(|tokens| f(tokens))
 }
 
-pub fn with_tokens_mut<T, F>(f: F) -> T
+fn with_tokens_mut<T, F>(f: F) -> T
 where
-    F: FnOnce(&mut BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+    F: FnOnce(&mut BTreeMap<TokenKey, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
 {
     TOKENS.with_borrow_mut(|tokens| f(tokens))
 }
 
+fn with_account_tokens<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<(AccountKey, TokenKey), (), VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ACCOUNT_TOKENS.with_borrow(|account_tokens| f(account_tokens))
+}
+
+fn with_account_tokens_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<(AccountKey, TokenKey), (), VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ACCOUNT_TOKENS.with_borrow_mut(|account_tokens| f(account_tokens))
+}
+
+fn with_token_subaccounts<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<TokenKey, Subaccount, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_SUBACCOUNTS.with_borrow(|subaccounts| f(subaccounts))
+}
+
+fn with_token_subaccounts_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<TokenKey, Subaccount, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_SUBACCOUNTS.with_borrow_mut(|subaccounts| f(subaccounts))
+}
+
+fn with_burned_tokens<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<CollectionId, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    BURNED_TOKENS.with_borrow(|burned| f(burned))
+}
+
+fn with_burned_tokens_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<CollectionId, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    BURNED_TOKENS.with_borrow_mut(|burned| f(burned))
+}
+
+/// Count a token of the collection as burned
+fn count_burned_token(collection_id: CollectionId) {
+    with_burned_tokens_mut(|burned| {
+        let count = burned.get(&collection_id).unwrap_or_default();
+        burned.insert(collection_id, count + 1);
+    });
+}
+
+fn with_token_supply<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<CollectionId, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_SUPPLY.with_borrow(|supply| f(supply))
+}
+
+/// Count a token added to the collection in its supply
+fn count_minted_token(collection_id: CollectionId) {
+    TOKEN_SUPPLY.with_borrow_mut(|supply| {
+        let count = supply.get(&collection_id).unwrap_or_default();
+        supply.insert(collection_id, count + 1);
+    });
+}
+
+fn with_owner_index_cursor<T, F>(f: F) -> T
+where
+    F: FnOnce(&StableCell<Option<TokenKey>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    OWNER_INDEX_CURSOR.with_borrow(|cursor| f(cursor))
+}
+
+fn with_owner_index_cursor_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<Option<TokenKey>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    OWNER_INDEX_CURSOR.with_borrow_mut(|cursor| f(cursor))
+}
+
+fn with_legacy_tokens<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    LEGACY_TOKENS.with_borrow(|tokens| f(tokens))
+}
+
+#[cfg(test)]
+fn with_legacy_tokens_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, TokenMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    LEGACY_TOKENS.with_borrow_mut(|tokens| f(tokens))
+}
+
+/// Move the token of the default collection stored by previous versions, if any, to the tokens, indexing its owner;
+/// legacy tokens were all held by default subaccounts. Tokens written to the default collection since are kept.
+fn migrate_legacy_token(id: StorableNat) {
+    let Some(token) = LEGACY_TOKENS.with_borrow_mut(|tokens| tokens.remove(&id)) else {
+        return;
+    };
+    let key = (DEFAULT_COLLECTION_ID, id);
+    if TOKENS.with_borrow(|tokens| tokens.contains_key(&key)) {
+        return;
+    }
+    if let Some(owner) = token.owner.filter(|_| !token.is_burned) {
+        ACCOUNT_TOKENS.with_borrow_mut(|account_tokens| {
+            account_tokens.insert(((owner.into(), Subaccount::default()), key.clone()), ())
+        });
+    }
+    if token.is_burned {
+        count_burned_token(DEFAULT_COLLECTION_ID);
+    }
+    count_minted_token(DEFAULT_COLLECTION_ID);
+    TOKENS.with_borrow_mut(|tokens| tokens.insert(key, token));
+}
+
 fn with_tx_history<T, F>(f: F) -> T
 where
     F: FnOnce(&BTreeMap<StorableNat, TxEvent, VirtualMemory<DefaultMemoryImpl>>) -> T,
@@ -80,3 +401,346 @@ where
 {
     TX_HISTORY.with_borrow_mut(|tx_history| f(tx_history))
 }
+
+fn with_assets<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, AssetMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ASSETS.with_borrow(|assets| f(assets))
+}
+
+fn with_assets_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, AssetMetadata, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ASSETS.with_borrow_mut(|assets| f(assets))
+}
+
+fn with_asset_chunks<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<AssetChunkKey, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ASSET_CHUNKS.with_borrow(|chunks| f(chunks))
+}
+
+fn with_asset_chunks_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<AssetChunkKey, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ASSET_CHUNKS.with_borrow_mut(|chunks| f(chunks))
+}
+
+fn with_next_asset_id_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    NEXT_ASSET_ID.with_borrow_mut(|next_id| f(next_id))
+}
+
+fn with_signed_nonces<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<SignedNonceKey, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SIGNED_NONCES.with_borrow(|nonces| f(nonces))
+}
+
+fn with_signed_nonces_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<SignedNonceKey, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SIGNED_NONCES.with_borrow_mut(|nonces| f(nonces))
+}
+
+fn with_token_royalties<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, Royalty, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_ROYALTIES.with_borrow(|royalties| f(royalties))
+}
+
+fn with_token_royalties_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, Royalty, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_ROYALTIES.with_borrow_mut(|royalties| f(royalties))
+}
+
+fn with_listings<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, Listing, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    LISTINGS.with_borrow(|listings| f(listings))
+}
+
+fn with_listings_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, Listing, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    LISTINGS.with_borrow_mut(|listings| f(listings))
+}
+
+fn with_proceeds<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<ProceedsKey, StorableNat, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    PROCEEDS.with_borrow(|proceeds| f(proceeds))
+}
+
+fn with_proceeds_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<ProceedsKey, StorableNat, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    PROCEEDS.with_borrow_mut(|proceeds| f(proceeds))
+}
+
+fn with_auctions<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, Auction, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AUCTIONS.with_borrow(|auctions| f(auctions))
+}
+
+fn with_auctions_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, Auction, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AUCTIONS.with_borrow_mut(|auctions| f(auctions))
+}
+
+fn with_offers<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, Offer, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    OFFERS.with_borrow(|offers| f(offers))
+}
+
+fn with_offers_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, Offer, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    OFFERS.with_borrow_mut(|offers| f(offers))
+}
+
+fn with_next_offer_id_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    NEXT_OFFER_ID.with_borrow_mut(|next_id| f(next_id))
+}
+
+/// Takes the next id of a monotonic counter, so that ids are never reused.
+/// The id is at least `floor`, the id following the stored ones, in case the counter is behind them.
+fn take_next_id(
+    counter: &mut StableCell<u64, VirtualMemory<DefaultMemoryImpl>>,
+    floor: u64,
+) -> u64 {
+    let id = (*counter.get()).max(floor);
+    counter
+        .set(id + 1)
+        .expect("failed to write counter to stable memory");
+
+    id
+}
+
+fn with_swaps<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, Swap, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SWAPS.with_borrow(|swaps| f(swaps))
+}
+
+fn with_swaps_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, Swap, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SWAPS.with_borrow_mut(|swaps| f(swaps))
+}
+
+fn with_next_swap_id_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    NEXT_SWAP_ID.with_borrow_mut(|next_id| f(next_id))
+}
+
+fn with_swap_locks<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SWAP_LOCKS.with_borrow(|locks| f(locks))
+}
+
+fn with_swap_locks_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SWAP_LOCKS.with_borrow_mut(|locks| f(locks))
+}
+
+fn with_sale_minted<T, F>(f: F) -> T
+where
+    F: FnOnce(&StableCell<u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SALE_MINTED.with_borrow(|minted| f(minted))
+}
+
+fn with_sale_minted_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SALE_MINTED.with_borrow_mut(|minted| f(minted))
+}
+
+fn with_sale_wallet_mints<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorablePrincipal, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SALE_WALLET_MINTS.with_borrow(|mints| f(mints))
+}
+
+fn with_sale_wallet_mints_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorablePrincipal, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    SALE_WALLET_MINTS.with_borrow_mut(|mints| f(mints))
+}
+
+fn with_allowlist_mints<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<AllowlistMintKey, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ALLOWLIST_MINTS.with_borrow(|mints| f(mints))
+}
+
+fn with_allowlist_mints_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<AllowlistMintKey, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    ALLOWLIST_MINTS.with_borrow_mut(|mints| f(mints))
+}
+
+fn with_random_pool<T, F>(f: F) -> T
+where
+    F: FnOnce(&StableCell<Option<RandomPool>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL.with_borrow(|pool| f(pool))
+}
+
+fn with_random_pool_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StableCell<Option<RandomPool>, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL.with_borrow_mut(|pool| f(pool))
+}
+
+fn with_random_pool_swaps<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL_SWAPS.with_borrow(|swaps| f(swaps))
+}
+
+fn with_random_pool_swaps_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL_SWAPS.with_borrow_mut(|swaps| f(swaps))
+}
+
+fn with_random_pool_positions<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL_POSITIONS.with_borrow(|positions| f(positions))
+}
+
+fn with_random_pool_positions_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, u64, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    RANDOM_POOL_POSITIONS.with_borrow_mut(|positions| f(positions))
+}
+
+fn with_reveal_metadata<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, StorableProperties, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    REVEAL_METADATA.with_borrow(|metadata| f(metadata))
+}
+
+fn with_reveal_metadata_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, StorableProperties, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    REVEAL_METADATA.with_borrow_mut(|metadata| f(metadata))
+}
+
+fn with_token_users<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, TokenUser, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_USERS.with_borrow(|users| f(users))
+}
+
+fn with_token_users_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, TokenUser, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_USERS.with_borrow_mut(|users| f(users))
+}
+
+fn with_token_parents<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, StorableNat, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_PARENTS.with_borrow(|parents| f(parents))
+}
+
+fn with_token_parents_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, StorableNat, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_PARENTS.with_borrow_mut(|parents| f(parents))
+}
+
+fn with_token_children<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<TokenChildKey, (), VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_CHILDREN.with_borrow(|children| f(children))
+}
+
+fn with_token_children_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<TokenChildKey, (), VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_CHILDREN.with_borrow_mut(|children| f(children))
+}
+
+fn with_edition_templates<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, EditionTemplate, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    EDITION_TEMPLATES.with_borrow(|templates| f(templates))
+}
+
+fn with_edition_templates_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, EditionTemplate, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    EDITION_TEMPLATES.with_borrow_mut(|templates| f(templates))
+}
+
+fn with_token_editions<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorableNat, TokenEdition, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_EDITIONS.with_borrow(|editions| f(editions))
+}
+
+fn with_token_editions_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorableNat, TokenEdition, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    TOKEN_EDITIONS.with_borrow_mut(|editions| f(editions))
+}
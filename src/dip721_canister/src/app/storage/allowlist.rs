@@ -0,0 +1,66 @@
+use candid::Principal;
+use dip721_rs::NftError;
+
+use super::{with_allowlist_mints, with_allowlist_mints_mut};
+
+/// Counters of the tokens minted by the principals of the allowlist phases
+pub struct AllowlistStorage;
+
+impl AllowlistStorage {
+    /// Get the amount of tokens minted by the principal in the phase
+    pub fn minted(phase: u32, principal: Principal) -> u64 {
+        with_allowlist_mints(|mints| mints.get(&(phase, principal.into())).unwrap_or_default())
+    }
+
+    /// Reserve `quantity` tokens of the phase for the principal, up to its `max_quantity`
+    pub fn reserve(
+        phase: u32,
+        principal: Principal,
+        quantity: u64,
+        max_quantity: u64,
+    ) -> Result<(), NftError> {
+        let minted = Self::minted(phase, principal);
+        if minted.saturating_add(quantity) > max_quantity {
+            return Err(NftError::Other("allowlist quantity exceeded".to_string()));
+        }
+        with_allowlist_mints_mut(|mints| {
+            mints.insert((phase, principal.into()), minted + quantity)
+        });
+
+        Ok(())
+    }
+
+    /// Release `quantity` tokens previously reserved for the principal
+    pub fn release(phase: u32, principal: Principal, quantity: u64) {
+        let minted = Self::minted(phase, principal).saturating_sub(quantity);
+        with_allowlist_mints_mut(|mints| {
+            if minted == 0 {
+                mints.remove(&(phase, principal.into()));
+            } else {
+                mints.insert((phase, principal.into()), minted);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_reserve_and_release_allowlist_mints() {
+        let alice = Principal::anonymous();
+        assert!(AllowlistStorage::reserve(1, alice, 2, 3).is_ok());
+        assert!(AllowlistStorage::reserve(1, alice, 2, 3).is_err());
+        // phases are counted separately
+        assert!(AllowlistStorage::reserve(2, alice, 3, 3).is_ok());
+        assert_eq!(AllowlistStorage::minted(1, alice), 2);
+
+        AllowlistStorage::release(1, alice, 2);
+        assert_eq!(AllowlistStorage::minted(1, alice), 0);
+        assert_eq!(AllowlistStorage::minted(2, alice), 3);
+    }
+}
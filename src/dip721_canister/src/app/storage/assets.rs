@@ -0,0 +1,207 @@
+use dip721_rs::NftError;
+use serde_bytes::ByteBuf;
+use sha2::{Digest as _, Sha256};
+
+use super::{
+    take_next_id, with_asset_chunks, with_asset_chunks_mut, with_assets, with_assets_mut,
+    with_next_asset_id_mut,
+};
+use crate::did::AssetMetadata;
+
+/// Maximum size of a single uploaded chunk
+pub const MAX_CHUNK_SIZE: usize = 2 * 1024 * 1024;
+
+pub struct AssetsStorage;
+
+impl AssetsStorage {
+    /// Get asset metadata
+    pub fn get_asset(id: u64) -> Option<AssetMetadata> {
+        with_assets(|assets| assets.get(&id))
+    }
+
+    /// Create a new empty asset and returns its id
+    pub fn create(content_type: String, sha256: [u8; 32]) -> u64 {
+        let id = Self::next_id();
+        let asset = AssetMetadata {
+            id,
+            content_type,
+            sha256: ByteBuf::from(sha256.to_vec()),
+            size: 0,
+            chunks: 0,
+            finalized: false,
+            created_at: crate::utils::time(),
+            created_by: crate::utils::caller(),
+        };
+        with_assets_mut(|assets| assets.insert(id, asset));
+
+        id
+    }
+
+    /// Append a chunk to a pending asset
+    pub fn upload_chunk(id: u64, chunk: Vec<u8>) -> Result<(), NftError> {
+        if chunk.len() > MAX_CHUNK_SIZE {
+            return Err(NftError::Other("chunk too big".to_string()));
+        }
+        let mut asset = Self::get_asset(id).ok_or(Self::asset_not_found())?;
+        if asset.finalized {
+            return Err(NftError::Other("asset already finalized".to_string()));
+        }
+
+        let chunk_index = asset.chunks;
+        asset.chunks += 1;
+        asset.size += chunk.len() as u64;
+
+        with_asset_chunks_mut(|chunks| chunks.insert((id, chunk_index), chunk));
+        with_assets_mut(|assets| assets.insert(id, asset));
+
+        Ok(())
+    }
+
+    /// Verify the checksum of the uploaded content and mark the asset as finalized
+    pub fn finalize(id: u64) -> Result<AssetMetadata, NftError> {
+        let mut asset = Self::get_asset(id).ok_or(Self::asset_not_found())?;
+        if asset.finalized {
+            return Err(NftError::Other("asset already finalized".to_string()));
+        }
+
+        let mut hasher = Sha256::new();
+        with_asset_chunks(|chunks| {
+            for (_, chunk) in chunks.range((id, 0)..(id, asset.chunks)) {
+                hasher.update(&chunk);
+            }
+        });
+        if hasher.finalize().as_slice() != asset.sha256.as_slice() {
+            return Err(NftError::Other("checksum mismatch".to_string()));
+        }
+
+        asset.finalized = true;
+        with_assets_mut(|assets| assets.insert(id, asset.clone()));
+
+        Ok(asset)
+    }
+
+    /// Delete asset and its content
+    pub fn delete(id: u64) -> Result<(), NftError> {
+        let asset = with_assets_mut(|assets| assets.remove(&id)).ok_or(Self::asset_not_found())?;
+        with_asset_chunks_mut(|chunks| {
+            for chunk_index in 0..asset.chunks {
+                chunks.remove(&(id, chunk_index));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Read the asset content in the range `[start, end)`
+    pub fn read(id: u64, start: u64, end: u64) -> Result<Vec<u8>, NftError> {
+        let asset = Self::get_asset(id).ok_or(Self::asset_not_found())?;
+        let end = end.min(asset.size);
+        if start > end {
+            return Err(NftError::Other("invalid range".to_string()));
+        }
+
+        let mut content = Vec::with_capacity((end - start) as usize);
+        let mut offset = 0;
+        with_asset_chunks(|chunks| {
+            for (_, chunk) in chunks.range((id, 0)..(id, asset.chunks)) {
+                let chunk_start = offset;
+                let chunk_end = offset + chunk.len() as u64;
+                offset = chunk_end;
+                if chunk_end <= start {
+                    continue;
+                }
+                if chunk_start >= end {
+                    break;
+                }
+                let from = start.saturating_sub(chunk_start) as usize;
+                let to = (end.min(chunk_end) - chunk_start) as usize;
+                content.extend_from_slice(&chunk[from..to]);
+            }
+        });
+
+        Ok(content)
+    }
+
+    /// Ids are never reused, so that tokens referencing a deleted asset can't serve another one
+    fn next_id() -> u64 {
+        let floor = with_assets(|assets| {
+            assets
+                .last_key_value()
+                .map(|(id, _)| id + 1)
+                .unwrap_or_default()
+        });
+        with_next_asset_id_mut(|next_id| take_next_id(next_id, floor))
+    }
+
+    fn asset_not_found() -> NftError {
+        NftError::Other("asset not found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+
+    #[test]
+    fn test_should_upload_and_finalize_asset() {
+        let id = AssetsStorage::create("image/png".to_string(), sha256(b"hello world"));
+        assert_eq!(id, 0);
+        assert!(AssetsStorage::upload_chunk(id, b"hello ".to_vec()).is_ok());
+        assert!(AssetsStorage::upload_chunk(id, b"world".to_vec()).is_ok());
+
+        let asset = AssetsStorage::finalize(id).unwrap();
+        assert!(asset.finalized);
+        assert_eq!(asset.size, 11);
+        assert_eq!(asset.chunks, 2);
+        assert_eq!(asset.content_type, "image/png");
+
+        // cannot upload after finalize
+        assert!(AssetsStorage::upload_chunk(id, b"!".to_vec()).is_err());
+        assert_eq!(
+            AssetsStorage::create("image/png".to_string(), sha256(b"")),
+            1
+        );
+    }
+
+    #[test]
+    fn test_should_not_finalize_on_checksum_mismatch() {
+        let id = AssetsStorage::create("image/png".to_string(), sha256(b"hello world"));
+        assert!(AssetsStorage::upload_chunk(id, b"hello".to_vec()).is_ok());
+        assert!(AssetsStorage::finalize(id).is_err());
+        assert!(!AssetsStorage::get_asset(id).unwrap().finalized);
+    }
+
+    #[test]
+    fn test_should_read_asset_range() {
+        let id = AssetsStorage::create("text/plain".to_string(), sha256(b"hello world"));
+        assert!(AssetsStorage::upload_chunk(id, b"hello ".to_vec()).is_ok());
+        assert!(AssetsStorage::upload_chunk(id, b"world".to_vec()).is_ok());
+
+        assert_eq!(AssetsStorage::read(id, 0, 11).unwrap(), b"hello world");
+        assert_eq!(AssetsStorage::read(id, 4, 8).unwrap(), b"o wo");
+        assert_eq!(AssetsStorage::read(id, 6, 100).unwrap(), b"world");
+        assert!(AssetsStorage::read(id, 12, 20).is_err());
+    }
+
+    #[test]
+    fn test_should_delete_asset() {
+        let id = AssetsStorage::create("text/plain".to_string(), sha256(b"hello"));
+        assert!(AssetsStorage::upload_chunk(id, b"hello".to_vec()).is_ok());
+        assert!(AssetsStorage::delete(id).is_ok());
+        assert!(AssetsStorage::get_asset(id).is_none());
+        assert!(with_asset_chunks(|chunks| chunks.is_empty()));
+        assert!(AssetsStorage::delete(id).is_err());
+        // the id of the deleted asset isn't reused
+        assert_ne!(
+            AssetsStorage::create("text/plain".to_string(), sha256(b"hello")),
+            id
+        );
+    }
+}
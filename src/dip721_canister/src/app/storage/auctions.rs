@@ -0,0 +1,102 @@
+use dip721_rs::TokenIdentifier;
+
+use super::{with_auctions, with_auctions_mut};
+use crate::did::Auction;
+use crate::storable::StorableNat;
+
+pub struct AuctionsStorage;
+
+impl AuctionsStorage {
+    /// Get the auction of the token, if any
+    pub fn get(token_identifier: &TokenIdentifier) -> Option<Auction> {
+        with_auctions(|auctions| auctions.get(&StorableNat::from(token_identifier.clone())))
+    }
+
+    /// Returns whether the token is under auction
+    pub fn contains(token_identifier: &TokenIdentifier) -> bool {
+        with_auctions(|auctions| {
+            auctions.contains_key(&StorableNat::from(token_identifier.clone()))
+        })
+    }
+
+    /// Get all the auctions
+    pub fn auctions() -> Vec<(TokenIdentifier, Auction)> {
+        with_auctions(|auctions| {
+            auctions
+                .iter()
+                .map(|(token_identifier, auction)| (token_identifier.0, auction))
+                .collect()
+        })
+    }
+
+    /// Returns the tokens of the auctions ended at `now`
+    pub fn ended(now: u64) -> Vec<TokenIdentifier> {
+        with_auctions(|auctions| {
+            auctions
+                .iter()
+                .filter(|(_, auction)| auction.has_ended(now))
+                .map(|(token_identifier, _)| token_identifier.0)
+                .collect()
+        })
+    }
+
+    /// Returns the end of the auction ending first
+    pub fn next_end() -> Option<u64> {
+        with_auctions(|auctions| auctions.iter().map(|(_, auction)| auction.ends_at).min())
+    }
+
+    /// Insert or update the auction of the token
+    pub fn insert(token_identifier: &TokenIdentifier, auction: Auction) {
+        with_auctions_mut(|auctions| {
+            auctions.insert(StorableNat::from(token_identifier.clone()), auction)
+        });
+    }
+
+    /// Remove the auction of the token, returning it
+    pub fn remove(token_identifier: &TokenIdentifier) -> Option<Auction> {
+        with_auctions_mut(|auctions| auctions.remove(&StorableNat::from(token_identifier.clone())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::did::AuctionKind;
+
+    fn auction(ends_at: u64) -> Auction {
+        Auction {
+            seller: Principal::anonymous(),
+            ledger: Principal::management_canister(),
+            kind: AuctionKind::Dutch {
+                start_price: 1_000_u64.into(),
+                end_price: 100_u64.into(),
+            },
+            starts_at: 0,
+            ends_at,
+            bids: vec![],
+        }
+    }
+
+    #[test]
+    fn test_should_insert_and_remove_auctions() {
+        assert!(!AuctionsStorage::contains(&1_u64.into()));
+        AuctionsStorage::insert(&1_u64.into(), auction(100));
+        AuctionsStorage::insert(&2_u64.into(), auction(50));
+        assert!(AuctionsStorage::contains(&1_u64.into()));
+        assert_eq!(AuctionsStorage::get(&1_u64.into()), Some(auction(100)));
+        assert_eq!(AuctionsStorage::auctions().len(), 2);
+        assert_eq!(AuctionsStorage::next_end(), Some(50));
+        assert_eq!(
+            AuctionsStorage::ended(75),
+            vec![TokenIdentifier::from(2_u64)]
+        );
+
+        assert_eq!(AuctionsStorage::remove(&2_u64.into()), Some(auction(50)));
+        assert_eq!(AuctionsStorage::next_end(), Some(100));
+        assert!(AuctionsStorage::ended(75).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use candid::Nat;
+use dip721_rs::{NftError, TokenIdentifier};
+
+use super::{
+    with_token_children, with_token_children_mut, with_token_parents, with_token_parents_mut,
+};
+use crate::storable::StorableNat;
+
+/// Tokens attached to a parent token, which they're transferred along with (ERC-998)
+pub struct ChildrenStorage;
+
+impl ChildrenStorage {
+    /// Get the parent the token is attached to, if any
+    pub fn parent_of(token_identifier: &TokenIdentifier) -> Option<TokenIdentifier> {
+        with_token_parents(|parents| parents.get(&StorableNat::from(token_identifier.clone())))
+            .map(|parent| parent.0)
+    }
+
+    /// Get the tokens attached to the parent, ordered by identifier
+    pub fn children_of(parent: &TokenIdentifier) -> Vec<TokenIdentifier> {
+        let first = StorableNat::from(Nat::from(0_u64));
+        let range = (StorableNat::from(parent.clone()), first.clone())
+            ..(StorableNat::from(parent.clone() + 1_u64), first);
+        with_token_children(|children| {
+            children
+                .range(range)
+                .map(|((_, child), _)| child.0)
+                .collect()
+        })
+    }
+
+    /// Get the tokens attached to the parent and, recursively, to its children
+    pub fn descendants_of(parent: &TokenIdentifier) -> Vec<TokenIdentifier> {
+        let mut descendants = Self::children_of(parent);
+        let mut index = 0;
+        while let Some(token_identifier) = descendants.get(index) {
+            let children = Self::children_of(token_identifier);
+            descendants.extend(children);
+            index += 1;
+        }
+
+        descendants
+    }
+
+    /// Get the root of the tree the token belongs to; the token itself if not attached
+    pub fn root_of(token_identifier: &TokenIdentifier) -> TokenIdentifier {
+        let mut root = token_identifier.clone();
+        while let Some(parent) = Self::parent_of(&root) {
+            root = parent;
+        }
+
+        root
+    }
+
+    /// Attach the child to the parent, checking the child is not attached yet and no cycle is made
+    pub fn attach(parent: &TokenIdentifier, child: &TokenIdentifier) -> Result<(), NftError> {
+        if Self::parent_of(child).is_some() {
+            return Err(NftError::Other(
+                "token is already attached to a parent".to_string(),
+            ));
+        }
+        // the parent can't be the child itself, nor one of its descendants
+        let mut ancestor = Some(parent.clone());
+        while let Some(token_identifier) = ancestor {
+            if &token_identifier == child {
+                return Err(NftError::Other(
+                    "token can't be attached to itself or its descendants".to_string(),
+                ));
+            }
+            ancestor = Self::parent_of(&token_identifier);
+        }
+
+        with_token_parents_mut(|parents| {
+            parents.insert(
+                StorableNat::from(child.clone()),
+                StorableNat::from(parent.clone()),
+            );
+        });
+        with_token_children_mut(|children| {
+            children.insert(
+                (
+                    StorableNat::from(parent.clone()),
+                    StorableNat::from(child.clone()),
+                ),
+                (),
+            );
+        });
+
+        Ok(())
+    }
+
+    /// Detach the child from the parent
+    pub fn detach(parent: &TokenIdentifier, child: &TokenIdentifier) -> Result<(), NftError> {
+        if Self::parent_of(child).as_ref() != Some(parent) {
+            return Err(NftError::Other(
+                "token is not attached to the parent".to_string(),
+            ));
+        }
+
+        with_token_parents_mut(|parents| {
+            parents.remove(&StorableNat::from(child.clone()));
+        });
+        with_token_children_mut(|children| {
+            children.remove(&(
+                StorableNat::from(parent.clone()),
+                StorableNat::from(child.clone()),
+            ));
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_attach_and_detach_children() {
+        assert!(ChildrenStorage::attach(&1_u64.into(), &2_u64.into()).is_ok());
+        assert!(ChildrenStorage::attach(&1_u64.into(), &3_u64.into()).is_ok());
+        assert!(ChildrenStorage::attach(&3_u64.into(), &4_u64.into()).is_ok());
+        assert!(ChildrenStorage::attach(&5_u64.into(), &2_u64.into()).is_err());
+
+        assert_eq!(
+            ChildrenStorage::children_of(&1_u64.into()),
+            vec![TokenIdentifier::from(2_u64), TokenIdentifier::from(3_u64)]
+        );
+        assert_eq!(
+            ChildrenStorage::descendants_of(&1_u64.into()),
+            vec![
+                TokenIdentifier::from(2_u64),
+                TokenIdentifier::from(3_u64),
+                TokenIdentifier::from(4_u64)
+            ]
+        );
+        assert_eq!(
+            ChildrenStorage::root_of(&4_u64.into()),
+            TokenIdentifier::from(1_u64)
+        );
+
+        assert!(ChildrenStorage::detach(&1_u64.into(), &4_u64.into()).is_err());
+        assert!(ChildrenStorage::detach(&3_u64.into(), &4_u64.into()).is_ok());
+        assert!(ChildrenStorage::parent_of(&4_u64.into()).is_none());
+        assert!(ChildrenStorage::children_of(&3_u64.into()).is_empty());
+    }
+
+    #[test]
+    fn test_should_not_attach_to_descendants() {
+        assert!(ChildrenStorage::attach(&1_u64.into(), &1_u64.into()).is_err());
+        assert!(ChildrenStorage::attach(&1_u64.into(), &2_u64.into()).is_ok());
+        assert!(ChildrenStorage::attach(&2_u64.into(), &3_u64.into()).is_ok());
+        assert!(ChildrenStorage::attach(&3_u64.into(), &1_u64.into()).is_err());
+    }
+}
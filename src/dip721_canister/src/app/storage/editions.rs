@@ -0,0 +1,201 @@
+use dip721_rs::{GenericValue, NftError, TokenIdentifier};
+
+use super::{
+    with_edition_templates, with_edition_templates_mut, with_token_editions,
+    with_token_editions_mut,
+};
+use crate::did::{EditionTemplate, TokenEdition};
+use crate::storable::StorableNat;
+
+/// Token property holding the id of the template the token is an edition of
+pub const EDITION_TEMPLATE_PROPERTY: &str = "edition_template";
+/// Token property holding the edition number of the token
+pub const EDITION_PROPERTY: &str = "edition";
+
+/// Edition templates and the editions minted from them
+pub struct EditionsStorage;
+
+impl EditionsStorage {
+    /// Get the template with the provided id
+    pub fn get_template(template_id: u64) -> Option<EditionTemplate> {
+        with_edition_templates(|templates| templates.get(&template_id))
+    }
+
+    /// Get all the templates, ordered by id
+    pub fn templates() -> Vec<(u64, EditionTemplate)> {
+        with_edition_templates(|templates| templates.iter().collect())
+    }
+
+    /// Insert a new template and returns its id
+    pub fn create_template(
+        properties: Vec<(String, GenericValue)>,
+        max_editions: Option<u64>,
+    ) -> u64 {
+        with_edition_templates_mut(|templates| {
+            let id = templates
+                .last_key_value()
+                .map(|(id, _)| id + 1)
+                .unwrap_or(1);
+            templates.insert(
+                id,
+                EditionTemplate {
+                    properties,
+                    max_editions,
+                    minted: 0,
+                },
+            );
+
+            id
+        })
+    }
+
+    /// Get the next edition of the template, checking it is not sold out
+    pub fn next_edition(template_id: u64) -> Result<TokenEdition, NftError> {
+        let template = Self::get_template(template_id)
+            .ok_or_else(|| NftError::Other("template not found".to_string()))?;
+        if template
+            .max_editions
+            .is_some_and(|max_editions| template.minted >= max_editions)
+        {
+            return Err(NftError::Other("editions sold out".to_string()));
+        }
+
+        Ok(TokenEdition {
+            template_id,
+            edition: template.minted + 1,
+        })
+    }
+
+    /// Register the token as the provided edition of its template
+    pub fn insert_edition(token_identifier: &TokenIdentifier, edition: TokenEdition) {
+        with_edition_templates_mut(|templates| {
+            if let Some(mut template) = templates.get(&edition.template_id) {
+                template.minted = template.minted.max(edition.edition);
+                templates.insert(edition.template_id, template);
+            }
+        });
+        with_token_editions_mut(|editions| {
+            editions.insert(StorableNat::from(token_identifier.clone()), edition);
+        });
+    }
+
+    /// Get the edition the token was minted as, if any
+    pub fn get_edition(token_identifier: &TokenIdentifier) -> Option<TokenEdition> {
+        with_token_editions(|editions| editions.get(&StorableNat::from(token_identifier.clone())))
+    }
+
+    /// Merge the properties of the token with the ones of its template and its edition number.
+    /// Properties of the token take precedence over the template ones with the same key.
+    pub fn merge_properties(
+        token_identifier: &TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Vec<(String, GenericValue)> {
+        let Some(edition) = Self::get_edition(token_identifier) else {
+            return properties;
+        };
+        let template_properties = Self::get_template(edition.template_id)
+            .map(|template| template.properties)
+            .unwrap_or_default();
+
+        let mut merged: Vec<(String, GenericValue)> =
+            Vec::with_capacity(template_properties.len() + properties.len() + 2);
+        for (key, value) in template_properties
+            .into_iter()
+            .chain([
+                (
+                    EDITION_TEMPLATE_PROPERTY.to_string(),
+                    GenericValue::Nat64Content(edition.template_id),
+                ),
+                (
+                    EDITION_PROPERTY.to_string(),
+                    GenericValue::Nat64Content(edition.edition),
+                ),
+            ])
+            .chain(properties)
+        {
+            match merged.iter_mut().find(|(k, _)| k == &key) {
+                Some((_, v)) => *v = value,
+                None => merged.push((key, value)),
+            }
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_mint_editions_up_to_max() {
+        let id = EditionsStorage::create_template(vec![], Some(2));
+        assert_eq!(id, 1);
+        assert_eq!(EditionsStorage::create_template(vec![], None), 2);
+
+        for (token, edition) in [(10_u64, 1), (11, 2)] {
+            let next = EditionsStorage::next_edition(id).unwrap();
+            assert_eq!(next.edition, edition);
+            EditionsStorage::insert_edition(&token.into(), next);
+        }
+        assert!(EditionsStorage::next_edition(id).is_err());
+        assert!(EditionsStorage::next_edition(3).is_err());
+        assert_eq!(EditionsStorage::get_template(id).unwrap().minted, 2);
+        assert_eq!(
+            EditionsStorage::get_edition(&11_u64.into()),
+            Some(TokenEdition {
+                template_id: id,
+                edition: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_should_merge_template_properties() {
+        let id = EditionsStorage::create_template(
+            vec![
+                (
+                    "name".to_string(),
+                    GenericValue::TextContent("art".to_string()),
+                ),
+                (
+                    "color".to_string(),
+                    GenericValue::TextContent("red".to_string()),
+                ),
+            ],
+            None,
+        );
+        let properties = vec![(
+            "color".to_string(),
+            GenericValue::TextContent("blue".to_string()),
+        )];
+        assert_eq!(
+            EditionsStorage::merge_properties(&1_u64.into(), properties.clone()),
+            properties
+        );
+
+        let edition = EditionsStorage::next_edition(id).unwrap();
+        EditionsStorage::insert_edition(&1_u64.into(), edition);
+        assert_eq!(
+            EditionsStorage::merge_properties(&1_u64.into(), properties),
+            vec![
+                (
+                    "name".to_string(),
+                    GenericValue::TextContent("art".to_string())
+                ),
+                (
+                    "color".to_string(),
+                    GenericValue::TextContent("blue".to_string())
+                ),
+                (
+                    EDITION_TEMPLATE_PROPERTY.to_string(),
+                    GenericValue::Nat64Content(id)
+                ),
+                (EDITION_PROPERTY.to_string(), GenericValue::Nat64Content(1)),
+            ]
+        );
+    }
+}
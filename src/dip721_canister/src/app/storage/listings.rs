@@ -0,0 +1,65 @@
+use dip721_rs::TokenIdentifier;
+
+use super::{with_listings, with_listings_mut};
+use crate::did::Listing;
+use crate::storable::StorableNat;
+
+pub struct ListingsStorage;
+
+impl ListingsStorage {
+    /// Get the listing of the token, if listed
+    pub fn get(token_identifier: &TokenIdentifier) -> Option<Listing> {
+        with_listings(|listings| listings.get(&StorableNat::from(token_identifier.clone())))
+    }
+
+    /// Get all the listings
+    pub fn listings() -> Vec<(TokenIdentifier, Listing)> {
+        with_listings(|listings| {
+            listings
+                .iter()
+                .map(|(token_identifier, listing)| (token_identifier.0, listing))
+                .collect()
+        })
+    }
+
+    /// List the token, replacing its previous listing
+    pub fn insert(token_identifier: &TokenIdentifier, listing: Listing) {
+        with_listings_mut(|listings| {
+            listings.insert(StorableNat::from(token_identifier.clone()), listing)
+        });
+    }
+
+    /// Remove the listing of the token, returning it
+    pub fn remove(token_identifier: &TokenIdentifier) -> Option<Listing> {
+        with_listings_mut(|listings| listings.remove(&StorableNat::from(token_identifier.clone())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_insert_and_remove_listings() {
+        let listing = Listing {
+            seller: Principal::anonymous(),
+            price: 100_u64.into(),
+            ledger: Principal::management_canister(),
+            listed_at: 0,
+        };
+        assert!(ListingsStorage::get(&1_u64.into()).is_none());
+        ListingsStorage::insert(&1_u64.into(), listing.clone());
+        assert_eq!(ListingsStorage::get(&1_u64.into()), Some(listing.clone()));
+        assert_eq!(
+            ListingsStorage::listings(),
+            vec![(1_u64.into(), listing.clone())]
+        );
+        assert_eq!(ListingsStorage::remove(&1_u64.into()), Some(listing));
+        assert!(ListingsStorage::get(&1_u64.into()).is_none());
+        assert!(ListingsStorage::remove(&1_u64.into()).is_none());
+    }
+}
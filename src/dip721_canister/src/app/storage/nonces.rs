@@ -0,0 +1,67 @@
+use candid::Principal;
+use dip721_rs::NftError;
+
+use super::{with_signed_nonces, with_signed_nonces_mut};
+use crate::storable::StorablePrincipal;
+
+pub struct NoncesStorage;
+
+impl NoncesStorage {
+    /// Returns whether the nonce has already been used by the signer
+    pub fn is_used(signer: Principal, nonce: u64) -> bool {
+        with_signed_nonces(|nonces| nonces.contains_key(&(StorablePrincipal::from(signer), nonce)))
+    }
+
+    /// Mark the nonce of a signed request as used, failing if it has already been used.
+    ///
+    /// The expired nonces of the signer are dropped, since their requests can't be executed anymore.
+    pub fn use_nonce(signer: Principal, nonce: u64, expires_at: u64) -> Result<(), NftError> {
+        let signer = StorablePrincipal::from(signer);
+        let now = crate::utils::time();
+
+        with_signed_nonces_mut(|nonces| {
+            if nonces.contains_key(&(signer, nonce)) {
+                return Err(NftError::Other("nonce already used".to_string()));
+            }
+
+            let expired = nonces
+                .range((signer, 0)..=(signer, u64::MAX))
+                .filter(|(_, expires_at)| *expires_at < now)
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>();
+            for key in expired {
+                nonces.remove(&key);
+            }
+
+            nonces.insert((signer, nonce), expires_at);
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_should_use_nonce_once() {
+        let signer = Principal::from_slice(&[1; 29]);
+        let expires_at = crate::utils::time() + 60_000_000_000;
+        assert!(!NoncesStorage::is_used(signer, 1));
+        assert!(NoncesStorage::use_nonce(signer, 1, expires_at).is_ok());
+        assert!(NoncesStorage::is_used(signer, 1));
+        assert!(NoncesStorage::use_nonce(signer, 1, expires_at).is_err());
+        assert!(NoncesStorage::use_nonce(signer, 2, expires_at).is_ok());
+        assert!(NoncesStorage::use_nonce(Principal::anonymous(), 1, expires_at).is_ok());
+    }
+
+    #[test]
+    fn test_should_drop_expired_nonces() {
+        let signer = Principal::from_slice(&[1; 29]);
+        assert!(NoncesStorage::use_nonce(signer, 1, 0).is_ok());
+        assert!(NoncesStorage::use_nonce(signer, 2, u64::MAX).is_ok());
+        assert!(!NoncesStorage::is_used(signer, 1));
+    }
+}
@@ -0,0 +1,132 @@
+use dip721_rs::TokenIdentifier;
+
+use super::{take_next_id, with_next_offer_id_mut, with_offers, with_offers_mut};
+use crate::did::Offer;
+
+pub struct OffersStorage;
+
+impl OffersStorage {
+    /// Get an offer by id
+    pub fn get(id: u64) -> Option<Offer> {
+        with_offers(|offers| offers.get(&id))
+    }
+
+    /// Get the offers made on the token
+    pub fn token_offers(token_identifier: &TokenIdentifier) -> Vec<(u64, Offer)> {
+        with_offers(|offers| {
+            offers
+                .iter()
+                .filter(|(_, offer)| &offer.token_identifier == token_identifier)
+                .collect()
+        })
+    }
+
+    /// Insert a new offer and returns its id
+    pub fn insert(offer: Offer) -> u64 {
+        let id = Self::next_id();
+        with_offers_mut(|offers| offers.insert(id, offer));
+
+        id
+    }
+
+    /// Remove an offer, returning it
+    pub fn remove(id: u64) -> Option<Offer> {
+        with_offers_mut(|offers| offers.remove(&id))
+    }
+
+    /// Remove the offers expired at `now`, returning them
+    pub fn remove_expired(now: u64) -> Vec<Offer> {
+        with_offers_mut(|offers| {
+            let expired = offers
+                .iter()
+                .filter(|(_, offer)| offer.expires_at <= now)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            expired
+                .into_iter()
+                .filter_map(|id| offers.remove(&id))
+                .collect()
+        })
+    }
+
+    /// Make the offers on the token expire now, so that they get refunded
+    pub fn expire_token_offers(token_identifier: &TokenIdentifier) {
+        let now = crate::utils::time();
+        for (id, mut offer) in Self::token_offers(token_identifier) {
+            offer.expires_at = offer.expires_at.min(now);
+            with_offers_mut(|offers| offers.insert(id, offer));
+        }
+    }
+
+    /// Returns the expiration of the offer expiring first
+    pub fn next_expiration() -> Option<u64> {
+        with_offers(|offers| offers.iter().map(|(_, offer)| offer.expires_at).min())
+    }
+
+    /// Ids are never reused, so that an offer can't be replaced by another one under the id of a pending acceptance
+    fn next_id() -> u64 {
+        let floor = with_offers(|offers| {
+            offers
+                .last_key_value()
+                .map(|(id, _)| id + 1)
+                .unwrap_or_default()
+        });
+        with_next_offer_id_mut(|next_id| take_next_id(next_id, floor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn offer(token_identifier: u64, expires_at: u64) -> Offer {
+        Offer {
+            token_identifier: token_identifier.into(),
+            bidder: Principal::anonymous(),
+            ledger: Principal::management_canister(),
+            amount: 100_u64.into(),
+            created_at: 0,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_should_insert_and_remove_offers() {
+        assert_eq!(OffersStorage::insert(offer(1, 100)), 0);
+        assert_eq!(OffersStorage::insert(offer(1, 50)), 1);
+        assert_eq!(OffersStorage::insert(offer(2, 200)), 2);
+        assert_eq!(OffersStorage::get(1), Some(offer(1, 50)));
+        assert_eq!(OffersStorage::token_offers(&1_u64.into()).len(), 2);
+        assert_eq!(OffersStorage::next_expiration(), Some(50));
+
+        assert_eq!(OffersStorage::remove_expired(75), vec![offer(1, 50)]);
+        assert_eq!(OffersStorage::remove(0), Some(offer(1, 100)));
+        assert!(OffersStorage::token_offers(&1_u64.into()).is_empty());
+    }
+
+    #[test]
+    fn test_should_not_reuse_ids_of_removed_offers() {
+        assert_eq!(OffersStorage::insert(offer(1, 100)), 0);
+        assert_eq!(OffersStorage::insert(offer(1, 100)), 1);
+        assert_eq!(OffersStorage::remove(1), Some(offer(1, 100)));
+
+        assert_eq!(OffersStorage::insert(offer(1, 50)), 2);
+        assert!(OffersStorage::get(1).is_none());
+    }
+
+    #[test]
+    fn test_should_expire_token_offers() {
+        OffersStorage::insert(offer(1, u64::MAX));
+        OffersStorage::insert(offer(2, u64::MAX));
+        OffersStorage::expire_token_offers(&1_u64.into());
+
+        let expired = OffersStorage::remove_expired(crate::utils::time());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].token_identifier, 1_u64);
+        assert!(OffersStorage::get(1).is_some());
+    }
+}
@@ -0,0 +1,64 @@
+use candid::{Nat, Principal};
+
+use super::{with_proceeds, with_proceeds_mut};
+use crate::storable::{StorableNat, StorablePrincipal};
+
+/// Amounts the canister owes to the recipients of sales whose payout failed
+pub struct ProceedsStorage;
+
+impl ProceedsStorage {
+    /// Get the amount owed to the recipient on the ledger
+    pub fn get(ledger: Principal, recipient: Principal) -> Nat {
+        with_proceeds(|proceeds| {
+            proceeds
+                .get(&(ledger.into(), recipient.into()))
+                .map(|amount| amount.0)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Add `amount` to the amount owed to the recipient on the ledger
+    pub fn credit(ledger: Principal, recipient: Principal, amount: Nat) {
+        let key = (
+            StorablePrincipal::from(ledger),
+            StorablePrincipal::from(recipient),
+        );
+        with_proceeds_mut(|proceeds| {
+            let balance = proceeds
+                .get(&key)
+                .map(|amount| amount.0)
+                .unwrap_or_default();
+            proceeds.insert(key, StorableNat::from(balance + amount));
+        });
+    }
+
+    /// Take the whole amount owed to the recipient on the ledger
+    pub fn take(ledger: Principal, recipient: Principal) -> Nat {
+        with_proceeds_mut(|proceeds| {
+            proceeds
+                .remove(&(ledger.into(), recipient.into()))
+                .map(|amount| amount.0)
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_credit_and_take_proceeds() {
+        let ledger = Principal::management_canister();
+        let recipient = Principal::anonymous();
+        assert_eq!(ProceedsStorage::get(ledger, recipient), 0_u64);
+        ProceedsStorage::credit(ledger, recipient, 100_u64.into());
+        ProceedsStorage::credit(ledger, recipient, 50_u64.into());
+        assert_eq!(ProceedsStorage::get(ledger, recipient), 150_u64);
+        assert_eq!(ProceedsStorage::take(ledger, recipient), 150_u64);
+        assert_eq!(ProceedsStorage::get(ledger, recipient), 0_u64);
+    }
+}
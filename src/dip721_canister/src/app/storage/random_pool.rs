@@ -0,0 +1,156 @@
+use dip721_rs::NftError;
+
+use super::{
+    with_random_pool, with_random_pool_mut, with_random_pool_positions,
+    with_random_pool_positions_mut, with_random_pool_swaps, with_random_pool_swaps_mut,
+};
+use crate::did::RandomPool;
+
+/// Pool of the token identifiers `1..=size` minted in random order.
+///
+/// The pool is a sparse Fisher-Yates shuffle: the remaining values are the positions `0..remaining`,
+/// each holding its own value unless it was swapped, so that picking a value is `O(log n)`.
+/// The positions holding a swapped value are also indexed by value, to tell whether a value is left in the pool.
+pub struct RandomPoolStorage;
+
+impl RandomPoolStorage {
+    /// Get the pool
+    pub fn get() -> Option<RandomPool> {
+        with_random_pool(|pool| pool.get().clone())
+    }
+
+    /// Create a pool of `size` identifiers, replacing the previous one if no identifier was picked from it
+    pub fn init(size: u64) -> Result<(), NftError> {
+        if Self::get().is_some_and(|pool| pool.remaining != pool.size) {
+            return Err(NftError::Other(
+                "identifiers already picked from the pool".to_string(),
+            ));
+        }
+        Self::set(RandomPool {
+            size,
+            remaining: size,
+        });
+
+        Ok(())
+    }
+
+    /// Take the value at `position` out of the pool, returning its token identifier
+    pub fn take(position: u64) -> Result<u64, NftError> {
+        let mut pool = Self::get()
+            .filter(|pool| position < pool.remaining)
+            .ok_or_else(|| NftError::Other("no identifiers left in the pool".to_string()))?;
+        pool.remaining -= 1;
+        let last = pool.remaining;
+
+        let (value, last_value) = with_random_pool_swaps_mut(|swaps| {
+            let value = swaps.remove(&position).unwrap_or(position);
+            let last_value = swaps.remove(&last).unwrap_or(last);
+            if position != last {
+                swaps.insert(position, last_value);
+            }
+            (value, last_value)
+        });
+        with_random_pool_positions_mut(|positions| {
+            positions.remove(&value);
+            positions.remove(&last_value);
+            if position != last {
+                positions.insert(last_value, position);
+            }
+        });
+        Self::set(pool);
+
+        Ok(value + 1)
+    }
+
+    /// Put back an identifier previously taken from the pool
+    pub fn put_back(token_identifier: u64) {
+        let Some(mut pool) = Self::get() else {
+            return;
+        };
+        let value = token_identifier - 1;
+        if value != pool.remaining {
+            with_random_pool_swaps_mut(|swaps| swaps.insert(pool.remaining, value));
+            with_random_pool_positions_mut(|positions| positions.insert(value, pool.remaining));
+        }
+        pool.remaining += 1;
+        Self::set(pool);
+    }
+
+    /// Returns whether the token identifier is left in the pool, to be picked by a mint
+    pub fn contains(token_identifier: u64) -> bool {
+        let Some(pool) = Self::get() else {
+            return false;
+        };
+        let Some(value) = token_identifier.checked_sub(1) else {
+            return false;
+        };
+        if with_random_pool_positions(|positions| positions.contains_key(&value)) {
+            return true;
+        }
+        // a value no longer at its own position was taken or swapped elsewhere
+        value < pool.remaining && !with_random_pool_swaps(|swaps| swaps.contains_key(&value))
+    }
+
+    fn set(pool: RandomPool) {
+        with_random_pool_mut(|cell| cell.set(Some(pool))).expect("failed to set random pool");
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_take_every_identifier_once() {
+        assert!(RandomPoolStorage::init(5).is_ok());
+        let mut taken = vec![
+            RandomPoolStorage::take(1).unwrap(),
+            RandomPoolStorage::take(3).unwrap(),
+            RandomPoolStorage::take(0).unwrap(),
+        ];
+        assert_eq!(taken, vec![2, 4, 1]);
+        assert!(RandomPoolStorage::init(10).is_err());
+        assert!(RandomPoolStorage::take(2).is_err());
+
+        RandomPoolStorage::put_back(4);
+        while RandomPoolStorage::get().unwrap().remaining > 0 {
+            taken.push(RandomPoolStorage::take(0).unwrap());
+        }
+        taken.sort();
+        assert_eq!(taken, vec![1, 2, 3, 4, 4, 5]);
+    }
+
+    #[test]
+    fn test_should_tell_identifiers_left_in_pool() {
+        assert!(!RandomPoolStorage::contains(1));
+        assert!(RandomPoolStorage::init(5).is_ok());
+        assert_eq!(RandomPoolStorage::take(1).unwrap(), 2);
+        assert_eq!(RandomPoolStorage::take(3).unwrap(), 4);
+        // 5 was swapped to the position of 2
+        for (token_identifier, left) in [
+            (0, false),
+            (1, true),
+            (2, false),
+            (3, true),
+            (4, false),
+            (5, true),
+            (6, false),
+        ] {
+            assert_eq!(
+                RandomPoolStorage::contains(token_identifier),
+                left,
+                "{token_identifier}"
+            );
+        }
+
+        RandomPoolStorage::put_back(2);
+        assert!(RandomPoolStorage::contains(2));
+        while RandomPoolStorage::get().unwrap().remaining > 0 {
+            RandomPoolStorage::take(0).unwrap();
+        }
+        assert!((1..=5).all(|token_identifier| !RandomPoolStorage::contains(token_identifier)));
+    }
+}
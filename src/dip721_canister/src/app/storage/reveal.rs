@@ -0,0 +1,68 @@
+use dip721_rs::GenericValue;
+
+use super::{with_reveal_metadata, with_reveal_metadata_mut};
+use crate::storable::StorableProperties;
+
+/// Maximum amount of metadata given to the tokens by a single message, so that it fits in the instructions limit
+const REVEAL_BATCH_SIZE: usize = 500;
+
+/// Revealed metadata waiting to be given to the tokens by the global timer, a batch at a time
+pub struct RevealStorage;
+
+impl RevealStorage {
+    /// Store the revealed metadata, in the committed order, to be given to the tokens
+    pub fn insert(metadata: Vec<Vec<(String, GenericValue)>>) {
+        with_reveal_metadata_mut(|pending| {
+            for (index, properties) in metadata.into_iter().enumerate() {
+                pending.insert(index as u64, StorableProperties(properties));
+            }
+        });
+    }
+
+    /// Returns whether revealed metadata is left to give to the tokens
+    pub fn is_pending() -> bool {
+        !with_reveal_metadata(|pending| pending.is_empty())
+    }
+
+    /// Take a batch of the revealed metadata, with its index in the committed order.
+    /// Taken metadata is removed, so the reveal resumes where it stopped.
+    pub fn take_batch() -> Vec<(u64, Vec<(String, GenericValue)>)> {
+        with_reveal_metadata_mut(|pending| {
+            std::iter::from_fn(|| pending.pop_first())
+                .take(REVEAL_BATCH_SIZE)
+                .map(|(index, properties)| (index, properties.0))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_take_metadata_in_batches() {
+        assert!(!RevealStorage::is_pending());
+        let metadata = (0..REVEAL_BATCH_SIZE as u64 + 1)
+            .map(|index| vec![("index".to_string(), GenericValue::Nat64Content(index))])
+            .collect::<Vec<_>>();
+        RevealStorage::insert(metadata.clone());
+        assert!(RevealStorage::is_pending());
+
+        let batch = RevealStorage::take_batch();
+        assert_eq!(batch.len(), REVEAL_BATCH_SIZE);
+        assert_eq!(batch[0], (0, metadata[0].clone()));
+        assert!(RevealStorage::is_pending());
+        assert_eq!(
+            RevealStorage::take_batch(),
+            vec![(
+                REVEAL_BATCH_SIZE as u64,
+                metadata[REVEAL_BATCH_SIZE].clone()
+            )]
+        );
+        assert!(!RevealStorage::is_pending());
+    }
+}
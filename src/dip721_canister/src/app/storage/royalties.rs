@@ -0,0 +1,52 @@
+use dip721_rs::TokenIdentifier;
+
+use super::{with_token_royalties, with_token_royalties_mut};
+use crate::did::Royalty;
+use crate::storable::StorableNat;
+
+pub struct RoyaltiesStorage;
+
+impl RoyaltiesStorage {
+    /// Get the royalty set on the token, if any
+    pub fn get(token_identifier: &TokenIdentifier) -> Option<Royalty> {
+        with_token_royalties(|royalties| {
+            royalties.get(&StorableNat::from(token_identifier.clone()))
+        })
+    }
+
+    /// Set the royalty of the token; `None` makes the token fall back to the collection royalty
+    pub fn set(token_identifier: &TokenIdentifier, royalty: Option<Royalty>) {
+        let key = StorableNat::from(token_identifier.clone());
+        with_token_royalties_mut(|royalties| match royalty {
+            Some(royalty) => royalties.insert(key, royalty),
+            None => royalties.remove(&key),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::did::RoyaltyRecipient;
+
+    #[test]
+    fn test_should_set_and_unset_token_royalty() {
+        let royalty = Royalty {
+            basis_points: 250,
+            recipients: vec![RoyaltyRecipient {
+                recipient: Principal::anonymous(),
+                share: 10_000,
+            }],
+        };
+        assert!(RoyaltiesStorage::get(&1_u64.into()).is_none());
+        RoyaltiesStorage::set(&1_u64.into(), Some(royalty.clone()));
+        assert_eq!(RoyaltiesStorage::get(&1_u64.into()), Some(royalty));
+        assert!(RoyaltiesStorage::get(&2_u64.into()).is_none());
+        RoyaltiesStorage::set(&1_u64.into(), None);
+        assert!(RoyaltiesStorage::get(&1_u64.into()).is_none());
+    }
+}
@@ -0,0 +1,93 @@
+use candid::Principal;
+use dip721_rs::NftError;
+
+use super::{
+    with_sale_minted, with_sale_minted_mut, with_sale_wallet_mints, with_sale_wallet_mints_mut,
+};
+use crate::did::MintSale;
+
+/// Counters of the tokens minted by the public sale
+pub struct SaleStorage;
+
+impl SaleStorage {
+    /// Get the amount of tokens minted by the sale
+    pub fn minted() -> u64 {
+        with_sale_minted(|minted| *minted.get())
+    }
+
+    /// Get the amount of tokens minted by the wallet in the sale
+    pub fn minted_by(wallet: Principal) -> u64 {
+        with_sale_wallet_mints(|mints| mints.get(&wallet.into()).unwrap_or_default())
+    }
+
+    /// Reserve `quantity` tokens of the sale for the wallet, checking the sale allocation and wallet limit
+    pub fn reserve(sale: &MintSale, wallet: Principal, quantity: u64) -> Result<(), NftError> {
+        let minted = Self::minted();
+        let minted_by = Self::minted_by(wallet);
+        if minted.saturating_add(quantity) > sale.allocation {
+            return Err(NftError::Other("sale allocation exceeded".to_string()));
+        }
+        if minted_by.saturating_add(quantity) > sale.wallet_limit {
+            return Err(NftError::Other("wallet limit exceeded".to_string()));
+        }
+        Self::set(wallet, minted + quantity, minted_by + quantity);
+
+        Ok(())
+    }
+
+    /// Release `quantity` tokens previously reserved for the wallet
+    pub fn release(wallet: Principal, quantity: u64) {
+        Self::set(
+            wallet,
+            Self::minted().saturating_sub(quantity),
+            Self::minted_by(wallet).saturating_sub(quantity),
+        );
+    }
+
+    fn set(wallet: Principal, minted: u64, minted_by: u64) {
+        with_sale_minted_mut(|cell| cell.set(minted)).expect("failed to set sale minted");
+        with_sale_wallet_mints_mut(|mints| {
+            if minted_by == 0 {
+                mints.remove(&wallet.into());
+            } else {
+                mints.insert(wallet.into(), minted_by);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn sale() -> MintSale {
+        MintSale {
+            price: 100_u64.into(),
+            ledger: Principal::management_canister(),
+            treasury: Principal::management_canister(),
+            wallet_limit: 3,
+            allocation: 4,
+            starts_at: 0,
+            ends_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_should_reserve_and_release_sale_mints() {
+        let alice = Principal::anonymous();
+        let bob = Principal::from_slice(&[1; 29]);
+        assert!(SaleStorage::reserve(&sale(), alice, 2).is_ok());
+        assert!(SaleStorage::reserve(&sale(), alice, 2).is_err());
+        assert!(SaleStorage::reserve(&sale(), bob, 3).is_err());
+        assert!(SaleStorage::reserve(&sale(), bob, 2).is_ok());
+        assert_eq!(SaleStorage::minted(), 4);
+        assert_eq!(SaleStorage::minted_by(alice), 2);
+
+        SaleStorage::release(bob, 2);
+        assert_eq!(SaleStorage::minted(), 2);
+        assert_eq!(SaleStorage::minted_by(bob), 0);
+    }
+}
@@ -0,0 +1,149 @@
+use candid::Principal;
+use dip721_rs::TokenIdentifier;
+
+use super::{
+    take_next_id, with_next_swap_id_mut, with_swap_locks, with_swap_locks_mut, with_swaps,
+    with_swaps_mut,
+};
+use crate::did::Swap;
+use crate::storable::StorableNat;
+
+/// Swap proposals and the tokens they lock
+pub struct SwapsStorage;
+
+impl SwapsStorage {
+    /// Get a swap by id
+    pub fn get(id: u64) -> Option<Swap> {
+        with_swaps(|swaps| swaps.get(&id))
+    }
+
+    /// Get the swaps proposed by or to the principal
+    pub fn swaps_of(principal: Principal) -> Vec<(u64, Swap)> {
+        with_swaps(|swaps| {
+            swaps
+                .iter()
+                .filter(|(_, swap)| swap.proposer == principal || swap.counterparty == principal)
+                .collect()
+        })
+    }
+
+    /// Insert a new swap, locking its offered tokens, and returns its id
+    pub fn insert(swap: Swap) -> u64 {
+        let id = Self::next_id();
+        Self::lock(id, &swap.offered);
+        with_swaps_mut(|swaps| swaps.insert(id, swap));
+
+        id
+    }
+
+    /// Remove a swap, unlocking its offered tokens, and returns it
+    pub fn remove(id: u64) -> Option<Swap> {
+        let swap = with_swaps_mut(|swaps| swaps.remove(&id))?;
+        Self::unlock(&swap.offered);
+
+        Some(swap)
+    }
+
+    /// Remove the swaps expired at `now`, unlocking their offered tokens, and returns them
+    pub fn remove_expired(now: u64) -> Vec<Swap> {
+        let expired = with_swaps(|swaps| {
+            swaps
+                .iter()
+                .filter(|(_, swap)| swap.expires_at <= now)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        });
+        expired.into_iter().filter_map(Self::remove).collect()
+    }
+
+    /// Returns the expiration of the swap expiring first
+    pub fn next_expiration() -> Option<u64> {
+        with_swaps(|swaps| swaps.iter().map(|(_, swap)| swap.expires_at).min())
+    }
+
+    /// Lock the tokens for the swap
+    pub fn lock(id: u64, tokens: &[TokenIdentifier]) {
+        with_swap_locks_mut(|locks| {
+            for token_identifier in tokens {
+                locks.insert(StorableNat::from(token_identifier.clone()), id);
+            }
+        });
+    }
+
+    /// Unlock the tokens
+    pub fn unlock(tokens: &[TokenIdentifier]) {
+        with_swap_locks_mut(|locks| {
+            for token_identifier in tokens {
+                locks.remove(&StorableNat::from(token_identifier.clone()));
+            }
+        });
+    }
+
+    /// Returns whether the token is locked by a swap
+    pub fn is_locked(token_identifier: &TokenIdentifier) -> bool {
+        Self::locked_by(token_identifier).is_some()
+    }
+
+    /// Returns the id of the swap locking the token, if any
+    pub fn locked_by(token_identifier: &TokenIdentifier) -> Option<u64> {
+        with_swap_locks(|locks| locks.get(&StorableNat::from(token_identifier.clone())))
+    }
+
+    /// Ids are never reused, so that a swap can't be replaced by another one under the id of a pending acceptance
+    fn next_id() -> u64 {
+        let floor = with_swaps(|swaps| {
+            swaps
+                .last_key_value()
+                .map(|(id, _)| id + 1)
+                .unwrap_or_default()
+        });
+        with_next_swap_id_mut(|next_id| take_next_id(next_id, floor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob};
+
+    fn swap(offered: u64, requested: u64, expires_at: u64) -> Swap {
+        Swap {
+            proposer: alice(),
+            counterparty: bob(),
+            offered: vec![offered.into()],
+            requested: vec![requested.into()],
+            payment: None,
+            created_at: 0,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_should_insert_and_remove_swaps() {
+        assert_eq!(SwapsStorage::insert(swap(1, 2, 100)), 0);
+        assert_eq!(SwapsStorage::insert(swap(3, 4, 50)), 1);
+        assert!(SwapsStorage::is_locked(&1_u64.into()));
+        assert!(!SwapsStorage::is_locked(&2_u64.into()));
+        assert_eq!(SwapsStorage::locked_by(&3_u64.into()), Some(1));
+        assert_eq!(SwapsStorage::swaps_of(bob()).len(), 2);
+        assert_eq!(SwapsStorage::next_expiration(), Some(50));
+
+        assert_eq!(SwapsStorage::remove_expired(75), vec![swap(3, 4, 50)]);
+        assert!(!SwapsStorage::is_locked(&3_u64.into()));
+        assert_eq!(SwapsStorage::remove(0), Some(swap(1, 2, 100)));
+        assert!(!SwapsStorage::is_locked(&1_u64.into()));
+        assert!(SwapsStorage::get(0).is_none());
+    }
+
+    #[test]
+    fn test_should_not_reuse_ids_of_removed_swaps() {
+        assert_eq!(SwapsStorage::insert(swap(1, 2, 100)), 0);
+        assert_eq!(SwapsStorage::remove(0), Some(swap(1, 2, 100)));
+
+        assert_eq!(SwapsStorage::insert(swap(3, 2, 100)), 1);
+        assert!(SwapsStorage::get(0).is_none());
+    }
+}
@@ -1,22 +1,92 @@
+use std::ops::Range;
+
 use candid::{Nat, Principal};
 use dip721_rs::{GenericValue, NftError, TokenIdentifier, TokenMetadata};
 use itertools::Itertools as _;
 
-use super::{with_token, with_token_mut, with_tokens, with_tokens_mut, TxHistory};
+use super::{
+    count_burned_token, count_minted_token, migrate_legacy_token, with_account_tokens,
+    with_account_tokens_mut, with_burned_tokens, with_legacy_tokens, with_owner_index_cursor,
+    with_owner_index_cursor_mut, with_token, with_token_mut, with_token_subaccounts,
+    with_token_subaccounts_mut, with_token_supply, with_tokens, with_tokens_mut, AccountKey,
+    AuctionsStorage, ChildrenStorage, ListingsStorage, OffersStorage, SwapsStorage, TokenKey,
+    TxHistory, UsersStorage,
+};
+use crate::did::{Account, CollectionId, Subaccount, DEFAULT_COLLECTION_ID};
+use crate::storable::StorableNat;
+
+/// Maximum amount of tokens migrated by a single message, so that it fits in the instructions limit
+const MIGRATION_BATCH_SIZE: usize = 500;
 
+/// Tokens of the collections, keyed by collection id and token identifier.
+/// Tokens are held by ICRC-1 accounts: the owner of the token metadata is the owner of the account,
+/// and tokens held by other subaccounts than the default one have their subaccount stored apart.
+/// Listings, auctions, offers, swaps, users, editions and children of the tokens only exist in the default collection.
 pub struct TokensStorage;
 
 impl TokensStorage {
     /// Get token metadata
-    pub fn get_token(id: &TokenIdentifier) -> Result<TokenMetadata, NftError> {
-        with_token(id, |token| Ok(token.clone()))
+    pub fn get_token(
+        collection_id: CollectionId,
+        id: &TokenIdentifier,
+    ) -> Result<TokenMetadata, NftError> {
+        with_token(collection_id, id, |token| Ok(token.clone()))
+    }
+
+    /// Get the account holding the token; `None` if burned
+    pub fn account_of(
+        collection_id: CollectionId,
+        id: &TokenIdentifier,
+    ) -> Result<Option<Account>, NftError> {
+        let owner = Self::get_token(collection_id, id)?.owner;
+        let subaccount = Self::subaccount_of(&Self::token_key(collection_id, id));
+
+        Ok(owner.map(|owner| Account::new(owner, subaccount)))
+    }
+
+    /// Get the accounts holding the tokens of the collection, burned tokens excluded
+    pub fn accounts(collection_id: CollectionId) -> Vec<(TokenIdentifier, Account)> {
+        with_tokens(|tokens| {
+            tokens
+                .range(Self::collection_range(collection_id))
+                .filter(|(_, token)| !token.is_burned)
+                .filter_map(|(key, token)| {
+                    let subaccount = Self::subaccount_of(&key);
+                    token
+                        .owner
+                        .map(|owner| (key.1 .0, Account::new(owner, subaccount)))
+                })
+                .collect()
+        })
+    }
+
+    /// Returns whether the token is held by the subaccount of the owner; `None` being the default subaccount.
+    ///
+    /// Tokens not indexed yet by the migrations are held by the default subaccount of their owner.
+    pub fn is_held_by(
+        collection_id: CollectionId,
+        id: &TokenIdentifier,
+        owner: Principal,
+        subaccount: Option<Subaccount>,
+    ) -> bool {
+        let key = Self::token_key(collection_id, id);
+        if with_account_tokens(|account_tokens| {
+            account_tokens.contains_key(&(Self::account_key(owner, subaccount), key.clone()))
+        }) {
+            return true;
+        }
+
+        subaccount.unwrap_or_default() == Subaccount::default()
+            && !Self::is_indexed(&key)
+            && Self::get_token(collection_id, id)
+                .is_ok_and(|token| !token.is_burned && token.owner == Some(owner))
     }
 
     /// Get the amount of unique holders of tokens
-    pub fn total_unique_holders() -> u64 {
+    pub fn total_unique_holders(collection_id: CollectionId) -> u64 {
         with_tokens(|tokens| {
             tokens
-                .iter()
+                .range(Self::collection_range(collection_id))
                 .filter_map(|(_, token)| token.owner)
                 .unique()
                 .count()
@@ -25,11 +95,12 @@ impl TokensStorage {
 
     /// Set a property on a token
     pub fn set_token_property(
+        collection_id: CollectionId,
         token_id: &TokenIdentifier,
         key: String,
         value: GenericValue,
     ) -> Result<(), NftError> {
-        with_token_mut(token_id, |token| {
+        with_token_mut(collection_id, token_id, |token| {
             for (k, v) in &mut token.properties {
                 if k == &key {
                     *v = value;
@@ -42,28 +113,74 @@ impl TokensStorage {
         })
     }
 
-    /// Get tokens owned by a certain principal
-    pub fn tokens_by_owner(owner: Principal) -> Vec<TokenIdentifier> {
+    /// Get the properties of every token of the collection, ordered by token identifier
+    pub fn tokens_properties(
+        collection_id: CollectionId,
+    ) -> Vec<(TokenIdentifier, Vec<(String, GenericValue)>)> {
         with_tokens(|tokens| {
             tokens
-                .iter()
-                .filter_map(|(id, token)| {
-                    if token.owner == Some(owner) && !token.is_burned {
-                        Some(id.0.clone())
-                    } else {
-                        None
-                    }
-                })
+                .range(Self::collection_range(collection_id))
+                .map(|(_, token)| (token.token_identifier, token.properties))
                 .collect()
         })
     }
 
+    /// Replace the properties of the token
+    pub fn replace_properties(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<(), NftError> {
+        with_token_mut(collection_id, token_id, |token| {
+            token.properties = properties;
+
+            Ok(())
+        })
+    }
+
+    /// Get tokens held by the default subaccount of a certain principal
+    pub fn tokens_by_owner(collection_id: CollectionId, owner: Principal) -> Vec<TokenIdentifier> {
+        Self::tokens_by_account(collection_id, owner, None)
+    }
+
+    /// Get tokens held by the subaccount of a certain principal; `None` being the default subaccount
+    pub fn tokens_by_account(
+        collection_id: CollectionId,
+        owner: Principal,
+        subaccount: Option<Subaccount>,
+    ) -> Vec<TokenIdentifier> {
+        let account = Self::account_key(owner, subaccount);
+        let range = Self::collection_range(collection_id);
+        let mut tokens = with_account_tokens(|account_tokens| {
+            account_tokens
+                .range((account, range.start)..(account, range.end))
+                .map(|((_, (_, id)), _)| id.0)
+                .collect::<Vec<_>>()
+        });
+        // tokens not indexed yet by the migrations are held by the default subaccount of their owner
+        if subaccount.unwrap_or_default() == Subaccount::default() {
+            tokens.extend(
+                Self::unindexed_tokens(collection_id)
+                    .into_iter()
+                    .filter(|token| !token.is_burned && token.owner == Some(owner))
+                    .map(|token| token.token_identifier),
+            );
+            tokens.sort();
+            tokens.dedup();
+        }
+
+        tokens
+    }
+
     /// Get tokens with operator set to a certain principal
-    pub fn tokens_by_operator(operator: Principal) -> Vec<TokenIdentifier> {
+    pub fn tokens_by_operator(
+        collection_id: CollectionId,
+        operator: Principal,
+    ) -> Vec<TokenIdentifier> {
         with_tokens(|tokens| {
             tokens
-                .iter()
-                .filter_map(|(id, token)| {
+                .range(Self::collection_range(collection_id))
+                .filter_map(|((_, id), token)| {
                     if token.operator == Some(operator) {
                         Some(id.0.clone())
                     } else {
@@ -75,52 +192,173 @@ impl TokensStorage {
     }
 
     /// Returns the total supply of tokens
-    pub fn total_supply() -> u64 {
-        with_tokens(|tokens| tokens.len())
+    pub fn total_supply(collection_id: CollectionId) -> u64 {
+        with_token_supply(|supply| supply.get(&collection_id).unwrap_or_default())
+    }
+
+    /// Returns the amount of burned tokens
+    pub fn total_burned(collection_id: CollectionId) -> u64 {
+        with_burned_tokens(|burned| burned.get(&collection_id).unwrap_or_default())
+    }
+
+    /// Returns the identifier following the greatest token identifier, starting from 1
+    pub fn next_token_id(collection_id: CollectionId) -> TokenIdentifier {
+        with_tokens(|tokens| {
+            tokens
+                .iter_upper_bound(&Self::collection_range(collection_id).end)
+                .next()
+                .filter(|((collection, _), _)| *collection == collection_id)
+                .map(|((_, id), _)| id.0 + 1_u64)
+                .unwrap_or_else(|| 1_u64.into())
+        })
+    }
+
+    /// Transfer token to the default subaccount of the provided principal, along with the tokens attached to it
+    pub fn transfer(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+        to: Principal,
+    ) -> Result<Nat, NftError> {
+        Self::transfer_to_account(collection_id, token_id, to, None)
     }
 
-    /// Transfer token to the provided principal
-    pub fn transfer(token_id: &TokenIdentifier, to: Principal) -> Result<Nat, NftError> {
-        with_token_mut(token_id, |token| {
+    /// Transfer token to the subaccount of the provided principal, along with the tokens attached to it;
+    /// `None` being the default subaccount
+    pub fn transfer_to_account(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+        to: Principal,
+        to_subaccount: Option<Subaccount>,
+    ) -> Result<Nat, NftError> {
+        Self::check_transferable(collection_id, token_id)?;
+        let tx_id = Self::transfer_token(collection_id, token_id, to, to_subaccount)?;
+        if collection_id == DEFAULT_COLLECTION_ID {
+            for child in ChildrenStorage::descendants_of(token_id) {
+                Self::transfer_token(collection_id, &child, to, to_subaccount)?;
+            }
+        }
+
+        Ok(tx_id)
+    }
+
+    fn transfer_token(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+        to: Principal,
+        to_subaccount: Option<Subaccount>,
+    ) -> Result<Nat, NftError> {
+        let (from, tx_id) = with_token_mut(collection_id, token_id, |token| {
             // check if burned
             if token.is_burned {
                 return Err(NftError::ExistedNFT);
             }
+            let from = token.owner;
             token.owner = Some(to);
             token.transferred_at = Some(crate::utils::time());
             token.transferred_by = Some(crate::utils::caller());
             token.operator = None;
+            // the listing was made by the previous owner, as was the user granted
+            if collection_id == DEFAULT_COLLECTION_ID {
+                ListingsStorage::remove(token_id);
+                UsersStorage::remove(token_id);
+            }
 
             // register transfer
             let tx_id = TxHistory::register_transfer(token);
 
-            Ok(tx_id)
-        })
+            Ok((from, tx_id))
+        })?;
+        Self::move_token(
+            &Self::token_key(collection_id, token_id),
+            from,
+            Some((to, to_subaccount)),
+        );
+
+        Ok(tx_id)
     }
 
     /// Burn token
-    pub fn burn(token_id: &TokenIdentifier) -> Result<Nat, NftError> {
-        with_token_mut(token_id, |token| {
+    pub fn burn(collection_id: CollectionId, token_id: &TokenIdentifier) -> Result<Nat, NftError> {
+        Self::check_transferable(collection_id, token_id)?;
+        if collection_id == DEFAULT_COLLECTION_ID
+            && !ChildrenStorage::children_of(token_id).is_empty()
+        {
+            return Err(NftError::Other("token has children attached".to_string()));
+        }
+        let (from, tx_id) = with_token_mut(collection_id, token_id, |token| {
             // check if burned
             if token.is_burned {
                 return Err(NftError::ExistedNFT);
             }
+            let from = token.owner;
             token.is_burned = true;
             token.owner = None;
             token.operator = None;
             token.burned_at = Some(crate::utils::time());
             token.burned_by = Some(crate::utils::caller());
+            if collection_id == DEFAULT_COLLECTION_ID {
+                ListingsStorage::remove(token_id);
+                UsersStorage::remove(token_id);
+                OffersStorage::expire_token_offers(token_id);
+            }
 
             // register burn
             let tx_id = TxHistory::register_token_burn(token);
 
-            Ok(tx_id)
-        })
+            Ok((from, tx_id))
+        })?;
+        Self::move_token(&Self::token_key(collection_id, token_id), from, None);
+        count_burned_token(collection_id);
+
+        Ok(tx_id)
+    }
+
+    /// Check the token is neither under auction, attached to a parent nor locked by a swap
+    pub fn check_transferable(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+    ) -> Result<(), NftError> {
+        Self::check_not_auctioned(collection_id, token_id)?;
+        Self::check_not_attached(collection_id, token_id)?;
+        if collection_id == DEFAULT_COLLECTION_ID && SwapsStorage::is_locked(token_id) {
+            Err(NftError::Other("token is locked by a swap".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Tokens under auction are locked until the auction is settled or cancelled
+    fn check_not_auctioned(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+    ) -> Result<(), NftError> {
+        if collection_id == DEFAULT_COLLECTION_ID && AuctionsStorage::contains(token_id) {
+            Err(NftError::Other("token is under auction".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Tokens attached to a parent are only transferred along with it, until they're detached
+    fn check_not_attached(
+        collection_id: CollectionId,
+        token_id: &TokenIdentifier,
+    ) -> Result<(), NftError> {
+        if collection_id == DEFAULT_COLLECTION_ID && ChildrenStorage::parent_of(token_id).is_some()
+        {
+            Err(NftError::Other("token is attached to a parent".to_string()))
+        } else {
+            Ok(())
+        }
     }
 
     /// Approve operator for token
-    pub fn approve(operator: Principal, token_id: &TokenIdentifier) -> Result<Nat, NftError> {
-        with_token_mut(token_id, |token| {
+    pub fn approve(
+        collection_id: CollectionId,
+        operator: Principal,
+        token_id: &TokenIdentifier,
+    ) -> Result<Nat, NftError> {
+        with_token_mut(collection_id, token_id, |token| {
             token.approved_at = Some(crate::utils::time());
             token.approved_by = Some(crate::utils::caller());
             token.operator = Some(operator);
@@ -133,10 +371,11 @@ impl TokensStorage {
 
     /// Remove approval for operator
     pub fn revoke_approval(
+        collection_id: CollectionId,
         operator: Principal,
         token_id: &TokenIdentifier,
     ) -> Result<Nat, NftError> {
-        with_token_mut(token_id, |token| {
+        with_token_mut(collection_id, token_id, |token| {
             if token.operator == Some(operator) {
                 token.approved_at = None;
                 token.approved_by = None;
@@ -148,14 +387,27 @@ impl TokensStorage {
         })
     }
 
-    /// Mint a new token
+    /// Mint a new token to the default subaccount of the provided principal
+    #[cfg(test)]
     pub fn mint(
+        collection_id: CollectionId,
+        to: Principal,
+        token_identifier: TokenIdentifier,
+        properties: Vec<(String, GenericValue)>,
+    ) -> Result<Nat, NftError> {
+        Self::mint_to_account(collection_id, to, None, token_identifier, properties)
+    }
+
+    /// Mint a new token to the subaccount of the provided principal; `None` being the default subaccount
+    pub fn mint_to_account(
+        collection_id: CollectionId,
         to: Principal,
+        to_subaccount: Option<Subaccount>,
         token_identifier: TokenIdentifier,
         properties: Vec<(String, GenericValue)>,
     ) -> Result<Nat, NftError> {
         // check if token already exists
-        if TokensStorage::get_token(&token_identifier).is_ok() {
+        if TokensStorage::get_token(collection_id, &token_identifier).is_ok() {
             return Err(NftError::ExistedNFT);
         }
 
@@ -178,32 +430,388 @@ impl TokensStorage {
         // register mint
         let tx_id = TxHistory::register_token_mint(&token);
 
-        with_tokens_mut(|tokens| tokens.insert(token_identifier.into(), token));
+        let key = Self::token_key(collection_id, &token_identifier);
+        count_minted_token(collection_id);
+        with_tokens_mut(|tokens| tokens.insert(key.clone(), token));
+        Self::move_token(&key, None, Some((to, to_subaccount)));
 
         Ok(tx_id)
     }
+
+    /// Returns whether tokens stored by previous versions, keyed by token identifier only, are left to migrate.
+    ///
+    /// Until then, they're read from where previous versions stored them and count in the balances of their owners,
+    /// but they don't count in the supply and the other listings of tokens.
+    pub fn has_legacy_tokens() -> bool {
+        !with_legacy_tokens(|legacy_tokens| legacy_tokens.is_empty())
+    }
+
+    /// Move a batch of the tokens stored by previous versions, keyed by token identifier only, to the default
+    /// collection. Migrated tokens are removed from the legacy tokens, so the migration resumes where it stopped.
+    pub fn migrate_legacy_tokens() {
+        let ids = with_legacy_tokens(|legacy_tokens| {
+            legacy_tokens
+                .iter()
+                .take(MIGRATION_BATCH_SIZE)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        });
+        for id in ids {
+            migrate_legacy_token(id);
+        }
+    }
+
+    /// Start building the owner index of the tokens stored by previous versions, if they were never indexed.
+    /// The index is built a batch at a time by [`Self::migrate_owner_index`]; until then, tokens left to index
+    /// are looked up apart, as held by the default subaccount of their owners.
+    pub fn start_owner_index_migration() {
+        if with_owner_index_cursor(|cursor| cursor.get().is_some())
+            || !with_account_tokens(|account_tokens| account_tokens.is_empty())
+        {
+            return;
+        }
+        let Some(first) = with_tokens(|tokens| tokens.first_key_value()).map(|(key, _)| key) else {
+            return;
+        };
+        with_owner_index_cursor_mut(|cursor| cursor.set(Some(first)))
+            .expect("failed to set owner index cursor");
+    }
+
+    /// Returns whether tokens stored by previous versions are left to index in the owner index
+    pub fn has_owner_index_to_migrate() -> bool {
+        with_owner_index_cursor(|cursor| cursor.get().is_some())
+    }
+
+    /// Index a batch of the tokens stored by previous versions in the owner index, from the cursor,
+    /// which is advanced to the next token, so the migration resumes where it stopped
+    pub fn migrate_owner_index() {
+        let Some(from) = with_owner_index_cursor(|cursor| cursor.get().clone()) else {
+            return;
+        };
+        let mut batch = with_tokens(|tokens| {
+            tokens
+                .range(from..)
+                .take(MIGRATION_BATCH_SIZE + 1)
+                .collect::<Vec<_>>()
+        });
+        let next = if batch.len() > MIGRATION_BATCH_SIZE {
+            batch.pop().map(|(key, _)| key)
+        } else {
+            None
+        };
+        for (key, token) in batch {
+            let Some(owner) = token.owner.filter(|_| !token.is_burned) else {
+                continue;
+            };
+            let subaccount = Self::subaccount_of(&key);
+            with_account_tokens_mut(|account_tokens| {
+                account_tokens.insert((Self::account_key(owner, subaccount), key), ())
+            });
+        }
+        with_owner_index_cursor_mut(|cursor| cursor.set(next))
+            .expect("failed to set owner index cursor");
+    }
+
+    /// Store the token as is, indexing its owner
+    #[cfg(test)]
+    pub fn store_token(collection_id: CollectionId, token: TokenMetadata) {
+        let key = Self::token_key(collection_id, &token.token_identifier);
+        let previous = with_tokens(|tokens| tokens.get(&key));
+        if token.is_burned && !previous.as_ref().is_some_and(|token| token.is_burned) {
+            count_burned_token(collection_id);
+        }
+        if previous.is_none() {
+            count_minted_token(collection_id);
+        }
+        let from = previous.and_then(|token| token.owner);
+        let to = token
+            .owner
+            .filter(|_| !token.is_burned)
+            .map(|owner| (owner, None));
+        with_tokens_mut(|tokens| tokens.insert(key.clone(), token));
+        Self::move_token(&key, from, to);
+    }
+
+    /// Move the token in the owner index from the account of its previous owner, if any, to its new account, if any
+    fn move_token(
+        key: &TokenKey,
+        from: Option<Principal>,
+        to: Option<(Principal, Option<Subaccount>)>,
+    ) {
+        let from_subaccount = with_token_subaccounts_mut(|subaccounts| {
+            match to.and_then(|(_, subaccount)| subaccount) {
+                Some(subaccount) => subaccounts.insert(key.clone(), subaccount),
+                None => subaccounts.remove(key),
+            }
+        });
+        with_account_tokens_mut(|account_tokens| {
+            if let Some(from) = from {
+                account_tokens.remove(&(Self::account_key(from, from_subaccount), key.clone()));
+            }
+            if let Some((to, to_subaccount)) = to {
+                account_tokens.insert((Self::account_key(to, to_subaccount), key.clone()), ());
+            }
+        });
+    }
+
+    /// Returns whether the token is indexed in the owner index, or doesn't exist.
+    /// Tokens stored by previous versions are indexed once migrated, and by the owner index migration.
+    fn is_indexed(key: &TokenKey) -> bool {
+        if with_tokens(|tokens| tokens.contains_key(key)) {
+            with_owner_index_cursor(|cursor| cursor.get().as_ref().is_none_or(|from| key < from))
+        } else {
+            key.0 != DEFAULT_COLLECTION_ID
+                || !with_legacy_tokens(|legacy_tokens| legacy_tokens.contains_key(&key.1))
+        }
+    }
+
+    /// Tokens of the collection not indexed yet in the owner index, while the migrations are in progress
+    fn unindexed_tokens(collection_id: CollectionId) -> Vec<TokenMetadata> {
+        let range = Self::collection_range(collection_id);
+        let mut unindexed = match with_owner_index_cursor(|cursor| cursor.get().clone()) {
+            Some(from) => with_tokens(|tokens| {
+                tokens
+                    .range(from.max(range.start)..)
+                    .take_while(|(key, _)| *key < range.end)
+                    .map(|(_, token)| token)
+                    .collect::<Vec<_>>()
+            }),
+            None => Vec::new(),
+        };
+        if collection_id == DEFAULT_COLLECTION_ID {
+            unindexed.extend(with_legacy_tokens(|legacy_tokens| {
+                legacy_tokens
+                    .iter()
+                    .filter(|(id, _)| {
+                        !with_tokens(|tokens| tokens.contains_key(&(collection_id, id.clone())))
+                    })
+                    .map(|(_, token)| token)
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        unindexed
+    }
+
+    /// Subaccount holding the token, if not the default one
+    fn subaccount_of(key: &TokenKey) -> Option<Subaccount> {
+        with_token_subaccounts(|subaccounts| subaccounts.get(key))
+    }
+
+    /// Key of the account in the owner index, the default subaccount being all zeros
+    fn account_key(owner: Principal, subaccount: Option<Subaccount>) -> AccountKey {
+        (owner.into(), subaccount.unwrap_or_default())
+    }
+
+    fn token_key(collection_id: CollectionId, id: &TokenIdentifier) -> TokenKey {
+        (collection_id, id.clone().into())
+    }
+
+    /// Range of the keys of the tokens of the collection
+    fn collection_range(collection_id: CollectionId) -> Range<TokenKey> {
+        let first = StorableNat::from(Nat::from(0_u64));
+        (collection_id, first.clone())..(collection_id + 1, first)
+    }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::app::storage::with_legacy_tokens_mut;
     use crate::app::test_utils::{alice, bob, store_mock_token_with};
 
     use super::*;
 
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_should_replace_properties() {
+        store_mock_token_with(1, |_| {});
+        store_mock_token_with(4, |_| {});
+        for id in [1_u64, 4] {
+            TokensStorage::replace_properties(
+                DEFAULT_COLLECTION_ID,
+                &id.into(),
+                vec![("id".to_string(), GenericValue::NatContent(id.into()))],
+            )
+            .unwrap();
+        }
+        assert!(
+            TokensStorage::replace_properties(DEFAULT_COLLECTION_ID, &2_u64.into(), vec![])
+                .is_err()
+        );
+        assert_eq!(
+            TokensStorage::tokens_properties(DEFAULT_COLLECTION_ID),
+            vec![
+                (
+                    1_u64.into(),
+                    vec![("id".to_string(), GenericValue::NatContent(1_u64.into()))]
+                ),
+                (
+                    4_u64.into(),
+                    vec![("id".to_string(), GenericValue::NatContent(4_u64.into()))]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_scope_tokens_by_collection() {
+        store_mock_token_with(1, |_| {});
+        store_mock_token_with(2, |_| {});
+        assert!(TokensStorage::mint(1, alice(), 1_u64.into(), vec![]).is_ok());
+        assert!(TokensStorage::mint(1, alice(), 1_u64.into(), vec![]).is_err());
+        assert!(TokensStorage::mint(2, bob(), 5_u64.into(), vec![]).is_ok());
+
+        assert_eq!(TokensStorage::total_supply(DEFAULT_COLLECTION_ID), 2);
+        assert_eq!(TokensStorage::total_supply(1), 1);
+        assert_eq!(
+            TokensStorage::next_token_id(DEFAULT_COLLECTION_ID),
+            TokenIdentifier::from(3_u64)
+        );
+        assert_eq!(
+            TokensStorage::next_token_id(1),
+            TokenIdentifier::from(2_u64)
+        );
+        assert_eq!(
+            TokensStorage::next_token_id(2),
+            TokenIdentifier::from(6_u64)
+        );
+        assert_eq!(
+            TokensStorage::next_token_id(3),
+            TokenIdentifier::from(1_u64)
+        );
+        assert_eq!(
+            TokensStorage::tokens_by_owner(1, alice()),
+            vec![TokenIdentifier::from(1_u64)]
+        );
+        assert!(TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).is_empty());
+        assert!(TokensStorage::get_token(2, &1_u64.into()).is_err());
+    }
+
+    #[test]
+    fn test_should_migrate_legacy_tokens() {
+        with_legacy_tokens_mut(|tokens| {
+            for id in 1..=MIGRATION_BATCH_SIZE as u64 + 2 {
+                tokens.insert(
+                    TokenIdentifier::from(id).into(),
+                    crate::app::test_utils::with_mock_token(id, |token| {
+                        token.owner = Some(alice())
+                    }),
+                );
+            }
+        });
+
+        // tokens not migrated yet can be read, but not minted again
+        assert!(TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1_u64.into()).is_ok());
+        assert_eq!(
+            TokensStorage::mint(DEFAULT_COLLECTION_ID, bob(), 1_u64.into(), vec![]),
+            Err(NftError::ExistedNFT)
+        );
+        // tokens not migrated yet are held by the default subaccount of their owner
+        assert!(TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &1_u64.into(),
+            alice(),
+            None
+        ));
+        assert!(!TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &1_u64.into(),
+            bob(),
+            None
+        ));
+        assert!(!TokensStorage::is_held_by(1, &1_u64.into(), alice(), None));
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).len(),
+            MIGRATION_BATCH_SIZE + 2
+        );
+        // changed tokens are migrated straight away
+        let last = TokenIdentifier::from(MIGRATION_BATCH_SIZE as u64 + 2);
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &last, bob()).is_ok());
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, bob()),
+            vec![last.clone()]
+        );
+
+        TokensStorage::migrate_legacy_tokens();
+        assert!(TokensStorage::has_legacy_tokens());
+        assert_eq!(
+            TokensStorage::total_supply(DEFAULT_COLLECTION_ID),
+            MIGRATION_BATCH_SIZE as u64 + 1
+        );
+        TokensStorage::migrate_legacy_tokens();
+        assert!(!TokensStorage::has_legacy_tokens());
+
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).len(),
+            MIGRATION_BATCH_SIZE + 1
+        );
+        assert_eq!(
+            TokensStorage::get_token(DEFAULT_COLLECTION_ID, &last)
+                .unwrap()
+                .owner,
+            Some(bob())
+        );
+    }
+
+    #[test]
+    fn test_should_count_burned_tokens() {
+        with_legacy_tokens_mut(|tokens| {
+            tokens.insert(
+                TokenIdentifier::from(3_u64).into(),
+                crate::app::test_utils::with_mock_token(3, |token| {
+                    token.owner = None;
+                    token.is_burned = true;
+                }),
+            );
+        });
+        TokensStorage::mint(DEFAULT_COLLECTION_ID, alice(), 1_u64.into(), vec![]).unwrap();
+        TokensStorage::mint(DEFAULT_COLLECTION_ID, alice(), 2_u64.into(), vec![]).unwrap();
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).unwrap();
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).is_err());
+        assert_eq!(TokensStorage::total_burned(DEFAULT_COLLECTION_ID), 1);
+
+        // burned legacy tokens count once migrated
+        TokensStorage::migrate_legacy_tokens();
+        assert_eq!(TokensStorage::total_burned(DEFAULT_COLLECTION_ID), 2);
+        assert_eq!(TokensStorage::total_burned(1), 0);
+    }
+
+    #[test]
+    fn test_should_count_token_supply() {
+        with_legacy_tokens_mut(|tokens| {
+            tokens.insert(
+                TokenIdentifier::from(3_u64).into(),
+                crate::app::test_utils::mock_token(3),
+            );
+        });
+        TokensStorage::mint(DEFAULT_COLLECTION_ID, alice(), 1_u64.into(), vec![]).unwrap();
+        TokensStorage::mint(DEFAULT_COLLECTION_ID, alice(), 2_u64.into(), vec![]).unwrap();
+        assert!(TokensStorage::mint(DEFAULT_COLLECTION_ID, alice(), 1_u64.into(), vec![]).is_err());
+        TokensStorage::mint(1, alice(), 1_u64.into(), vec![]).unwrap();
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).unwrap();
+        assert_eq!(TokensStorage::total_supply(DEFAULT_COLLECTION_ID), 2);
+
+        // legacy tokens count once migrated
+        TokensStorage::migrate_legacy_tokens();
+        assert_eq!(TokensStorage::total_supply(DEFAULT_COLLECTION_ID), 3);
+        assert_eq!(TokensStorage::total_supply(1), 1);
+        assert_eq!(TokensStorage::total_supply(2), 0);
+    }
+
     #[test]
     fn test_should_mint_token() {
         let id = 1_u64;
         let recipient = alice();
         let properties = vec![("key".to_string(), GenericValue::Int64Content(1))];
 
-        let tx_id = TokensStorage::mint(recipient, id.into(), properties).unwrap();
+        let tx_id =
+            TokensStorage::mint(DEFAULT_COLLECTION_ID, recipient, id.into(), properties).unwrap();
         assert_eq!(tx_id, 0u64);
 
         // get token
-        let token = TokensStorage::get_token(&id.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &id.into()).unwrap();
         assert_eq!(token.owner, Some(recipient));
     }
 
@@ -219,7 +827,7 @@ mod test {
             token.owner = Some(bob());
         });
 
-        assert_eq!(TokensStorage::total_supply(), 3);
+        assert_eq!(TokensStorage::total_supply(DEFAULT_COLLECTION_ID), 3);
     }
 
     #[test]
@@ -234,7 +842,10 @@ mod test {
             token.owner = Some(bob());
         });
 
-        assert_eq!(TokensStorage::total_unique_holders(), 2);
+        assert_eq!(
+            TokensStorage::total_unique_holders(DEFAULT_COLLECTION_ID),
+            2
+        );
     }
 
     #[test]
@@ -250,7 +861,7 @@ mod test {
         });
 
         assert_eq!(
-            TokensStorage::tokens_by_owner(alice()),
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()),
             vec![TokenIdentifier::from(1_u64), TokenIdentifier::from(3_u64)]
         );
     }
@@ -269,7 +880,7 @@ mod test {
         });
 
         assert_eq!(
-            TokensStorage::tokens_by_operator(bob()),
+            TokensStorage::tokens_by_operator(DEFAULT_COLLECTION_ID, bob()),
             vec![TokenIdentifier::from(1_u64)]
         );
     }
@@ -280,12 +891,13 @@ mod test {
             token.owner = Some(alice());
         });
         assert!(TokensStorage::set_token_property(
+            DEFAULT_COLLECTION_ID,
             &1_u64.into(),
             "key".to_string(),
             GenericValue::FloatContent(2.2)
         )
         .is_ok());
-        let token = TokensStorage::get_token(&1_u64.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1_u64.into()).unwrap();
         assert_eq!(
             token.properties,
             vec![("key".to_string(), GenericValue::FloatContent(2.2))]
@@ -298,11 +910,11 @@ mod test {
             token.owner = Some(alice());
         });
         assert!(
-            TokensStorage::burn(&1u64.into()).is_ok(),
+            TokensStorage::burn(DEFAULT_COLLECTION_ID, &1u64.into()).is_ok(),
             "Should burn token"
         );
         assert!(
-            TokensStorage::burn(&1u64.into()).is_err(),
+            TokensStorage::burn(DEFAULT_COLLECTION_ID, &1u64.into()).is_err(),
             "Should already be burned"
         );
     }
@@ -313,10 +925,10 @@ mod test {
             token.owner = Some(alice());
         });
         assert!(
-            TokensStorage::approve(bob(), &1u64.into()).is_ok(),
+            TokensStorage::approve(DEFAULT_COLLECTION_ID, bob(), &1u64.into()).is_ok(),
             "Should approve token"
         );
-        let token = TokensStorage::get_token(&1u64.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1u64.into()).unwrap();
         assert_eq!(token.operator, Some(bob()));
         assert!(token.approved_at.is_some());
         assert!(token.approved_by.is_some());
@@ -324,18 +936,23 @@ mod test {
         // disapprove, but with different operator
 
         assert!(
-            TokensStorage::revoke_approval(Principal::management_canister(), &1u64.into()).is_ok(),
+            TokensStorage::revoke_approval(
+                DEFAULT_COLLECTION_ID,
+                Principal::management_canister(),
+                &1u64.into()
+            )
+            .is_ok(),
             "Should revoke approval"
         );
-        let token = TokensStorage::get_token(&1u64.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1u64.into()).unwrap();
         assert_eq!(token.operator, Some(bob()));
 
         // revoke for bob
         assert!(
-            TokensStorage::revoke_approval(bob(), &1u64.into()).is_ok(),
+            TokensStorage::revoke_approval(DEFAULT_COLLECTION_ID, bob(), &1u64.into()).is_ok(),
             "Should revoke approval"
         );
-        let token = TokensStorage::get_token(&1u64.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1u64.into()).unwrap();
         assert_eq!(token.operator, None);
         assert!(token.approved_at.is_none());
         assert!(token.approved_by.is_none());
@@ -347,20 +964,241 @@ mod test {
             token.owner = Some(alice());
         });
         assert!(
-            TokensStorage::transfer(&1u64.into(), bob()).is_ok(),
+            TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1u64.into(), bob()).is_ok(),
             "Should transfer token"
         );
-        let token = TokensStorage::get_token(&1u64.into()).unwrap();
+        let token = TokensStorage::get_token(DEFAULT_COLLECTION_ID, &1u64.into()).unwrap();
         assert_eq!(token.owner, Some(bob()));
         assert!(token.transferred_at.is_some());
         assert!(token.transferred_by.is_some());
         assert!(
-            TokensStorage::burn(&1u64.into()).is_ok(),
+            TokensStorage::burn(DEFAULT_COLLECTION_ID, &1u64.into()).is_ok(),
             "Should already be burned"
         );
         assert!(
-            TokensStorage::transfer(&1u64.into(), alice()).is_err(),
+            TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1u64.into(), alice()).is_err(),
             "Should not allow transfer of burned token"
         );
     }
+
+    #[test]
+    fn test_should_transfer_children_along_with_parent() {
+        for id in 1..=4_u64 {
+            store_mock_token_with(id, |token| {
+                token.owner = Some(alice());
+            });
+        }
+        ChildrenStorage::attach(&1_u64.into(), &2_u64.into()).unwrap();
+        ChildrenStorage::attach(&2_u64.into(), &3_u64.into()).unwrap();
+
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &2_u64.into(), bob()).is_err());
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &3_u64.into()).is_err());
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).is_err());
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1_u64.into(), bob()).is_ok());
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, bob()),
+            vec![
+                TokenIdentifier::from(1_u64),
+                TokenIdentifier::from(2_u64),
+                TokenIdentifier::from(3_u64)
+            ]
+        );
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()),
+            vec![TokenIdentifier::from(4_u64)]
+        );
+    }
+
+    #[test]
+    fn test_should_remove_listing_on_transfer_and_burn() {
+        let listing = crate::did::Listing {
+            seller: alice(),
+            price: 100_u64.into(),
+            ledger: Principal::management_canister(),
+            listed_at: 0,
+        };
+        store_mock_token_with(1_u64, |token| {
+            token.owner = Some(alice());
+        });
+        store_mock_token_with(2_u64, |token| {
+            token.owner = Some(alice());
+        });
+        ListingsStorage::insert(&1u64.into(), listing.clone());
+        ListingsStorage::insert(&2u64.into(), listing);
+
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1u64.into(), bob()).is_ok());
+        assert!(ListingsStorage::get(&1u64.into()).is_none());
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &2u64.into()).is_ok());
+        assert!(ListingsStorage::get(&2u64.into()).is_none());
+    }
+
+    #[test]
+    fn test_should_lock_auctioned_token() {
+        store_mock_token_with(1_u64, |token| {
+            token.owner = Some(alice());
+        });
+        AuctionsStorage::insert(
+            &1u64.into(),
+            crate::did::Auction {
+                seller: alice(),
+                ledger: Principal::management_canister(),
+                kind: crate::did::AuctionKind::Dutch {
+                    start_price: 100_u64.into(),
+                    end_price: 10_u64.into(),
+                },
+                starts_at: 0,
+                ends_at: 1,
+                bids: vec![],
+            },
+        );
+
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1u64.into(), bob()).is_err());
+        assert!(TokensStorage::burn(DEFAULT_COLLECTION_ID, &1u64.into()).is_err());
+        AuctionsStorage::remove(&1u64.into());
+        assert!(TokensStorage::transfer(DEFAULT_COLLECTION_ID, &1u64.into(), bob()).is_ok());
+    }
+
+    #[test]
+    fn test_should_index_tokens_by_account() {
+        let subaccount = [1_u8; 32];
+        store_mock_token_with(1_u64, |token| {
+            token.owner = Some(alice());
+        });
+        TokensStorage::mint_to_account(
+            DEFAULT_COLLECTION_ID,
+            alice(),
+            Some(subaccount),
+            2_u64.into(),
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()),
+            vec![TokenIdentifier::from(1_u64)]
+        );
+        assert_eq!(
+            TokensStorage::tokens_by_account(DEFAULT_COLLECTION_ID, alice(), Some(subaccount)),
+            vec![TokenIdentifier::from(2_u64)]
+        );
+        assert_eq!(
+            TokensStorage::account_of(DEFAULT_COLLECTION_ID, &2_u64.into()).unwrap(),
+            Some(Account::new(alice(), Some(subaccount)))
+        );
+        assert!(TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &2_u64.into(),
+            alice(),
+            Some(subaccount)
+        ));
+        assert!(!TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &2_u64.into(),
+            alice(),
+            None
+        ));
+
+        TokensStorage::transfer(DEFAULT_COLLECTION_ID, &2_u64.into(), bob()).unwrap();
+        assert!(
+            TokensStorage::tokens_by_account(DEFAULT_COLLECTION_ID, alice(), Some(subaccount))
+                .is_empty()
+        );
+        assert_eq!(
+            TokensStorage::account_of(DEFAULT_COLLECTION_ID, &2_u64.into()).unwrap(),
+            Some(Account::from(bob()))
+        );
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &2_u64.into()).unwrap();
+        assert!(TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, bob()).is_empty());
+        assert_eq!(
+            TokensStorage::account_of(DEFAULT_COLLECTION_ID, &2_u64.into()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_should_migrate_owner_index() {
+        let held = MIGRATION_BATCH_SIZE as u64 + 1;
+        with_tokens_mut(|tokens| {
+            for id in 1..=held {
+                tokens.insert(
+                    (DEFAULT_COLLECTION_ID, TokenIdentifier::from(id).into()),
+                    crate::app::test_utils::with_mock_token(id, |token| {
+                        token.owner = Some(alice())
+                    }),
+                );
+            }
+            tokens.insert(
+                (
+                    DEFAULT_COLLECTION_ID,
+                    TokenIdentifier::from(held + 1).into(),
+                ),
+                crate::app::test_utils::with_mock_token(held + 1, |token| {
+                    token.owner = None;
+                    token.is_burned = true;
+                }),
+            );
+        });
+        assert!(TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).is_empty());
+        assert!(!TokensStorage::has_owner_index_to_migrate());
+        TokensStorage::start_owner_index_migration();
+        assert!(TokensStorage::has_owner_index_to_migrate());
+        // tokens left to index are held by their owners
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).len(),
+            held as usize
+        );
+        assert!(TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &held.into(),
+            alice(),
+            None
+        ));
+        assert!(!TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &held.into(),
+            bob(),
+            None
+        ));
+        assert!(!TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &held.into(),
+            alice(),
+            Some([1; 32])
+        ));
+
+        TokensStorage::migrate_owner_index();
+        assert!(TokensStorage::has_owner_index_to_migrate());
+        assert_eq!(
+            with_account_tokens(|account_tokens| account_tokens.len()),
+            MIGRATION_BATCH_SIZE as u64
+        );
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).len(),
+            held as usize
+        );
+        // tokens left to index are indexed once transferred
+        TokensStorage::transfer(DEFAULT_COLLECTION_ID, &held.into(), bob()).unwrap();
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, bob()),
+            vec![TokenIdentifier::from(held)]
+        );
+        // the migration isn't restarted while in progress
+        TokensStorage::start_owner_index_migration();
+        TokensStorage::migrate_owner_index();
+        assert!(!TokensStorage::has_owner_index_to_migrate());
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, alice()).len(),
+            held as usize - 1
+        );
+        assert_eq!(
+            TokensStorage::tokens_by_owner(DEFAULT_COLLECTION_ID, bob()),
+            vec![TokenIdentifier::from(held)]
+        );
+        assert!(TokensStorage::is_held_by(
+            DEFAULT_COLLECTION_ID,
+            &1_u64.into(),
+            alice(),
+            None
+        ));
+    }
 }
@@ -1,5 +1,7 @@
 use candid::Nat;
-use dip721_rs::{TokenMetadata, TxEvent};
+use dip721_rs::{GenericValue, TokenIdentifier, TokenMetadata, TxEvent};
+
+use crate::did::TokenUser;
 
 use super::{with_tx_history, with_tx_history_mut};
 
@@ -77,6 +79,112 @@ impl TxHistory {
         id
     }
 
+    /// Register the change of the user of a token; `None` if the user was removed
+    pub fn register_set_user(token_identifier: &TokenIdentifier, user: Option<&TokenUser>) -> Nat {
+        let mut details = vec![(
+            "token_identifier".to_string(),
+            GenericValue::NatContent(token_identifier.clone()),
+        )];
+        if let Some(user) = user {
+            details.push(("user".to_string(), GenericValue::Principal(user.user)));
+            details.push((
+                "expires_at".to_string(),
+                GenericValue::Nat64Content(user.expires_at),
+            ));
+        }
+        let event = TxEvent {
+            caller: crate::utils::caller(),
+            details,
+            operation: "set_user".to_string(),
+            time: crate::utils::time(),
+        };
+        let id = Self::next_id();
+        with_tx_history_mut(|tx_history| {
+            tx_history.insert(id.clone().into(), event);
+        });
+
+        id
+    }
+
+    /// Register a batch of transfers, grouping the transactions of its transfers
+    pub fn register_transfer_batch(tx_ids: &[Nat]) -> Nat {
+        let event = TxEvent {
+            caller: crate::utils::caller(),
+            details: tx_ids
+                .iter()
+                .map(|tx_id| ("tx_id".to_string(), GenericValue::NatContent(tx_id.clone())))
+                .collect(),
+            operation: "transfer_batch".to_string(),
+            time: crate::utils::time(),
+        };
+        let id = Self::next_id();
+        with_tx_history_mut(|tx_history| {
+            tx_history.insert(id.clone().into(), event);
+        });
+
+        id
+    }
+
+    /// Register an accepted swap, grouping the transactions of its transfers
+    pub fn register_swap(swap_id: u64, tx_ids: &[Nat]) -> Nat {
+        let mut details = vec![("swap_id".to_string(), GenericValue::Nat64Content(swap_id))];
+        details.extend(
+            tx_ids
+                .iter()
+                .map(|tx_id| ("tx_id".to_string(), GenericValue::NatContent(tx_id.clone()))),
+        );
+        let event = TxEvent {
+            caller: crate::utils::caller(),
+            details,
+            operation: "swap".to_string(),
+            time: crate::utils::time(),
+        };
+        let id = Self::next_id();
+        with_tx_history_mut(|tx_history| {
+            tx_history.insert(id.clone().into(), event);
+        });
+
+        id
+    }
+
+    /// Register the attachment of a token to a parent token
+    pub fn register_attach_child(parent: &TokenIdentifier, child: &TokenIdentifier) -> Nat {
+        Self::register_child_event("attach_child", parent, child)
+    }
+
+    /// Register the detachment of a token from its parent token
+    pub fn register_detach_child(parent: &TokenIdentifier, child: &TokenIdentifier) -> Nat {
+        Self::register_child_event("detach_child", parent, child)
+    }
+
+    fn register_child_event(
+        operation: &str,
+        parent: &TokenIdentifier,
+        child: &TokenIdentifier,
+    ) -> Nat {
+        let event = TxEvent {
+            caller: crate::utils::caller(),
+            details: vec![
+                (
+                    "parent_token_identifier".to_string(),
+                    GenericValue::NatContent(parent.clone()),
+                ),
+                (
+                    "child_token_identifier".to_string(),
+                    GenericValue::NatContent(child.clone()),
+                ),
+            ],
+            operation: operation.to_string(),
+            time: crate::utils::time(),
+        };
+        let id = Self::next_id();
+        with_tx_history_mut(|tx_history| {
+            tx_history.insert(id.clone().into(), event);
+        });
+
+        id
+    }
+
     /// get next transaction id
     fn next_id() -> Nat {
         with_tx_history(|tx_history| tx_history.len()).into()
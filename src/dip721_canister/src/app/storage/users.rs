@@ -0,0 +1,53 @@
+use dip721_rs::TokenIdentifier;
+
+use super::{with_token_users, with_token_users_mut};
+use crate::did::TokenUser;
+use crate::storable::StorableNat;
+
+/// Users of the tokens, allowed to use them without owning them (ERC-4907)
+pub struct UsersStorage;
+
+impl UsersStorage {
+    /// Get the user of the token, unless it has expired at `now`
+    pub fn get(token_identifier: &TokenIdentifier, now: u64) -> Option<TokenUser> {
+        with_token_users(|users| users.get(&StorableNat::from(token_identifier.clone())))
+            .filter(|user| user.expires_at > now)
+    }
+
+    /// Set the user of the token, replacing the previous one
+    pub fn insert(token_identifier: &TokenIdentifier, user: TokenUser) {
+        with_token_users_mut(|users| {
+            users.insert(StorableNat::from(token_identifier.clone()), user);
+        });
+    }
+
+    /// Remove the user of the token
+    pub fn remove(token_identifier: &TokenIdentifier) {
+        with_token_users_mut(|users| {
+            users.remove(&StorableNat::from(token_identifier.clone()));
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_expire_users() {
+        let user = TokenUser {
+            user: Principal::anonymous(),
+            expires_at: 100,
+        };
+        UsersStorage::insert(&1_u64.into(), user.clone());
+        assert_eq!(UsersStorage::get(&1_u64.into(), 99), Some(user));
+        assert!(UsersStorage::get(&1_u64.into(), 100).is_none());
+
+        UsersStorage::remove(&1_u64.into());
+        assert!(UsersStorage::get(&1_u64.into(), 0).is_none());
+    }
+}
@@ -1,9 +1,10 @@
 use candid::Principal;
 use dip721_rs::{TokenIdentifier, TokenMetadata};
 
+use crate::did::DEFAULT_COLLECTION_ID;
 use crate::utils::caller;
 
-use super::storage::{with_tokens_mut, TokensStorage};
+use super::storage::TokensStorage;
 
 pub fn mock_token(id: u64) -> TokenMetadata {
     TokenMetadata {
@@ -35,11 +36,9 @@ where
 pub fn store_mock_token(id: u64) -> TokenMetadata {
     let token = mock_token(id);
 
-    with_tokens_mut(|tokens| {
-        tokens.insert(TokenIdentifier::from(id).into(), token);
-    });
+    TokensStorage::store_token(DEFAULT_COLLECTION_ID, token);
 
-    TokensStorage::get_token(&id.into()).unwrap()
+    TokensStorage::get_token(DEFAULT_COLLECTION_ID, &id.into()).unwrap()
 }
 
 pub fn store_mock_token_with<F>(id: u64, f: F) -> TokenMetadata
@@ -48,11 +47,9 @@ where
 {
     let token = with_mock_token(id, f);
 
-    with_tokens_mut(|tokens| {
-        tokens.insert(TokenIdentifier::from(id).into(), token);
-    });
+    TokensStorage::store_token(DEFAULT_COLLECTION_ID, token);
 
-    TokensStorage::get_token(&id.into()).unwrap()
+    TokensStorage::get_token(DEFAULT_COLLECTION_ID, &id.into()).unwrap()
 }
 
 pub fn alice() -> Principal {
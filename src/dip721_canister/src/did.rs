@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
-use candid::{CandidType, Principal};
-use dip721_rs::SupportedInterface;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use candid::{CandidType, Nat, Principal};
+use dip721_rs::{GenericValue, NftError, SupportedInterface, TokenIdentifier};
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
 
@@ -13,12 +15,641 @@ pub struct CanisterInitData {
     pub name: String,
     pub symbol: String,
     pub logo: Option<String>,
+    /// Provenance hash of the final metadata of a delayed reveal drop
+    pub provenance_hash: Option<ByteBuf>,
+}
+
+/// Id of a collection hosted by the canister
+pub type CollectionId = u64;
+
+/// Collection addressed by the DIP721 methods, configured on init
+pub const DEFAULT_COLLECTION_ID: CollectionId = 0;
+
+/// ICRC-1 subaccount
+pub type Subaccount = [u8; 32];
+
+/// ICRC-1 account. Both the `None` and the all-zero subaccount are the default subaccount of the owner,
+/// which the DIP721 methods operate on.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<ByteBuf>,
+}
+
+impl Account {
+    pub fn new(owner: Principal, subaccount: Option<Subaccount>) -> Self {
+        Self {
+            owner,
+            subaccount: subaccount.map(|subaccount| ByteBuf::from(subaccount.to_vec())),
+        }
+    }
+
+    /// Returns the subaccount, `None` if it is the default one.
+    /// Returns an error if the subaccount is not 32 bytes long.
+    pub fn effective_subaccount(&self) -> Result<Option<Subaccount>, NftError> {
+        let Some(subaccount) = &self.subaccount else {
+            return Ok(None);
+        };
+        let subaccount = Subaccount::try_from(subaccount.as_slice())
+            .map_err(|_| NftError::Other("subaccount must be 32 bytes long".to_string()))?;
+
+        Ok(Some(subaccount).filter(|subaccount| subaccount != &Subaccount::default()))
+    }
+}
+
+impl From<Principal> for Account {
+    fn from(owner: Principal) -> Self {
+        Self {
+            owner,
+            subaccount: None,
+        }
+    }
+}
+
+/// Arguments to create a collection beside the default one
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct CreateCollectionArgs {
+    pub name: String,
+    pub symbol: String,
+    pub logo: Option<String>,
+    pub custodians: Vec<Principal>,
+    pub supported_interfaces: Vec<SupportedInterface>,
+    /// Maximum amount of tokens, unlimited if none
+    pub max_supply: Option<u64>,
+}
+
+/// Collection hosted by the canister, with its own configuration and tokens.
+///
+/// Listings, auctions, offers, swaps, users, editions and children only exist in the default collection.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct Collection {
+    pub name: String,
+    pub symbol: String,
+    pub logo: Option<Logo>,
+    pub custodians: Vec<Principal>,
+    pub supported_interfaces: Vec<SupportedInterface>,
+    /// Maximum amount of tokens, unlimited if none
+    pub max_supply: Option<u64>,
+    pub created_at: u64,
+}
+
+/// Canister logo
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Logo {
+    /// MIME type of the logo
+    pub content_type: String,
+    pub data: ByteBuf,
+    /// Text encoding the logo was set with, returned by `dip721_logo`; plain base64 if none
+    pub encoding: Option<LogoEncoding>,
+}
+
+/// Text encoding of a logo set with `dip721_set_logo`
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LogoEncoding {
+    /// Base64 `data:` URL
+    DataUrl,
+    /// Text which isn't base64, kept as is
+    Text,
+}
+
+impl Logo {
+    /// Decode a logo from a base64 string, optionally formatted as a `data:` URL.
+    ///
+    /// When the MIME type is not provided, it is guessed from the content.
+    pub fn from_base64(logo: &str) -> Result<Self, base64::DecodeError> {
+        let (content_type, encoded) = match logo
+            .strip_prefix("data:")
+            .and_then(|data_url| data_url.split_once(";base64,"))
+        {
+            Some((content_type, encoded)) => (Some(content_type.to_string()), encoded),
+            None => (None, logo),
+        };
+        let data = BASE64.decode(encoded.trim())?;
+        let encoding = content_type.is_some().then_some(LogoEncoding::DataUrl);
+        let content_type = content_type.unwrap_or_else(|| Self::guess_content_type(&data));
+
+        Ok(Self {
+            content_type,
+            data: ByteBuf::from(data),
+            encoding,
+        })
+    }
+
+    /// Decode a logo set as text: base64, optionally formatted as a `data:` URL, or any other text kept as is
+    pub fn from_text(logo: &str) -> Self {
+        Self::from_base64(logo).unwrap_or_else(|_| Self {
+            content_type: "text/plain".to_string(),
+            data: ByteBuf::from(logo.as_bytes().to_vec()),
+            encoding: Some(LogoEncoding::Text),
+        })
+    }
+
+    /// Encode the logo as text, with the encoding it was set with
+    pub fn to_text(&self) -> String {
+        match self.encoding {
+            None => BASE64.encode(&self.data),
+            Some(LogoEncoding::DataUrl) => format!(
+                "data:{};base64,{}",
+                self.content_type,
+                BASE64.encode(&self.data)
+            ),
+            Some(LogoEncoding::Text) => String::from_utf8_lossy(&self.data).into_owned(),
+        }
+    }
+
+    fn guess_content_type(data: &[u8]) -> String {
+        let content_type = match data {
+            [0x89, b'P', b'N', b'G', ..] => "image/png",
+            [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+            [b'G', b'I', b'F', b'8', ..] => "image/gif",
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+            _ if data.starts_with(b"<svg") || data.starts_with(b"<?xml") => "image/svg+xml",
+            _ => "application/octet-stream",
+        };
+
+        content_type.to_string()
+    }
+}
+
+/// Mapping of token property keys to the fields of the marketplace metadata JSON of a token.
+///
+/// Properties which are neither mapped nor excluded are rendered as attributes.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TokenJsonMapping {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub external_url: Option<String>,
+    pub animation_url: Option<String>,
+    /// Properties not to render as attributes
+    pub excluded_attributes: Vec<String>,
+}
+
+impl Default for TokenJsonMapping {
+    fn default() -> Self {
+        Self {
+            name: Some("name".to_string()),
+            description: Some("description".to_string()),
+            image: Some("image".to_string()),
+            external_url: None,
+            animation_url: None,
+            excluded_attributes: vec![],
+        }
+    }
+}
+
+/// Metadata of a media asset stored in the canister
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AssetMetadata {
+    /// Asset identifier
+    pub id: u64,
+    /// MIME type of the asset content
+    pub content_type: String,
+    /// Expected sha256 of the asset content
+    pub sha256: ByteBuf,
+    /// Size in bytes of the uploaded content
+    pub size: u64,
+    /// Amount of uploaded chunks
+    pub chunks: u32,
+    /// Whether the upload has been finalized; only finalized assets are served
+    pub finalized: bool,
+    pub created_at: u64,
+    pub created_by: Principal,
+}
+
+/// Basis points representing the whole (100%)
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Royalty paid to the creators on secondary sales
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Royalty {
+    /// Royalty of the sale price, in basis points
+    pub basis_points: u16,
+    /// Recipients splitting the royalty
+    pub recipients: Vec<RoyaltyRecipient>,
+}
+
+/// Recipient of a share of a royalty
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RoyaltyRecipient {
+    pub recipient: Principal,
+    /// Share of the royalty, in basis points; shares of all the recipients must sum to [`BASIS_POINTS`]
+    pub share: u16,
+}
+
+/// Amount of a royalty due to a recipient
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RoyaltyPayment {
+    pub recipient: Principal,
+    pub amount: Nat,
+}
+
+impl Royalty {
+    /// Returns whether the royalty is at most the sale price and the shares sum to the whole royalty
+    pub fn is_valid(&self) -> bool {
+        self.basis_points <= BASIS_POINTS
+            && !self.recipients.is_empty()
+            && self
+                .recipients
+                .iter()
+                .map(|recipient| recipient.share as u32)
+                .sum::<u32>()
+                == BASIS_POINTS as u32
+    }
+
+    /// Returns the amounts due to each recipient for the given sale price.
+    ///
+    /// The rounding remainder of the split goes to the first recipient, so that the payments sum to the royalty.
+    pub fn payments(&self, sale_price: &Nat) -> Vec<RoyaltyPayment> {
+        let royalty = sale_price.clone() * self.basis_points / BASIS_POINTS;
+        let mut payments = self
+            .recipients
+            .iter()
+            .map(|recipient| RoyaltyPayment {
+                recipient: recipient.recipient,
+                amount: royalty.clone() * recipient.share / BASIS_POINTS,
+            })
+            .collect::<Vec<_>>();
+
+        let paid = payments.iter().fold(Nat::from(0_u64), |paid, payment| {
+            paid + payment.amount.clone()
+        });
+        if let Some(first) = payments.first_mut() {
+            first.amount += royalty - paid;
+        }
+
+        payments
+    }
+}
+
+/// Token listed for sale at a fixed price on the canister marketplace
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Listing {
+    /// Owner of the token when it was listed
+    pub seller: Principal,
+    /// Price, in the smallest unit of the ledger token
+    pub price: Nat,
+    /// ICRC-2 ledger the price is paid on
+    pub ledger: Principal,
+    pub listed_at: u64,
+}
+
+/// Fee taken by the platform on every marketplace sale
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PlatformFee {
+    /// Fee of the sale price, in basis points
+    pub basis_points: u16,
+    pub recipient: Principal,
+}
+
+/// Settings of the canister marketplace
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MarketplaceSettings {
+    /// ICRC-2 ledgers tokens can be listed on
+    pub ledgers: Vec<Principal>,
+    pub platform_fee: Option<PlatformFee>,
+}
+
+/// Public sale of newly minted tokens
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MintSale {
+    /// Price of a token, in the smallest unit of the ledger token
+    pub price: Nat,
+    /// ICRC-2 ledger the price is paid on
+    pub ledger: Principal,
+    /// Receives the price of the minted tokens
+    pub treasury: Principal,
+    /// Maximum amount of tokens minted by a wallet
+    pub wallet_limit: u64,
+    /// Maximum amount of tokens minted by the sale
+    pub allocation: u64,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+impl MintSale {
+    /// Returns whether the sale is open at `now`
+    pub fn is_open(&self, now: u64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// Minting phase restricted to the principals of an allowlist
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AllowlistPhase {
+    /// SHA-256 Merkle root of the `(principal, max_quantity)` entries of the allowlist
+    pub merkle_root: ByteBuf,
+    /// Price of a token, in the smallest unit of the ledger token
+    pub price: Nat,
+    /// ICRC-2 ledger the price is paid on
+    pub ledger: Principal,
+    /// Receives the price of the minted tokens
+    pub treasury: Principal,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+impl AllowlistPhase {
+    /// Returns whether the phase is open at `now`
+    pub fn is_open(&self, now: u64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// Authorization to mint a token, signed off-chain by a voucher signer and redeemable by anyone
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MintVoucher {
+    pub token_identifier: TokenIdentifier,
+    pub to: Principal,
+    /// SHA-256 of the candid encoding of the token properties
+    pub properties_hash: ByteBuf,
+    /// Canister the voucher can be redeemed on
+    pub canister_id: Principal,
+    pub expires_at: u64,
+    pub nonce: u64,
+}
+
+impl MintVoucher {
+    /// Returns the message signed by the voucher signer: the candid encoding of the voucher
+    pub fn message(&self) -> Vec<u8> {
+        candid::encode_one(self).expect("failed to encode voucher")
+    }
+}
+
+/// Commitment to the final metadata of a delayed reveal drop
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Provenance {
+    /// SHA-256 of the concatenated SHA-256 of the candid encoded properties of each token, in the committed order
+    pub hash: ByteBuf,
+    pub committed_at: u64,
+    pub reveal: Option<Reveal>,
+}
+
+/// Reveal of the final metadata committed by the provenance hash
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Reveal {
+    /// Randomness returned by `raw_rand`, from which the offset is derived
+    pub seed: ByteBuf,
+    /// The token `k` is given the metadata at `(k - 1 + offset) % metadata_hashes.len()`
+    pub offset: u64,
+    /// SHA-256 of the candid encoded properties of each token, in the committed order
+    pub metadata_hashes: Vec<ByteBuf>,
+    pub revealed_at: u64,
+}
+
+/// Pool of the token identifiers `1..=size` minted in random order
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RandomPool {
+    pub size: u64,
+    /// Amount of identifiers not picked yet
+    pub remaining: u64,
+}
+
+/// User of a token, allowed to use it without owning it until `expires_at` (ERC-4907)
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TokenUser {
+    pub user: Principal,
+    pub expires_at: u64,
+}
+
+/// Template holding the properties shared by the editions of an artwork
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct EditionTemplate {
+    pub properties: Vec<(String, GenericValue)>,
+    /// Maximum amount of editions, unlimited if none
+    pub max_editions: Option<u64>,
+    /// Amount of editions minted so far
+    pub minted: u64,
+}
+
+/// Edition of a template a token was minted as, starting from 1
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TokenEdition {
+    pub template_id: u64,
+    pub edition: u64,
+}
+
+/// Result of a batch transfer: the id of the transaction grouping the batch and the ones of its transfers
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TransferBatch {
+    pub batch_id: Nat,
+    pub tx_ids: Vec<Nat>,
+}
+
+/// Offer to buy a token, escrowed by the canister until accepted, withdrawn or expired
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Offer {
+    pub token_identifier: TokenIdentifier,
+    pub bidder: Principal,
+    /// ICRC-2 ledger the amount is escrowed on
+    pub ledger: Principal,
+    pub amount: Nat,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Payment on an ICRC-2 ledger the counterparty of a swap adds to their tokens
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SwapPayment {
+    pub ledger: Principal,
+    pub amount: Nat,
+}
+
+/// Arguments of a swap proposal
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ProposeSwapArgs {
+    pub counterparty: Principal,
+    /// Tokens of the proposer, given to the counterparty
+    pub offered: Vec<TokenIdentifier>,
+    /// Tokens of the counterparty, given to the proposer
+    pub requested: Vec<TokenIdentifier>,
+    /// Payment from the counterparty to the proposer, if any
+    pub payment: Option<SwapPayment>,
+    pub expires_at: u64,
+}
+
+/// Proposal to swap tokens with a counterparty.
+/// The offered tokens are locked until the swap is accepted, cancelled or expired.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Swap {
+    pub proposer: Principal,
+    pub counterparty: Principal,
+    pub offered: Vec<TokenIdentifier>,
+    pub requested: Vec<TokenIdentifier>,
+    pub payment: Option<SwapPayment>,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Kind of a token auction
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AuctionKind {
+    /// Ascending auction won by the highest bid at the end
+    English {
+        /// Minimum amount of the first bid
+        reserve_price: Nat,
+        /// Minimum raise over the highest bid
+        min_increment: Nat,
+        /// Bids placed in the last `extension` nanoseconds postpone the end by `extension` from the bid
+        extension: u64,
+    },
+    /// Descending auction won by the first bid, with the price decaying linearly over the auction
+    Dutch { start_price: Nat, end_price: Nat },
+}
+
+/// Bid on an auction, escrowed by the canister
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Bid {
+    pub bidder: Principal,
+    pub amount: Nat,
+    pub placed_at: u64,
+}
+
+/// Auction of a token
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Auction {
+    /// Owner of the token when the auction was created
+    pub seller: Principal,
+    /// ICRC-2 ledger bids are paid on
+    pub ledger: Principal,
+    pub kind: AuctionKind,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    /// Escrowed bids, from the lowest to the highest
+    pub bids: Vec<Bid>,
+}
+
+impl Auction {
+    /// Returns the highest bid
+    pub fn highest_bid(&self) -> Option<&Bid> {
+        self.bids.last()
+    }
+
+    /// Returns the minimum amount of the next bid at `now`: the current price for Dutch auctions
+    pub fn min_bid(&self, now: u64) -> Nat {
+        match &self.kind {
+            AuctionKind::English {
+                reserve_price,
+                min_increment,
+                ..
+            } => match self.highest_bid() {
+                Some(bid) => bid.amount.clone() + min_increment.clone(),
+                None => reserve_price.clone(),
+            },
+            AuctionKind::Dutch {
+                start_price,
+                end_price,
+            } => {
+                let duration = self.ends_at.saturating_sub(self.starts_at);
+                let elapsed = now.saturating_sub(self.starts_at).min(duration);
+                if duration == 0 || start_price <= end_price {
+                    return end_price.clone();
+                }
+                let decay = (start_price.clone() - end_price.clone()) * elapsed / duration;
+                start_price.clone() - decay
+            }
+        }
+    }
+
+    /// Returns whether the auction has ended at `now`
+    pub fn has_ended(&self, now: u64) -> bool {
+        now >= self.ends_at
+    }
+}
+
+/// EXT token identifier: the text encoding of the canister id and the token index
+pub type ExtTokenIdentifier = String;
+
+/// EXT account identifier: the hex encoding of the CRC32 and SHA-224 of an account
+pub type ExtAccountIdentifier = String;
+
+/// EXT token index, mapped onto the token identifiers of the default collection
+pub type ExtTokenIndex = u32;
+
+/// Result of the EXT methods, whose variants are lowercase
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ExtResult<T, E> {
+    #[serde(rename = "ok")]
+    Ok(T),
+    #[serde(rename = "err")]
+    Err(E),
+}
+
+impl<T, E> From<Result<T, E>> for ExtResult<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self::Ok(value),
+            Err(err) => Self::Err(err),
+        }
+    }
+}
+
+/// Result of `tokens_ext`: the tokens held by an account identifier, with their listing and metadata
+pub type ExtTokensResult =
+    ExtResult<Vec<(ExtTokenIndex, Option<ExtListing>, Option<ByteBuf>)>, ExtCommonError>;
+
+/// Error of the EXT query methods
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ExtCommonError {
+    InvalidToken(ExtTokenIdentifier),
+    Other(String),
+}
+
+/// EXT user, either an account identifier or the default account of a principal
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ExtUser {
+    #[serde(rename = "address")]
+    Address(ExtAccountIdentifier),
+    #[serde(rename = "principal")]
+    Principal(Principal),
+}
+
+/// EXT metadata of a token
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ExtMetadata {
+    #[serde(rename = "nonfungible")]
+    Nonfungible { metadata: Option<ByteBuf> },
+}
+
+/// EXT marketplace listing
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExtListing {
+    pub locked: Option<candid::Int>,
+    pub price: u64,
+    pub seller: Principal,
+}
+
+/// Arguments of an EXT transfer, sent from the `subaccount` of the caller
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExtTransferRequest {
+    pub from: ExtUser,
+    pub to: ExtUser,
+    pub token: ExtTokenIdentifier,
+    pub amount: Nat,
+    pub memo: ByteBuf,
+    pub notify: bool,
+    pub subaccount: Option<ByteBuf>,
+}
+
+/// Error of an EXT transfer
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ExtTransferError {
+    Unauthorized(ExtAccountIdentifier),
+    InsufficientBalance,
+    Rejected,
+    InvalidToken(ExtTokenIdentifier),
+    CannotNotify(ExtAccountIdentifier),
+    Other(String),
 }
 
 const HTTP_OK: u16 = 200;
+const HTTP_NO_CONTENT: u16 = 204;
 const HTTP_UPGRADE: u16 = 204;
+const HTTP_PARTIAL_CONTENT: u16 = 206;
+const HTTP_NOT_MODIFIED: u16 = 304;
 const HTTP_BAD_REQUEST: u16 = 400;
 const HTTP_NOT_FOUND: u16 = 404;
+const HTTP_RANGE_NOT_SATISFIABLE: u16 = 416;
 const HTTP_INTERNAL_ERROR: u16 = 500;
 
 /// A HTTP response.
@@ -49,44 +680,41 @@ impl HttpResponse {
         }
     }
 
-    /// Returns a new `HttpResponse` intended to be used for internal errors.
-    pub fn internal_error(e: String) -> Self {
-        let body = match serde_json::to_vec(&e) {
-            Ok(bytes) => ByteBuf::from(&bytes[..]),
-            Err(e) => ByteBuf::from(e.to_string().as_bytes()),
-        };
+    /// Returns a new `HttpResponse` with a JSON error object as body
+    fn error(status_code: u16, message: String) -> Self {
+        let body = serde_json::json!({ "error": { "code": status_code, "message": message } });
 
         Self {
-            status_code: HTTP_INTERNAL_ERROR,
+            status_code,
             headers: HashMap::from([("content-type".into(), "application/json".into())]),
-            body,
+            body: ByteBuf::from(body.to_string().into_bytes()),
             upgrade: None,
         }
     }
 
+    /// Returns a new `HttpResponse` intended to be used for internal errors.
+    pub fn internal_error(e: String) -> Self {
+        Self::error(HTTP_INTERNAL_ERROR, e)
+    }
+
     /// Returns a new `HttpResponse` intended to be used for bad request
     pub fn bad_request(e: String) -> Self {
-        let body = match serde_json::to_vec(&e) {
-            Ok(bytes) => ByteBuf::from(&bytes[..]),
-            Err(e) => ByteBuf::from(e.to_string().as_bytes()),
-        };
-
-        Self {
-            status_code: HTTP_BAD_REQUEST,
-            headers: HashMap::from([("content-type".into(), "application/json".into())]),
-            body,
-            upgrade: None,
-        }
+        Self::error(HTTP_BAD_REQUEST, e)
     }
 
     /// Returns a new `HttpResponse` intended to be used for not found
     pub fn not_found() -> Self {
-        Self {
-            status_code: HTTP_NOT_FOUND,
-            headers: HashMap::from([("content-type".into(), "application/json".into())]),
-            body: ByteBuf::from("Not Found".as_bytes()),
-            upgrade: None,
-        }
+        Self::error(HTTP_NOT_FOUND, "Not Found".to_string())
+    }
+
+    /// Returns a new `HttpResponse` without content
+    pub fn no_content() -> Self {
+        Self::new(
+            HTTP_NO_CONTENT,
+            HashMap::default(),
+            ByteBuf::default(),
+            None,
+        )
     }
 
     /// Returns an OK response with the given body.
@@ -106,6 +734,68 @@ impl HttpResponse {
         )
     }
 
+    /// Returns an OK response with a raw body of the given content type.
+    pub fn ok_raw(content_type: String, body: Vec<u8>) -> Self {
+        Self::new(
+            HTTP_OK,
+            HashMap::from([("content-type".into(), content_type.into())]),
+            ByteBuf::from(body),
+            None,
+        )
+    }
+
+    /// Returns a partial content response with the bytes in `[start, end)` of a resource of `size` bytes.
+    pub fn partial_content(
+        content_type: String,
+        body: Vec<u8>,
+        start: u64,
+        end: u64,
+        size: u64,
+    ) -> Self {
+        Self::new(
+            HTTP_PARTIAL_CONTENT,
+            HashMap::from([
+                ("content-type".into(), content_type.into()),
+                (
+                    "content-range".into(),
+                    format!("bytes {start}-{}/{size}", end.saturating_sub(1)).into(),
+                ),
+            ]),
+            ByteBuf::from(body),
+            None,
+        )
+    }
+
+    /// Returns a new `HttpResponse` for a range which cannot be served for a resource of `size` bytes.
+    pub fn range_not_satisfiable(size: u64) -> Self {
+        Self::new(
+            HTTP_RANGE_NOT_SATISFIABLE,
+            HashMap::from([("content-range".into(), format!("bytes */{size}").into())]),
+            ByteBuf::default(),
+            None,
+        )
+    }
+
+    /// Set a header on the response
+    pub fn with_header(
+        mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns a new `HttpResponse` telling that the cached resource is still valid
+    pub fn not_modified() -> Self {
+        Self::new(
+            HTTP_NOT_MODIFIED,
+            HashMap::default(),
+            ByteBuf::default(),
+            None,
+        )
+    }
+
     /// Upgrade response to update call.
     pub fn upgrade_response() -> Self {
         Self::new(
@@ -142,30 +832,58 @@ impl HttpRequest {
         }
     }
 
-    pub fn decode_body<S>(&self) -> Result<S, HttpResponse>
-    where
-        S: serde::de::DeserializeOwned,
-    {
-        serde_json::from_slice::<HttpApiRequest<S>>(&self.body)
-            .map_err(|_| HttpResponse::bad_request("Invalid request body".to_string()))
-            .map(|m| m.params)
+    /// Returns the path of the requested URL, without the query string
+    pub fn path(&self) -> &str {
+        self.url.split('?').next().unwrap_or_default()
     }
 
-    pub fn decode_method(&self) -> Result<String, HttpResponse> {
-        serde_json::from_slice::<HttpApiMethod>(&self.body)
-            .map_err(|_| HttpResponse::bad_request("Invalid request body".to_string()))
-            .map(|m| m.method)
+    /// Returns the percent-decoded query string parameters of the requested URL
+    pub fn query_params(&self) -> HashMap<String, String> {
+        let Some((_, query)) = self.url.split_once('?') else {
+            return HashMap::default();
+        };
+
+        query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (key, value) = param.split_once('=').unwrap_or((param, ""));
+                (percent_decode(key), percent_decode(value))
+            })
+            .collect()
     }
-}
 
-#[derive(Clone, Debug, Deserialize)]
-struct HttpApiMethod {
-    pub method: String,
+    /// Returns the value of a request header, matching the name case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
 }
 
-/// The important components of an HTTP request.
-#[derive(Clone, Debug, Deserialize)]
-pub struct HttpApiRequest<S> {
-    pub method: String,
-    pub params: S,
+/// Decodes a percent-encoded query string component
+fn percent_decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => match component
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    index += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
 }
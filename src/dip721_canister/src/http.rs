@@ -1,80 +1,182 @@
-use candid::Nat;
-use dip721_rs::Dip721;
-use serde::Deserialize;
-
-use crate::{
-    app::App,
-    did::{HttpRequest, HttpResponse},
-};
-
-#[derive(Deserialize)]
-struct TokenIdentifierReq {
-    pub id: Nat,
-}
+mod cors;
+mod json_rpc;
+mod metrics;
+mod rest;
+mod token_json;
+mod update;
+
+use self::cors::Cors;
+use self::json_rpc::JsonRpcApi;
+use self::rest::RestApi;
+use self::update::UpdateApi;
+use crate::did::{HttpRequest, HttpResponse};
 
 pub struct HttpApi;
 
 impl HttpApi {
     /// Handles an HTTP request
     pub async fn handle_http_request(req: HttpRequest) -> HttpResponse {
-        // must be a GET request
-        if req.method != "GET" {
-            return HttpResponse::bad_request("expected GET method".to_string());
+        let response = match req.method.as_ref() {
+            "OPTIONS" => return Cors::preflight(&req),
+            // POST requests may change the state, so they must go through consensus
+            "POST" => return HttpResponse::upgrade_response(),
+            // path-based routes, or JSON-RPC
+            "GET" => RestApi::route(&req).unwrap_or_else(|| Self::handle_json_rpc(&req)),
+            _ => HttpResponse::bad_request("expected GET, POST or OPTIONS method".to_string()),
+        };
+
+        Cors::apply(&req, Self::not_modified_if_none_match(&req, response))
+    }
+
+    /// Handles an HTTP request upgraded to an update call
+    pub async fn handle_http_request_update(req: HttpRequest) -> HttpResponse {
+        let response = if req.method != "POST" {
+            HttpResponse::bad_request("expected POST method".to_string())
+        } else {
+            UpdateApi::route(&req).unwrap_or_else(|| Self::handle_json_rpc(&req))
+        };
+
+        Cors::apply(&req, response)
+    }
+
+    /// Replaces the response with a `304 Not Modified` if its `etag` matches the `if-none-match` header
+    fn not_modified_if_none_match(req: &HttpRequest, response: HttpResponse) -> HttpResponse {
+        let (Some(if_none_match), Some(etag)) =
+            (req.header("if-none-match"), response.headers.get("etag"))
+        else {
+            return response;
+        };
+        let matches = if_none_match.split(',').any(|tag| {
+            let tag = tag.trim();
+            tag == "*" || tag.trim_start_matches("W/") == etag.trim_start_matches("W/")
+        });
+        if response.status_code != 200 || !matches {
+            return response;
+        }
+
+        let mut not_modified = HttpResponse::not_modified();
+        for header in ["etag", "cache-control"] {
+            if let Some(value) = response.headers.get(header) {
+                not_modified = not_modified.with_header(header, value.clone());
+            }
         }
-        // Must be a JSON-RPC request
-        if req.headers.get("content-type").map(|s| s.as_ref()) != Some("application/json") {
+
+        not_modified
+    }
+
+    /// Handles a JSON-RPC request, which must have a JSON content type
+    fn handle_json_rpc(req: &HttpRequest) -> HttpResponse {
+        let is_json = req
+            .header("content-type")
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+        if !is_json {
             return HttpResponse::bad_request(
                 "expected content-type: application/json".to_string(),
             );
         }
-        let method = match req.decode_method() {
-            Ok(request) => request,
-            Err(response) => return response,
-        };
 
-        match method.as_str() {
-            "dip721_metadata" => Self::dip721_metadata(),
-            "dip721_name" => Self::dip721_name(),
-            "dip721_symbol" => Self::dip721_symbol(),
-            "dip721_logo" => Self::dip721_logo(),
-            "dip721_total_unique_holders" => Self::dip721_total_unique_holders(),
-            "dip721_token_metadata" => Self::dip721_token_metadata(req),
-            "dip721_total_supply" => Self::dip721_total_supply(),
-            _ => HttpResponse::bad_request("unknown method".to_string()),
-        }
+        JsonRpcApi::handle(req)
     }
+}
 
-    fn dip721_metadata() -> HttpResponse {
-        HttpResponse::ok(App::dip721_metadata())
-    }
+#[cfg(test)]
+mod test {
 
-    fn dip721_name() -> HttpResponse {
-        HttpResponse::ok(App::dip721_name())
-    }
+    use pretty_assertions::assert_eq;
 
-    fn dip721_symbol() -> HttpResponse {
-        HttpResponse::ok(App::dip721_symbol())
-    }
+    use super::*;
+
+    #[tokio::test]
+    async fn test_should_fallback_to_json_rpc() {
+        let mut req =
+            HttpRequest::new(br#"{"jsonrpc":"2.0","method":"dip721_total_supply","id":1}"#);
+        req.method = "GET".into();
+        req.url = "/".to_string();
+        let response = HttpApi::handle_http_request(req).await;
+        assert_eq!(response.status_code, 200);
 
-    fn dip721_logo() -> HttpResponse {
-        HttpResponse::ok(App::dip721_logo())
+        let mut req =
+            HttpRequest::new(br#"{"jsonrpc":"2.0","method":"dip721_total_supply","id":1}"#);
+        req.method = "GET".into();
+        req.url = "/unknown".to_string();
+        req.headers.clear();
+        let response = HttpApi::handle_http_request(req).await;
+        assert_eq!(response.status_code, 400);
     }
 
-    fn dip721_total_unique_holders() -> HttpResponse {
-        HttpResponse::ok(App::dip721_total_unique_holders())
+    #[tokio::test]
+    async fn test_should_honour_if_none_match() {
+        let req = |if_none_match: &'static str| HttpRequest {
+            method: "GET".into(),
+            url: "/logo".to_string(),
+            headers: [("if-none-match".into(), if_none_match.into())].into(),
+            body: Default::default(),
+        };
+        let response = HttpResponse::ok_raw("text/plain".to_string(), b"hello".to_vec())
+            .with_header("etag", "\"abc\"")
+            .with_header("cache-control", "public, max-age=3600");
+
+        let not_modified = HttpApi::not_modified_if_none_match(&req("\"abc\""), response.clone());
+        assert_eq!(not_modified.status_code, 304);
+        assert!(not_modified.body.is_empty());
+        assert_eq!(not_modified.headers.get("etag").unwrap(), "\"abc\"");
+
+        let not_modified =
+            HttpApi::not_modified_if_none_match(&req("\"foo\", W/\"abc\""), response.clone());
+        assert_eq!(not_modified.status_code, 304);
+
+        let modified = HttpApi::not_modified_if_none_match(&req("\"foo\""), response);
+        assert_eq!(modified.status_code, 200);
     }
 
-    fn dip721_token_metadata(req: HttpRequest) -> HttpResponse {
-        let params = match req.decode_body::<TokenIdentifierReq>() {
-            Ok(request) => request,
-            Err(response) => return response,
+    #[tokio::test]
+    async fn test_should_not_modify_burned_token() {
+        use crate::app::storage::TokensStorage;
+        use crate::did::DEFAULT_COLLECTION_ID;
+
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).unwrap();
+        let req = |url: &str, if_none_match: Option<String>| HttpRequest {
+            method: "GET".into(),
+            url: url.to_string(),
+            headers: if_none_match
+                .map(|etag| ("if-none-match".into(), etag.into()))
+                .into_iter()
+                .collect(),
+            body: Default::default(),
         };
-        App::dip721_token_metadata(params.id)
-            .map(HttpResponse::ok)
-            .unwrap_or_else(|_| HttpResponse::not_found())
+
+        for url in ["/tokens/1", "/token/1.json"] {
+            let response = HttpApi::handle_http_request(req(url, None)).await;
+            assert_eq!(response.status_code, 200);
+            let etag = response.headers.get("etag").unwrap().to_string();
+
+            let response = HttpApi::handle_http_request(req(url, Some(etag.clone()))).await;
+            assert_eq!(response.status_code, 304);
+            assert_eq!(response.headers.get("etag").unwrap(), etag.as_str());
+        }
     }
 
-    fn dip721_total_supply() -> HttpResponse {
-        HttpResponse::ok(App::dip721_total_supply())
+    #[tokio::test]
+    async fn test_should_upgrade_post_requests() {
+        let mut req =
+            HttpRequest::new(br#"{"jsonrpc":"2.0","method":"dip721_total_supply","id":1}"#);
+        req.url = "/".to_string();
+        let response = HttpApi::handle_http_request(req.clone()).await;
+        assert_eq!(response.upgrade, Some(true));
+
+        // JSON-RPC is served by the update call too
+        let response = HttpApi::handle_http_request_update(req.clone()).await;
+        assert_eq!(response.status_code, 200);
+
+        req.method = "GET".into();
+        let response = HttpApi::handle_http_request_update(req).await;
+        assert_eq!(response.status_code, 400);
     }
 }
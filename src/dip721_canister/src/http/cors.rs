@@ -0,0 +1,130 @@
+use crate::app::App;
+use crate::did::{HttpRequest, HttpResponse};
+
+const ALLOWED_METHODS: &str = "GET, POST, OPTIONS";
+const ALLOWED_HEADERS: &str = "content-type, range, if-none-match";
+const EXPOSED_HEADERS: &str = "accept-ranges, content-range, etag";
+/// How long browsers may cache the preflight response, in seconds
+const PREFLIGHT_MAX_AGE: &str = "86400";
+
+/// Cross-origin resource sharing, for the origins allowed in the configuration
+pub struct Cors;
+
+impl Cors {
+    /// Responds to an `OPTIONS` preflight request.
+    ///
+    /// The access control headers are only set if the request origin is allowed.
+    pub fn preflight(req: &HttpRequest) -> HttpResponse {
+        let response = HttpResponse::no_content().with_header("vary", "origin");
+        let Some(origin) = Self::allowed_origin(req) else {
+            return response;
+        };
+        let allowed_headers = req
+            .header("access-control-request-headers")
+            .unwrap_or(ALLOWED_HEADERS)
+            .to_string();
+
+        response
+            .with_header("access-control-allow-origin", origin)
+            .with_header("access-control-allow-methods", ALLOWED_METHODS)
+            .with_header("access-control-allow-headers", allowed_headers)
+            .with_header("access-control-max-age", PREFLIGHT_MAX_AGE)
+    }
+
+    /// Sets the access control headers on the response if the request origin is allowed
+    pub fn apply(req: &HttpRequest, response: HttpResponse) -> HttpResponse {
+        let response = response.with_header("vary", "origin");
+        match Self::allowed_origin(req) {
+            Some(origin) => response
+                .with_header("access-control-allow-origin", origin)
+                .with_header("access-control-expose-headers", EXPOSED_HEADERS),
+            None => response,
+        }
+    }
+
+    fn allowed_origin(req: &HttpRequest) -> Option<String> {
+        req.header("origin")
+            .filter(|origin| App::is_allowed_origin(origin))
+            .map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+    use serde_bytes::ByteBuf;
+
+    use super::*;
+    use crate::did::CanisterInitData;
+
+    fn request(method: &'static str, headers: &[(&'static str, &'static str)]) -> HttpRequest {
+        HttpRequest {
+            method: method.into(),
+            url: "/metadata".to_string(),
+            headers: headers
+                .iter()
+                .map(|(key, value)| ((*key).into(), (*value).into()))
+                .collect::<HashMap<_, _>>(),
+            body: ByteBuf::default(),
+        }
+    }
+
+    fn allow_origins(origins: &[&str]) {
+        App::init(CanisterInitData {
+            custodians: vec![crate::utils::caller()],
+            supported_interfaces: vec![],
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            logo: None,
+            provenance_hash: None,
+        });
+        App::set_allowed_origins(origins.iter().map(|origin| origin.to_string()).collect());
+    }
+
+    #[test]
+    fn test_should_respond_to_preflight() {
+        allow_origins(&["https://example.com"]);
+        let response = Cors::preflight(&request(
+            "OPTIONS",
+            &[
+                ("origin", "https://example.com"),
+                ("access-control-request-headers", "content-type"),
+            ],
+        ));
+        assert_eq!(response.status_code, 204);
+        assert_eq!(
+            response.headers.get("access-control-allow-origin").unwrap(),
+            "https://example.com"
+        );
+        assert_eq!(
+            response
+                .headers
+                .get("access-control-allow-headers")
+                .unwrap(),
+            "content-type"
+        );
+
+        let response = Cors::preflight(&request("OPTIONS", &[("origin", "https://example.org")]));
+        assert_eq!(response.status_code, 204);
+        assert!(!response.headers.contains_key("access-control-allow-origin"));
+    }
+
+    #[test]
+    fn test_should_apply_cors_headers() {
+        allow_origins(&["*"]);
+        let response = Cors::apply(
+            &request("GET", &[("origin", "https://example.org")]),
+            HttpResponse::not_found(),
+        );
+        assert_eq!(
+            response.headers.get("access-control-allow-origin").unwrap(),
+            "https://example.org"
+        );
+
+        let response = Cors::apply(&request("GET", &[]), HttpResponse::not_found());
+        assert!(!response.headers.contains_key("access-control-allow-origin"));
+    }
+}
@@ -0,0 +1,519 @@
+//! # JSON-RPC
+//!
+//! JSON-RPC 2.0 interface to the canister query methods
+
+use candid::{Nat, Principal};
+use dip721_rs::{Dip721 as _, NftError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::app::App;
+use crate::did::{Account, HttpRequest, HttpResponse};
+
+const JSON_RPC_VERSION: &str = "2.0";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// Error returned by the called method; the [`NftError`] is reported in the error data
+const NFT_ERROR: i64 = -32000;
+
+/// JSON-RPC error object
+#[derive(Debug, Serialize, PartialEq)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+}
+
+impl From<NftError> for JsonRpcError {
+    fn from(err: NftError) -> Self {
+        Self {
+            code: NFT_ERROR,
+            message: err.to_string(),
+            data: serde_json::to_value(err).ok(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum JsonRpcOutcome {
+    Result(Value),
+    Error(JsonRpcError),
+}
+
+/// JSON-RPC response object
+#[derive(Debug, Serialize, PartialEq)]
+struct JsonRpcResponse {
+    jsonrpc: &'static str,
+    #[serde(flatten)]
+    outcome: JsonRpcOutcome,
+    id: Value,
+}
+
+impl JsonRpcResponse {
+    fn new(id: Value, outcome: Result<Value, JsonRpcError>) -> Self {
+        Self {
+            jsonrpc: JSON_RPC_VERSION,
+            outcome: match outcome {
+                Ok(result) => JsonRpcOutcome::Result(result),
+                Err(err) => JsonRpcOutcome::Error(err),
+            },
+            id,
+        }
+    }
+}
+
+/// Method params, which can be either positional or named
+struct Params<'a>(Option<&'a Value>);
+
+impl Params<'_> {
+    /// Get the param at `index` if params are positional, or the one called `name` if they are named
+    fn get<T>(&self, index: usize, name: &str) -> Result<T, JsonRpcError>
+    where
+        T: DeserializeOwned,
+    {
+        let param = match self.0 {
+            Some(Value::Array(params)) => params.get(index),
+            Some(Value::Object(params)) => params.get(name),
+            _ => None,
+        }
+        .ok_or_else(|| JsonRpcError::new(INVALID_PARAMS, format!("missing param {name}")))?;
+
+        serde_json::from_value(param.clone()).map_err(|err| {
+            JsonRpcError::new(INVALID_PARAMS, format!("invalid param {name}: {err}"))
+        })
+    }
+}
+
+pub struct JsonRpcApi;
+
+impl JsonRpcApi {
+    /// Handles a JSON-RPC request body, either a single call or a batch.
+    ///
+    /// Notifications are executed but not answered; if there is nothing to answer the response has no content.
+    pub fn handle(req: &HttpRequest) -> HttpResponse {
+        let body = match serde_json::from_slice::<Value>(&req.body) {
+            Ok(body) => body,
+            Err(err) => {
+                return HttpResponse::ok(JsonRpcResponse::new(
+                    Value::Null,
+                    Err(JsonRpcError::new(PARSE_ERROR, err)),
+                ))
+            }
+        };
+
+        match body {
+            Value::Array(batch) if batch.is_empty() => HttpResponse::ok(JsonRpcResponse::new(
+                Value::Null,
+                Err(JsonRpcError::new(INVALID_REQUEST, "empty batch")),
+            )),
+            Value::Array(batch) => {
+                let responses = batch
+                    .iter()
+                    .filter_map(Self::handle_call)
+                    .collect::<Vec<_>>();
+                if responses.is_empty() {
+                    HttpResponse::no_content()
+                } else {
+                    HttpResponse::ok(responses)
+                }
+            }
+            call => match Self::handle_call(&call) {
+                Some(response) => HttpResponse::ok(response),
+                None => HttpResponse::no_content(),
+            },
+        }
+    }
+
+    /// Handles a single call; returns `None` for notifications
+    fn handle_call(call: &Value) -> Option<JsonRpcResponse> {
+        let Some(call) = call.as_object() else {
+            return Some(JsonRpcResponse::new(
+                Value::Null,
+                Err(JsonRpcError::new(INVALID_REQUEST, "expected object")),
+            ));
+        };
+        // a call without id is a notification
+        let id = call.get("id").cloned();
+        let response_id = id.clone().unwrap_or_default();
+
+        let valid_id = matches!(
+            id,
+            None | Some(Value::Null | Value::Number(_) | Value::String(_))
+        );
+        let method = match (call.get("jsonrpc"), call.get("method"), valid_id) {
+            (Some(Value::String(version)), Some(Value::String(method)), true)
+                if version == JSON_RPC_VERSION =>
+            {
+                method
+            }
+            _ => {
+                return Some(JsonRpcResponse::new(
+                    response_id,
+                    Err(JsonRpcError::new(INVALID_REQUEST, "invalid request")),
+                ))
+            }
+        };
+
+        let outcome = Self::call(method, Params(call.get("params")));
+
+        id.map(|_| JsonRpcResponse::new(response_id, outcome))
+    }
+
+    fn call(method: &str, params: Params) -> Result<Value, JsonRpcError> {
+        match method {
+            "dip721_metadata" => Self::result(App::dip721_metadata()),
+            "dip721_stats" => Self::result(App::dip721_stats()),
+            "dip721_logo" => Self::result(App::dip721_logo()),
+            "dip721_name" => Self::result(App::dip721_name()),
+            "dip721_symbol" => Self::result(App::dip721_symbol()),
+            "dip721_custodians" => Self::result(App::dip721_custodians()),
+            "dip721_cycles" => Self::result(App::dip721_cycles()),
+            "dip721_total_unique_holders" => Self::result(App::dip721_total_unique_holders()),
+            "dip721_token_metadata" => Self::nft_result(App::dip721_token_metadata(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "dip721_balance_of" => {
+                Self::nft_result(App::dip721_balance_of(params.get::<Principal>(0, "owner")?))
+            }
+            "dip721_owner_of" => Self::nft_result(App::dip721_owner_of(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "dip721_owner_token_identifiers" => Self::nft_result(
+                App::dip721_owner_token_identifiers(params.get::<Principal>(0, "owner")?),
+            ),
+            "dip721_owner_token_metadata" => Self::nft_result(App::dip721_owner_token_metadata(
+                params.get::<Principal>(0, "owner")?,
+            )),
+            "dip721_operator_of" => Self::nft_result(App::dip721_operator_of(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "dip721_operator_token_identifiers" => Self::nft_result(
+                App::dip721_operator_token_identifiers(params.get::<Principal>(0, "operator")?),
+            ),
+            "dip721_operator_token_metadata" => Self::nft_result(
+                App::dip721_operator_token_metadata(params.get::<Principal>(0, "operator")?),
+            ),
+            "dip721_supported_interfaces" => Self::result(App::dip721_supported_interfaces()),
+            "dip721_total_supply" => Self::result(App::dip721_total_supply()),
+            "dip721_transaction" => {
+                Self::nft_result(App::dip721_transaction(params.get::<Nat>(0, "tx_id")?))
+            }
+            "dip721_total_transactions" => Self::result(App::dip721_total_transactions()),
+            "get_asset" => Self::result(App::get_asset(params.get::<u64>(0, "id")?)),
+            "get_token_json_mapping" => Self::result(App::get_token_json_mapping()),
+            "get_collection_royalty" => Self::result(App::get_collection_royalty()),
+            "get_token_royalty" => Self::result(App::get_token_royalty(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "royalty_info" => Self::nft_result(App::royalty_info(
+                params.get::<Nat>(0, "token_identifier")?,
+                params.get::<Nat>(1, "sale_price")?,
+            )),
+            "get_marketplace_settings" => Self::result(App::get_marketplace_settings()),
+            "get_listing" => {
+                Self::result(App::get_listing(params.get::<Nat>(0, "token_identifier")?))
+            }
+            "get_listings" => Self::result(App::get_listings()),
+            "get_auction" => {
+                Self::result(App::get_auction(params.get::<Nat>(0, "token_identifier")?))
+            }
+            "get_auctions" => Self::result(App::get_auctions()),
+            "get_offer" => Self::result(App::get_offer(params.get::<u64>(0, "id")?)),
+            "get_token_offers" => Self::result(App::get_token_offers(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "get_swap" => Self::result(App::get_swap(params.get::<u64>(0, "id")?)),
+            "get_swaps" => Self::result(App::get_swaps(params.get::<Principal>(0, "principal")?)),
+            "get_proceeds" => Self::result(App::get_proceeds(
+                params.get::<Principal>(0, "ledger")?,
+                params.get::<Principal>(1, "owner")?,
+            )),
+            "get_mint_sale" => Self::result(App::get_mint_sale()),
+            "get_sale_minted" => Self::result(App::get_sale_minted()),
+            "get_sale_minted_by" => Self::result(App::get_sale_minted_by(
+                params.get::<Principal>(0, "owner")?,
+            )),
+            "get_allowlist_phases" => Self::result(App::get_allowlist_phases()),
+            "get_allowlist_minted" => Self::result(App::get_allowlist_minted(
+                params.get::<u32>(0, "phase_id")?,
+                params.get::<Principal>(1, "owner")?,
+            )),
+            "user_of" => Self::nft_result(App::user_of(params.get::<Nat>(0, "token_identifier")?)),
+            "user_expires" => {
+                Self::nft_result(App::user_expires(params.get::<Nat>(0, "token_identifier")?))
+            }
+            "get_edition_templates" => Self::result(App::get_edition_templates()),
+            "get_edition_count" => {
+                Self::nft_result(App::get_edition_count(params.get::<u64>(0, "template_id")?))
+            }
+            "get_token_edition" => Self::nft_result(App::get_token_edition(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "get_collection" => {
+                Self::result(App::get_collection(params.get::<u64>(0, "collection_id")?))
+            }
+            "get_collections" => Self::result(App::get_collections()),
+            "collection_token_metadata" => Self::nft_result(App::collection_token_metadata(
+                params.get::<u64>(0, "collection_id")?,
+                params.get::<Nat>(1, "token_identifier")?,
+            )),
+            "collection_owner_of" => Self::nft_result(App::collection_owner_of(
+                params.get::<u64>(0, "collection_id")?,
+                params.get::<Nat>(1, "token_identifier")?,
+            )),
+            "collection_owner_token_identifiers" => {
+                Self::nft_result(App::collection_owner_token_identifiers(
+                    params.get::<u64>(0, "collection_id")?,
+                    params.get::<Principal>(1, "owner")?,
+                ))
+            }
+            "collection_balance_of" => Self::nft_result(App::collection_balance_of(
+                params.get::<u64>(0, "collection_id")?,
+                params.get::<Principal>(1, "owner")?,
+            )),
+            "collection_total_supply" => Self::nft_result(App::collection_total_supply(
+                params.get::<u64>(0, "collection_id")?,
+            )),
+            "get_token_children" => Self::nft_result(App::get_token_children(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "root_owner_of" => Self::nft_result(App::root_owner_of(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "account_balance_of" => Self::nft_result(App::account_balance_of(
+                params.get::<Account>(0, "account")?,
+            )),
+            "account_token_identifiers" => Self::nft_result(App::account_token_identifiers(
+                params.get::<Account>(0, "account")?,
+            )),
+            "account_of" => {
+                Self::nft_result(App::account_of(params.get::<Nat>(0, "token_identifier")?))
+            }
+            "ext_token_identifier_of" => Self::nft_result(App::ext_token_identifier_of(
+                params.get::<Nat>(0, "token_identifier")?,
+            )),
+            "bearer" => Self::result(App::ext_bearer(params.get::<String>(0, "token")?)),
+            "getRegistry" => Self::result(App::ext_get_registry()),
+            "getTokens" => Self::result(App::ext_get_tokens()),
+            "tokens_ext" => Self::result(App::ext_tokens(
+                params.get::<String>(0, "account_identifier")?,
+            )),
+            "get_random_pool" => Self::result(App::get_random_pool()),
+            "get_provenance" => Self::result(App::get_provenance()),
+            "verify_reveal" => Self::nft_result(App::verify_reveal()),
+            "get_http_minters" => Self::result(App::get_http_minters()),
+            "get_allowed_origins" => Self::result(App::get_allowed_origins()),
+            "get_http_base_url" => Self::result(App::get_http_base_url()),
+            "get_voucher_signers" => Self::result(App::get_voucher_signers()),
+            _ => Err(JsonRpcError::new(METHOD_NOT_FOUND, "method not found")),
+        }
+    }
+
+    fn result<T>(value: T) -> Result<Value, JsonRpcError>
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(value).map_err(|err| JsonRpcError::new(INTERNAL_ERROR, err))
+    }
+
+    fn nft_result<T>(result: Result<T, NftError>) -> Result<Value, JsonRpcError>
+    where
+        T: Serialize,
+    {
+        Self::result(result?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use dip721_rs::SupportedInterface;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::app::storage::{ProceedsStorage, TokensStorage};
+    use crate::did::{CanisterInitData, DEFAULT_COLLECTION_ID};
+
+    fn init_canister() {
+        App::init(CanisterInitData {
+            custodians: vec![crate::utils::caller()],
+            supported_interfaces: vec![SupportedInterface::TransactionHistory],
+            name: "nft".to_string(),
+            symbol: "NFT".to_string(),
+            logo: None,
+            provenance_hash: None,
+        });
+    }
+
+    fn call(body: Value) -> HttpResponse {
+        JsonRpcApi::handle(&HttpRequest::new(body.to_string().as_bytes()))
+    }
+
+    fn response_body(response: &HttpResponse) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    #[test]
+    fn test_should_call_method() {
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        let response = call(json!({
+            "jsonrpc": "2.0",
+            "method": "dip721_owner_of",
+            "params": [1],
+            "id": 1
+        }));
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response_body(&response),
+            json!({
+                "jsonrpc": "2.0",
+                "result": crate::utils::caller().to_text(),
+                "id": 1
+            })
+        );
+
+        // named params
+        let response = call(json!({
+            "jsonrpc": "2.0",
+            "method": "dip721_balance_of",
+            "params": { "owner": crate::utils::caller().to_text() },
+            "id": "a"
+        }));
+        let body = response_body(&response);
+        assert!(body.get("result").is_some());
+        assert_eq!(body["id"], "a");
+    }
+
+    #[test]
+    fn test_should_get_proceeds() {
+        ProceedsStorage::credit(
+            Principal::management_canister(),
+            crate::utils::caller(),
+            100_u64.into(),
+        );
+        let response = call(json!({
+            "jsonrpc": "2.0",
+            "method": "get_proceeds",
+            "params": {
+                "ledger": Principal::management_canister().to_text(),
+                "owner": crate::utils::caller().to_text()
+            },
+            "id": 1
+        }));
+        assert_eq!(
+            response_body(&response)["result"],
+            serde_json::to_value(Nat::from(100_u64)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_should_return_null_result() {
+        let response = call(json!({ "jsonrpc": "2.0", "method": "dip721_logo", "id": 1 }));
+        assert_eq!(
+            response_body(&response),
+            json!({ "jsonrpc": "2.0", "result": null, "id": 1 })
+        );
+    }
+
+    #[test]
+    fn test_should_get_configuration() {
+        init_canister();
+        App::set_http_minters(vec![Principal::management_canister()]);
+        let response = call(json!({ "jsonrpc": "2.0", "method": "get_http_minters", "id": 1 }));
+        assert_eq!(
+            response_body(&response)["result"],
+            json!([Principal::management_canister().to_text()])
+        );
+
+        App::set_allowed_origins(vec!["https://example.com".to_string()]);
+        let response = call(json!({ "jsonrpc": "2.0", "method": "get_allowed_origins", "id": 1 }));
+        assert_eq!(
+            response_body(&response)["result"],
+            json!(["https://example.com"])
+        );
+
+        App::set_voucher_signers(vec![Principal::anonymous()]);
+        let response = call(json!({ "jsonrpc": "2.0", "method": "get_voucher_signers", "id": 1 }));
+        assert_eq!(
+            response_body(&response)["result"],
+            json!([Principal::anonymous().to_text()])
+        );
+    }
+
+    #[test]
+    fn test_should_return_errors() {
+        let response = JsonRpcApi::handle(&HttpRequest::new(b"{"));
+        assert_eq!(response_body(&response)["error"]["code"], PARSE_ERROR);
+
+        let response = call(json!({ "method": "dip721_logo", "id": 1 }));
+        assert_eq!(response_body(&response)["error"]["code"], INVALID_REQUEST);
+
+        let response = call(json!({ "jsonrpc": "2.0", "method": "foo", "id": 1 }));
+        assert_eq!(response_body(&response)["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = call(json!({
+            "jsonrpc": "2.0",
+            "method": "dip721_owner_of",
+            "params": ["foo"],
+            "id": 1
+        }));
+        assert_eq!(response_body(&response)["error"]["code"], INVALID_PARAMS);
+
+        let response = call(json!({
+            "jsonrpc": "2.0",
+            "method": "dip721_owner_of",
+            "params": [5],
+            "id": 1
+        }));
+        let body = response_body(&response);
+        assert_eq!(body["error"]["code"], NFT_ERROR);
+        assert_eq!(body["error"]["data"], "TokenNotFound");
+    }
+
+    #[test]
+    fn test_should_handle_batch_and_notifications() {
+        let response = call(json!([
+            { "jsonrpc": "2.0", "method": "dip721_name", "id": 1 },
+            { "jsonrpc": "2.0", "method": "dip721_symbol" },
+            { "jsonrpc": "2.0", "method": "foo", "id": 2 },
+            1
+        ]));
+        let body = response_body(&response);
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[2]["error"]["code"], INVALID_REQUEST);
+
+        let response = call(json!({ "jsonrpc": "2.0", "method": "dip721_name" }));
+        assert_eq!(response.status_code, 204);
+        assert!(response.body.is_empty());
+
+        let response = call(json!([]));
+        assert_eq!(response_body(&response)["error"]["code"], INVALID_REQUEST);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::Write as _;
+
+use dip721_rs::Dip721 as _;
+use num_traits::ToPrimitive as _;
+
+use crate::app::App;
+use crate::did::HttpResponse;
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Canister metrics in the Prometheus text format
+pub struct Prometheus {
+    text: String,
+}
+
+impl Prometheus {
+    /// Renders the canister metrics
+    pub fn serve() -> HttpResponse {
+        let mut metrics = Self {
+            text: String::new(),
+        };
+
+        metrics.gauge(
+            "dip721_cycles_balance",
+            "Cycles balance of the canister",
+            Self::nat(App::dip721_cycles()),
+        );
+        metrics.header(
+            "dip721_stable_memory_pages",
+            "gauge",
+            "Stable memory pages used by each memory",
+        );
+        for (memory, pages) in App::memory_pages() {
+            metrics.sample(
+                "dip721_stable_memory_pages",
+                &[("memory", memory)],
+                pages as f64,
+            );
+        }
+        metrics.gauge(
+            "dip721_heap_memory_bytes",
+            "Heap memory size in bytes",
+            crate::utils::heap_size() as f64,
+        );
+        metrics.gauge(
+            "dip721_total_supply",
+            "Amount of minted tokens, including burned ones",
+            Self::nat(App::dip721_total_supply()),
+        );
+        metrics.gauge(
+            "dip721_burned_tokens",
+            "Amount of burned tokens",
+            App::total_burned() as f64,
+        );
+        metrics.gauge(
+            "dip721_unique_holders",
+            "Amount of unique token holders",
+            Self::nat(App::dip721_total_unique_holders()),
+        );
+        metrics.gauge(
+            "dip721_transactions",
+            "Amount of registered transactions",
+            Self::nat(App::dip721_total_transactions()),
+        );
+
+        metrics.header(
+            "dip721_calls_total",
+            "counter",
+            "Update calls served since the last upgrade, by method",
+        );
+        for (method, count) in App::call_metrics() {
+            metrics.sample("dip721_calls_total", &[("method", method)], count as f64);
+        }
+        metrics.header(
+            "dip721_errors_total",
+            "counter",
+            "Errors returned by update calls since the last upgrade, by method and error",
+        );
+        for (method, error, count) in App::error_metrics() {
+            metrics.sample(
+                "dip721_errors_total",
+                &[("method", method), ("error", error)],
+                count as f64,
+            );
+        }
+
+        HttpResponse::ok_raw(
+            PROMETHEUS_CONTENT_TYPE.to_string(),
+            metrics.text.into_bytes(),
+        )
+        .with_header("cache-control", "no-store")
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.header(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    fn header(&mut self, name: &str, metric_type: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {metric_type}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let labels = labels
+            .iter()
+            .map(|(key, value)| format!("{key}=\"{value}\""))
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            let _ = writeln!(self.text, "{name} {value}");
+        } else {
+            let _ = writeln!(self.text, "{name}{{{}}} {value}", labels.join(","));
+        }
+    }
+
+    fn nat(value: candid::Nat) -> f64 {
+        value.0.to_f64().unwrap_or(f64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::storage::TokensStorage;
+    use crate::did::DEFAULT_COLLECTION_ID;
+
+    #[test]
+    fn test_should_serve_metrics() {
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            2_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &2_u64.into()).unwrap();
+        App::metered::<()>("dip721_burn", Err(dip721_rs::NftError::TokenNotFound)).unwrap_err();
+
+        let response = Prometheus::serve();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.headers.get("content-type").unwrap(),
+            PROMETHEUS_CONTENT_TYPE
+        );
+        let text = String::from_utf8(response.body.into_vec()).unwrap();
+        assert!(text.contains("# TYPE dip721_total_supply gauge\ndip721_total_supply 2\n"));
+        assert!(text.contains("dip721_burned_tokens 1\n"));
+        assert!(text.contains("dip721_transactions 3\n"));
+        assert!(text.contains("dip721_stable_memory_pages{memory=\"tokens\"}"));
+        assert!(text.contains("dip721_calls_total{method=\"dip721_burn\"} 1\n"));
+        assert!(text
+            .contains("dip721_errors_total{method=\"dip721_burn\",error=\"TokenNotFound\"} 1\n"));
+    }
+}
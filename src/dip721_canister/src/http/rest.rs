@@ -0,0 +1,426 @@
+use std::str::FromStr as _;
+
+use candid::{Nat, Principal};
+use dip721_rs::{Dip721 as _, TokenMetadata};
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+
+use super::metrics::Prometheus;
+use super::token_json::TokenJson;
+use crate::app::App;
+use crate::did::{HttpRequest, HttpResponse};
+
+/// Maximum size of the body returned by a single HTTP response
+const MAX_RESPONSE_BODY_SIZE: u64 = 2 * 1024 * 1024;
+/// Default amount of items returned by a paginated route
+const DEFAULT_PAGE_LIMIT: usize = 50;
+/// Maximum amount of items returned by a paginated route
+const MAX_PAGE_LIMIT: usize = 100;
+
+/// Cache control of the resources which can't change anymore, such as transactions and burned tokens
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+/// Cache control of the resources which can change, cached as long as their etag still matches
+const REVALIDATE_CACHE_CONTROL: &str = "no-cache";
+
+/// A page of tokens
+#[derive(Serialize)]
+struct TokensPage {
+    total: usize,
+    offset: usize,
+    limit: usize,
+    tokens: Vec<TokenMetadata>,
+}
+
+/// Path-based HTTP routes, addressable by browsers and plain HTTP clients
+pub struct RestApi;
+
+impl RestApi {
+    /// Routes the request by its URL path; returns `None` if no route matches the path
+    pub fn route(req: &HttpRequest) -> Option<HttpResponse> {
+        let path = req.path().trim_matches('/');
+        let segments = path.split('/').collect::<Vec<_>>();
+
+        let response = match segments.as_slice() {
+            ["logo"] => Self::serve_logo(),
+            ["assets", asset_id] => Self::serve_asset(req, asset_id),
+            ["metadata"] => HttpResponse::ok(App::dip721_metadata()),
+            ["stats"] => HttpResponse::ok(App::dip721_stats()),
+            ["metrics"] => Prometheus::serve(),
+            ["tokens", token_id] => Self::token(token_id),
+            ["token", file] if file.ends_with(".json") => {
+                Self::token_json(file.trim_end_matches(".json"))
+            }
+            ["owners", owner, "tokens"] => Self::owner_tokens(req, owner),
+            ["transactions", tx_id] => Self::transaction(tx_id),
+            _ => return None,
+        };
+
+        Some(response)
+    }
+
+    /// Serves the canister logo
+    fn serve_logo() -> HttpResponse {
+        match App::get_logo() {
+            Some(logo) => {
+                let etag = format!("\"{}\"", hex::encode(Sha256::digest(&logo.data)));
+                HttpResponse::ok_raw(logo.content_type, logo.data.into_vec())
+                    .with_header("cache-control", "public, max-age=3600")
+                    .with_header("etag", etag)
+            }
+            None => HttpResponse::not_found(),
+        }
+    }
+
+    /// Serves the content of a finalized asset, honouring the `range` header.
+    ///
+    /// Assets bigger than [`MAX_RESPONSE_BODY_SIZE`] are served partially, so clients must request the remaining ranges.
+    fn serve_asset(req: &HttpRequest, asset_id: &str) -> HttpResponse {
+        let asset = match asset_id.parse().ok().and_then(App::get_asset) {
+            Some(asset) if asset.finalized => asset,
+            _ => return HttpResponse::not_found(),
+        };
+
+        let range = req.header("range");
+        let (start, end) = match range.map(|range| Self::parse_range(range, asset.size)) {
+            Some(Some(range)) => range,
+            Some(None) => return HttpResponse::range_not_satisfiable(asset.size),
+            None => (0, asset.size),
+        };
+        let end = end.min(start + MAX_RESPONSE_BODY_SIZE);
+
+        let body = match App::read_asset(asset.id, start, end) {
+            Ok(body) => body,
+            Err(err) => return HttpResponse::internal_error(err.to_string()),
+        };
+        let response = if range.is_none() && end == asset.size {
+            HttpResponse::ok_raw(asset.content_type, body)
+        } else {
+            HttpResponse::partial_content(asset.content_type, body, start, end, asset.size)
+        };
+
+        response
+            .with_header("accept-ranges", "bytes")
+            .with_header("cache-control", "public, max-age=86400")
+            .with_header("etag", format!("\"{}\"", hex::encode(&asset.sha256)))
+    }
+
+    /// Parses a single `bytes=` range header into a `[start, end)` range of a resource of `size` bytes.
+    fn parse_range(range: &str, size: u64) -> Option<(u64, u64)> {
+        let (start, end) = range.trim().strip_prefix("bytes=")?.split_once('-')?;
+        let (start, end) = match (start.trim(), end.trim()) {
+            ("", suffix) => (size.saturating_sub(suffix.parse().ok()?), size),
+            (start, "") => (start.parse().ok()?, size),
+            (start, end) => (
+                start.parse().ok()?,
+                end.parse::<u64>().ok()?.saturating_add(1).min(size),
+            ),
+        };
+
+        (start < end).then_some((start, end))
+    }
+
+    fn token(token_id: &str) -> HttpResponse {
+        let Ok(token_id) = Nat::from_str(token_id) else {
+            return HttpResponse::bad_request("invalid token id".to_string());
+        };
+        match App::dip721_token_metadata(token_id) {
+            Ok(token) => Self::token_cache(token.is_burned, HttpResponse::ok(token)),
+            Err(_) => HttpResponse::not_found(),
+        }
+    }
+
+    /// Returns the marketplace metadata JSON of the token
+    fn token_json(token_id: &str) -> HttpResponse {
+        let Ok(token_id) = Nat::from_str(token_id) else {
+            return HttpResponse::bad_request("invalid token id".to_string());
+        };
+        match App::dip721_token_metadata(token_id) {
+            Ok(token) => Self::token_cache(
+                token.is_burned,
+                HttpResponse::ok(TokenJson::new(
+                    &token,
+                    &App::get_token_json_mapping(),
+                    App::dip721_name().as_deref(),
+                    App::royalty(&token.token_identifier),
+                )),
+            ),
+            Err(_) => HttpResponse::not_found(),
+        }
+    }
+
+    /// Returns a page of the tokens owned by `owner`, according to the `offset` and `limit` query parameters
+    fn owner_tokens(req: &HttpRequest, owner: &str) -> HttpResponse {
+        let Ok(owner) = Principal::from_text(owner) else {
+            return HttpResponse::bad_request("invalid principal".to_string());
+        };
+        let params = req.query_params();
+        let offset = match params.get("offset").map(|offset| offset.parse::<usize>()) {
+            Some(Ok(offset)) => offset,
+            Some(Err(_)) => return HttpResponse::bad_request("invalid offset".to_string()),
+            None => 0,
+        };
+        let limit = match params.get("limit").map(|limit| limit.parse::<usize>()) {
+            Some(Ok(limit)) => limit.min(MAX_PAGE_LIMIT),
+            Some(Err(_)) => return HttpResponse::bad_request("invalid limit".to_string()),
+            None => DEFAULT_PAGE_LIMIT,
+        };
+
+        let token_ids = App::dip721_owner_token_identifiers(owner).unwrap_or_default();
+        let mut tokens = Vec::with_capacity(limit);
+        for token_id in token_ids.iter().skip(offset).take(limit) {
+            match App::dip721_token_metadata(token_id.clone()) {
+                Ok(token) => tokens.push(token),
+                Err(err) => return HttpResponse::internal_error(err.to_string()),
+            }
+        }
+
+        HttpResponse::ok(TokensPage {
+            total: token_ids.len(),
+            offset,
+            limit,
+            tokens,
+        })
+    }
+
+    fn transaction(tx_id: &str) -> HttpResponse {
+        let Ok(tx_id) = Nat::from_str(tx_id) else {
+            return HttpResponse::bad_request("invalid transaction id".to_string());
+        };
+        App::dip721_transaction(tx_id)
+            .map(|tx| Self::immutable(HttpResponse::ok(tx)))
+            .unwrap_or_else(|_| HttpResponse::not_found())
+    }
+
+    /// Marks the response of a token as cacheable forever once burned, or until its etag changes otherwise
+    fn token_cache(is_burned: bool, response: HttpResponse) -> HttpResponse {
+        if is_burned {
+            Self::immutable(response)
+        } else {
+            Self::with_etag(response).with_header("cache-control", REVALIDATE_CACHE_CONTROL)
+        }
+    }
+
+    /// Marks the response as cacheable forever, with an etag of its body
+    fn immutable(response: HttpResponse) -> HttpResponse {
+        Self::with_etag(response).with_header("cache-control", IMMUTABLE_CACHE_CONTROL)
+    }
+
+    /// Tags the response with an etag of its body
+    fn with_etag(response: HttpResponse) -> HttpResponse {
+        let etag = format!("\"{}\"", hex::encode(Sha256::digest(&response.body)));
+        response.with_header("etag", etag)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashMap;
+
+    use dip721_rs::SupportedInterface;
+    use pretty_assertions::assert_eq;
+    use serde_bytes::ByteBuf;
+
+    use super::*;
+    use crate::app::storage::{AssetsStorage, TokensStorage};
+    use crate::did::{CanisterInitData, DEFAULT_COLLECTION_ID};
+
+    fn get(url: &str, headers: &[(&'static str, &'static str)]) -> HttpRequest {
+        HttpRequest {
+            method: "GET".into(),
+            url: url.to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| ((*k).into(), (*v).into()))
+                .collect::<HashMap<_, _>>(),
+            body: ByteBuf::default(),
+        }
+    }
+
+    fn init_canister() {
+        App::init(CanisterInitData {
+            custodians: vec![crate::utils::caller()],
+            supported_interfaces: vec![SupportedInterface::TransactionHistory],
+            name: "nft".to_string(),
+            symbol: "NFT".to_string(),
+            logo: None,
+            provenance_hash: None,
+        });
+    }
+
+    fn store_asset(content: &[u8]) -> u64 {
+        let id = AssetsStorage::create("text/plain".to_string(), Sha256::digest(content).into());
+        AssetsStorage::upload_chunk(id, content.to_vec()).unwrap();
+        AssetsStorage::finalize(id).unwrap();
+
+        id
+    }
+
+    #[test]
+    fn test_should_serve_asset() {
+        let id = store_asset(b"hello world");
+        let response = RestApi::route(&get(&format!("/assets/{id}"), &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_slice(), b"hello world");
+        assert_eq!(response.headers.get("content-type").unwrap(), "text/plain");
+        assert!(response.headers.contains_key("etag"));
+
+        let response = RestApi::route(&get("/assets/100", &[])).unwrap();
+        assert_eq!(response.status_code, 404);
+    }
+
+    #[test]
+    fn test_should_serve_asset_range() {
+        let id = store_asset(b"hello world");
+        let response =
+            RestApi::route(&get(&format!("/assets/{id}"), &[("Range", "bytes=6-")])).unwrap();
+        assert_eq!(response.status_code, 206);
+        assert_eq!(response.body.as_slice(), b"world");
+        assert_eq!(
+            response.headers.get("content-range").unwrap(),
+            "bytes 6-10/11"
+        );
+
+        let response =
+            RestApi::route(&get(&format!("/assets/{id}"), &[("range", "bytes=20-")])).unwrap();
+        assert_eq!(response.status_code, 416);
+    }
+
+    #[test]
+    fn test_should_serve_logo() {
+        let response = RestApi::route(&get("/logo", &[])).unwrap();
+        assert_eq!(response.status_code, 404);
+
+        init_canister();
+        App::set_logo("image/png".to_string(), ByteBuf::from(vec![0x89, b'P']));
+        let response = RestApi::route(&get("/logo", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_slice(), &[0x89, b'P']);
+        assert_eq!(response.headers.get("content-type").unwrap(), "image/png");
+    }
+
+    #[test]
+    fn test_should_not_route_unknown_path() {
+        assert!(RestApi::route(&get("/", &[])).is_none());
+        assert!(RestApi::route(&get("/tokens", &[])).is_none());
+        assert!(RestApi::route(&get("/foo/bar", &[])).is_none());
+    }
+
+    #[test]
+    fn test_should_get_metadata_and_stats() {
+        let response = RestApi::route(&get("/metadata", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        let response = RestApi::route(&get("/stats/", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+    }
+
+    #[test]
+    fn test_should_get_token() {
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        let response = RestApi::route(&get("/tokens/1", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.headers.get("cache-control").unwrap(),
+            REVALIDATE_CACHE_CONTROL
+        );
+        let live_etag = response.headers.get("etag").unwrap().clone();
+
+        TokensStorage::burn(DEFAULT_COLLECTION_ID, &1_u64.into()).unwrap();
+        let response = RestApi::route(&get("/tokens/1", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.headers.get("cache-control").unwrap(),
+            IMMUTABLE_CACHE_CONTROL
+        );
+        assert_ne!(response.headers.get("etag").unwrap(), &live_etag);
+
+        let response = RestApi::route(&get("/tokens/2", &[])).unwrap();
+        assert_eq!(response.status_code, 404);
+        let response = RestApi::route(&get("/tokens/abc", &[])).unwrap();
+        assert_eq!(response.status_code, 400);
+    }
+
+    #[test]
+    fn test_should_get_token_json() {
+        init_canister();
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![(
+                "name".to_string(),
+                dip721_rs::GenericValue::TextContent("Sword".to_string()),
+            )],
+        )
+        .unwrap();
+        let response = RestApi::route(&get("/token/1.json", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        let json: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(json["name"], "Sword");
+        assert_eq!(json["attributes"], serde_json::json!([]));
+
+        let response = RestApi::route(&get("/token/2.json", &[])).unwrap();
+        assert_eq!(response.status_code, 404);
+        assert!(RestApi::route(&get("/token/1", &[])).is_none());
+    }
+
+    #[test]
+    fn test_should_get_owner_tokens_page() {
+        let owner = crate::utils::caller();
+        for id in 1..=5_u64 {
+            TokensStorage::mint(DEFAULT_COLLECTION_ID, owner, id.into(), vec![]).unwrap();
+        }
+        let response = RestApi::route(&get(
+            &format!("/owners/{owner}/tokens?offset=1&limit=3"),
+            &[],
+        ))
+        .unwrap();
+        assert_eq!(response.status_code, 200);
+        let page: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(page["total"], 5);
+        assert_eq!(page["offset"], 1);
+        assert_eq!(page["tokens"].as_array().unwrap().len(), 3);
+
+        let response =
+            RestApi::route(&get(&format!("/owners/{owner}/tokens?limit=foo"), &[])).unwrap();
+        assert_eq!(response.status_code, 400);
+        let response = RestApi::route(&get("/owners/foo/tokens", &[])).unwrap();
+        assert_eq!(response.status_code, 400);
+    }
+
+    #[test]
+    fn test_should_get_transaction() {
+        init_canister();
+        TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            crate::utils::caller(),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        let response = RestApi::route(&get("/transactions/0", &[])).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.headers.get("cache-control").unwrap(),
+            IMMUTABLE_CACHE_CONTROL
+        );
+        let response = RestApi::route(&get("/transactions/1", &[])).unwrap();
+        assert_eq!(response.status_code, 404);
+    }
+
+    #[test]
+    fn test_should_parse_range() {
+        assert_eq!(RestApi::parse_range("bytes=0-4", 10), Some((0, 5)));
+        assert_eq!(RestApi::parse_range("bytes=5-", 10), Some((5, 10)));
+        assert_eq!(RestApi::parse_range("bytes=-3", 10), Some((7, 10)));
+        assert_eq!(RestApi::parse_range("bytes=0-100", 10), Some((0, 10)));
+        assert_eq!(RestApi::parse_range("bytes=10-", 10), None);
+        assert_eq!(RestApi::parse_range("items=0-4", 10), None);
+    }
+}
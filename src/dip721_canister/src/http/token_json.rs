@@ -0,0 +1,263 @@
+//! # Token JSON
+//!
+//! ERC-721 style token metadata, as expected by marketplaces and aggregators
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use dip721_rs::{GenericValue, TokenMetadata};
+use num_traits::ToPrimitive as _;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::app::{App, TOKEN_ASSET_PROPERTY};
+use crate::did::{Royalty, TokenJsonMapping};
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TokenJson {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation_url: Option<String>,
+    pub attributes: Vec<TokenAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub royalty: Option<Royalty>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TokenAttribute {
+    pub trait_type: String,
+    pub value: Value,
+}
+
+impl TokenJson {
+    /// Render the token metadata JSON according to the provided mapping.
+    ///
+    /// When no image is mapped, the token asset is used if any.
+    pub fn new(
+        token: &TokenMetadata,
+        mapping: &TokenJsonMapping,
+        collection: Option<&str>,
+        royalty: Option<Royalty>,
+    ) -> Self {
+        let property = |key: &Option<String>| {
+            key.as_ref().and_then(|key| {
+                token
+                    .properties
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, value)| Self::to_text(value))
+            })
+        };
+
+        let name = property(&mapping.name).unwrap_or_else(|| match collection {
+            Some(collection) => format!("{collection} #{}", token.token_identifier),
+            None => format!("#{}", token.token_identifier),
+        });
+        let image = property(&mapping.image).or_else(|| {
+            token
+                .properties
+                .iter()
+                .find_map(|(key, value)| match value {
+                    GenericValue::Nat64Content(asset_id) if key == TOKEN_ASSET_PROPERTY => {
+                        Some(App::canister_url(&format!("/assets/{asset_id}")))
+                    }
+                    _ => None,
+                })
+        });
+
+        let mapped_keys = [
+            &mapping.name,
+            &mapping.description,
+            &mapping.image,
+            &mapping.external_url,
+            &mapping.animation_url,
+        ];
+        let attributes = token
+            .properties
+            .iter()
+            .filter(|(key, _)| {
+                key != TOKEN_ASSET_PROPERTY
+                    && !mapped_keys.iter().any(|k| k.as_ref() == Some(key))
+                    && !mapping.excluded_attributes.contains(key)
+            })
+            .map(|(key, value)| TokenAttribute {
+                trait_type: key.clone(),
+                value: Self::to_json(value),
+            })
+            .collect();
+
+        Self {
+            name,
+            description: property(&mapping.description),
+            image,
+            external_url: property(&mapping.external_url),
+            animation_url: property(&mapping.animation_url),
+            attributes,
+            royalty,
+        }
+    }
+
+    /// Convert a property value to JSON.
+    ///
+    /// Big integers which don't fit in 64 bits are rendered as strings and blobs as base64.
+    pub fn to_json(value: &GenericValue) -> Value {
+        match value {
+            GenericValue::BoolContent(value) => Value::Bool(*value),
+            GenericValue::TextContent(value) => Value::String(value.clone()),
+            GenericValue::BlobContent(value) => Value::String(BASE64.encode(value)),
+            GenericValue::Principal(value) => Value::String(value.to_text()),
+            GenericValue::Nat8Content(value) => Value::from(*value),
+            GenericValue::Nat16Content(value) => Value::from(*value),
+            GenericValue::Nat32Content(value) => Value::from(*value),
+            GenericValue::Nat64Content(value) => Value::from(*value),
+            GenericValue::NatContent(value) => match value.0.to_u64() {
+                Some(value) => Value::from(value),
+                None => Value::String(value.0.to_string()),
+            },
+            GenericValue::Int8Content(value) => Value::from(*value),
+            GenericValue::Int16Content(value) => Value::from(*value),
+            GenericValue::Int32Content(value) => Value::from(*value),
+            GenericValue::Int64Content(value) => Value::from(*value),
+            GenericValue::IntContent(value) => match value.0.to_i64() {
+                Some(value) => Value::from(value),
+                None => Value::String(value.0.to_string()),
+            },
+            GenericValue::FloatContent(value) => {
+                Number::from_f64(*value).map_or(Value::Null, Value::Number)
+            }
+            GenericValue::NestedContent(values) => Value::Object(
+                values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::to_json(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+
+    fn to_text(value: &GenericValue) -> String {
+        match Self::to_json(value) {
+            Value::String(value) => value,
+            value => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::{Int, Nat, Principal};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::with_mock_token;
+
+    #[test]
+    fn test_should_convert_generic_value_to_json() {
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::BoolContent(true)),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::BlobContent(b"hello".to_vec())),
+            Value::String("aGVsbG8=".to_string())
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::Principal(Principal::management_canister())),
+            Value::String("aaaaa-aa".to_string())
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::Int8Content(-3)),
+            Value::from(-3)
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::NatContent(Nat::from(5_u64))),
+            Value::from(5)
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::NatContent(Nat::from(u128::MAX))),
+            Value::String(u128::MAX.to_string())
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::IntContent(Int::from(-5))),
+            Value::from(-5)
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::FloatContent(f64::NAN)),
+            Value::Null
+        );
+        assert_eq!(
+            TokenJson::to_json(&GenericValue::NestedContent(vec![(
+                "key".to_string(),
+                GenericValue::Nat8Content(1)
+            )])),
+            serde_json::json!({ "key": 1 })
+        );
+    }
+
+    #[test]
+    fn test_should_render_token_json() {
+        let token = with_mock_token(1, |token| {
+            token.properties = vec![
+                (
+                    "title".to_string(),
+                    GenericValue::TextContent("Sword".to_string()),
+                ),
+                (
+                    "description".to_string(),
+                    GenericValue::TextContent("A sharp sword".to_string()),
+                ),
+                (
+                    TOKEN_ASSET_PROPERTY.to_string(),
+                    GenericValue::Nat64Content(3),
+                ),
+                ("damage".to_string(), GenericValue::Nat32Content(10)),
+                ("secret".to_string(), GenericValue::BoolContent(true)),
+            ];
+        });
+        let mapping = TokenJsonMapping {
+            name: Some("title".to_string()),
+            excluded_attributes: vec!["secret".to_string()],
+            ..Default::default()
+        };
+
+        let json = TokenJson::new(&token, &mapping, Some("Items"), None);
+        assert_eq!(json.name, "Sword");
+        assert_eq!(json.description.as_deref(), Some("A sharp sword"));
+        assert_eq!(json.image, Some(App::canister_url("/assets/3")));
+        assert_eq!(
+            json.attributes,
+            vec![TokenAttribute {
+                trait_type: "damage".to_string(),
+                value: Value::from(10)
+            }]
+        );
+
+        let royalty = Royalty {
+            basis_points: 500,
+            recipients: vec![crate::did::RoyaltyRecipient {
+                recipient: Principal::anonymous(),
+                share: 10_000,
+            }],
+        };
+        let json = TokenJson::new(
+            &with_mock_token(2, |_| {}),
+            &mapping,
+            Some("Items"),
+            Some(royalty),
+        );
+        assert_eq!(json.name, "Items #2");
+        assert!(json.image.is_none());
+        assert_eq!(
+            serde_json::to_value(&json).unwrap()["royalty"],
+            serde_json::json!({
+                "basis_points": 500,
+                "recipients": [{ "recipient": "2vxsx-fae", "share": 10_000 }]
+            })
+        );
+    }
+}
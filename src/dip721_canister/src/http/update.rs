@@ -0,0 +1,306 @@
+use candid::{Nat, Principal};
+use dip721_rs::{GenericValue, NftError};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::app::App;
+use crate::did::{HttpRequest, HttpResponse};
+
+/// Body of a signed request.
+///
+/// `payload` is the JSON text which has been signed, `public_key` is the DER encoded key of the signer
+/// and both `public_key` and `signature` are hex encoded.
+#[derive(Deserialize)]
+struct SignedRequest {
+    payload: String,
+    public_key: String,
+    signature: String,
+}
+
+/// Signed payload, preventing the operation to be replayed or executed by another route
+#[derive(Deserialize)]
+struct SignedPayload<T> {
+    canister_id: Principal,
+    /// Route the payload was signed for, such as `mint`
+    method: String,
+    nonce: u64,
+    /// Expiration timestamp in nanoseconds
+    expires_at: u64,
+    #[serde(flatten)]
+    operation: T,
+}
+
+#[derive(Deserialize)]
+struct TransferOperation {
+    to: Principal,
+    token_identifier: Nat,
+}
+
+#[derive(Deserialize)]
+struct MintOperation {
+    to: Principal,
+    token_identifier: Nat,
+    #[serde(default)]
+    properties: Vec<(String, GenericValue)>,
+}
+
+/// Routes served by the `http_request_update` call, which can alter the canister state
+pub struct UpdateApi;
+
+impl UpdateApi {
+    /// Routes the request by its URL path; returns `None` if no route matches the path
+    pub fn route(req: &HttpRequest) -> Option<HttpResponse> {
+        let response = match req.path().trim_matches('/') {
+            "transfer" => Self::transfer(req),
+            "mint" => Self::mint(req),
+            _ => return None,
+        };
+
+        Some(response)
+    }
+
+    /// Transfers a token owned by the signer
+    fn transfer(req: &HttpRequest) -> HttpResponse {
+        Self::signed(req, "transfer", |signer, operation: TransferOperation| {
+            App::signed_transfer(signer, operation.to, operation.token_identifier)
+        })
+    }
+
+    /// Mints a token; the signer must be an HTTP minter
+    fn mint(req: &HttpRequest) -> HttpResponse {
+        Self::signed(req, "mint", |signer, operation: MintOperation| {
+            App::signed_mint(
+                signer,
+                operation.to,
+                operation.token_identifier,
+                operation.properties,
+            )
+        })
+    }
+
+    /// Verifies the signed request, signed for `method`, and executes `f` with the signer and the signed operation.
+    ///
+    /// Responds with the id of the transaction registered by `f`.
+    fn signed<T, F>(req: &HttpRequest, method: &str, f: F) -> HttpResponse
+    where
+        T: DeserializeOwned,
+        F: FnOnce(Principal, T) -> Result<Nat, NftError>,
+    {
+        let request = match serde_json::from_slice::<SignedRequest>(&req.body) {
+            Ok(request) => request,
+            Err(err) => return HttpResponse::bad_request(format!("invalid request: {err}")),
+        };
+        let (public_key, signature) = match (
+            hex::decode(&request.public_key),
+            hex::decode(&request.signature),
+        ) {
+            (Ok(public_key), Ok(signature)) => (public_key, signature),
+            _ => return HttpResponse::bad_request("invalid hex encoding".to_string()),
+        };
+        let payload = match serde_json::from_str::<SignedPayload<T>>(&request.payload) {
+            Ok(payload) => payload,
+            Err(err) => return HttpResponse::bad_request(format!("invalid payload: {err}")),
+        };
+        if payload.method != method {
+            return HttpResponse::bad_request(format!("payload not signed for {method}"));
+        }
+
+        let result = App::execute_signed_payload(
+            &public_key,
+            request.payload.as_bytes(),
+            &signature,
+            payload.canister_id,
+            payload.nonce,
+            payload.expires_at,
+            |signer| f(signer, payload.operation),
+        );
+
+        match result {
+            Ok(tx_id) => HttpResponse::ok(serde_json::json!({ "tx_id": tx_id.0.to_string() })),
+            Err(err) => HttpResponse::bad_request(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use dip721_rs::{Dip721 as _, SupportedInterface};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::app::signature::test_utils::{ed25519_key, ed25519_sign};
+    use crate::did::{CanisterInitData, DEFAULT_COLLECTION_ID};
+
+    fn init_canister() {
+        App::init(CanisterInitData {
+            custodians: vec![crate::utils::caller()],
+            supported_interfaces: vec![SupportedInterface::Mint],
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            logo: None,
+            provenance_hash: None,
+        });
+    }
+
+    fn signed_request(url: &str, seed: u8, payload: Value) -> HttpRequest {
+        let (key, public_key) = ed25519_key(seed);
+        let payload = payload.to_string();
+        let body = json!({
+            "payload": payload,
+            "public_key": hex::encode(&public_key),
+            "signature": hex::encode(ed25519_sign(&key, payload.as_bytes())),
+        });
+        let mut req = HttpRequest::new(body.to_string().as_bytes());
+        req.url = url.to_string();
+
+        req
+    }
+
+    fn signer(seed: u8) -> Principal {
+        Principal::self_authenticating(ed25519_key(seed).1)
+    }
+
+    fn expires_at() -> u64 {
+        crate::utils::time() + 60_000_000_000
+    }
+
+    #[test]
+    fn test_should_mint_with_allowlisted_key() {
+        init_canister();
+        let payload = json!({
+            "canister_id": crate::utils::id().to_text(),
+            "method": "mint",
+            "nonce": 1,
+            "expires_at": expires_at(),
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1,
+            "properties": [["name", { "TextContent": "Sword" }]]
+        });
+
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload.clone())).unwrap();
+        assert_eq!(response.status_code, 400);
+
+        App::set_http_minters(vec![signer(1)]);
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload.clone())).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            App::dip721_owner_of(1_u64.into()).unwrap(),
+            Some(crate::utils::caller())
+        );
+
+        // replay
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload)).unwrap();
+        assert_eq!(response.status_code, 400);
+    }
+
+    #[test]
+    fn test_should_transfer_signed_by_owner() {
+        init_canister();
+        crate::app::storage::TokensStorage::mint(
+            DEFAULT_COLLECTION_ID,
+            signer(2),
+            1_u64.into(),
+            vec![],
+        )
+        .unwrap();
+        let payload = json!({
+            "canister_id": crate::utils::id().to_text(),
+            "method": "transfer",
+            "nonce": 1,
+            "expires_at": expires_at(),
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1
+        });
+
+        // not the owner
+        let response = UpdateApi::route(&signed_request("/transfer", 3, payload.clone())).unwrap();
+        assert_eq!(response.status_code, 400);
+
+        let response = UpdateApi::route(&signed_request("/transfer", 2, payload)).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&response.body).unwrap(),
+            json!({ "tx_id": "1" })
+        );
+        assert_eq!(
+            App::dip721_owner_of(1_u64.into()).unwrap(),
+            Some(crate::utils::caller())
+        );
+    }
+
+    #[test]
+    fn test_should_reject_invalid_signed_requests() {
+        init_canister();
+        App::set_http_minters(vec![signer(1)]);
+        let payload = json!({
+            "canister_id": crate::utils::id().to_text(),
+            "method": "mint",
+            "nonce": 1,
+            "expires_at": 0,
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1
+        });
+        // expired
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload)).unwrap();
+        assert_eq!(response.status_code, 400);
+
+        // another canister
+        let payload = json!({
+            "canister_id": Principal::management_canister().to_text(),
+            "method": "mint",
+            "nonce": 1,
+            "expires_at": expires_at(),
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1
+        });
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload)).unwrap();
+        assert_eq!(response.status_code, 400);
+
+        // tampered payload
+        let mut req = signed_request(
+            "/mint",
+            1,
+            json!({
+                "canister_id": crate::utils::id().to_text(),
+                "method": "mint",
+                "nonce": 1,
+                "expires_at": expires_at(),
+                "to": crate::utils::caller().to_text(),
+                "token_identifier": 1
+            }),
+        );
+        let mut body = serde_json::from_slice::<Value>(&req.body).unwrap();
+        body["payload"] = Value::String(body["payload"].as_str().unwrap().replace(":1}", ":2}"));
+        req.body = serde_bytes::ByteBuf::from(body.to_string().into_bytes());
+        let response = UpdateApi::route(&req).unwrap();
+        assert_eq!(response.status_code, 400);
+        assert!(App::dip721_owner_of(2_u64.into()).is_err());
+
+        // signed for another route
+        let payload = json!({
+            "canister_id": crate::utils::id().to_text(),
+            "method": "transfer",
+            "nonce": 2,
+            "expires_at": expires_at(),
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1
+        });
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload)).unwrap();
+        assert_eq!(response.status_code, 400);
+        assert!(App::dip721_owner_of(1_u64.into()).is_err());
+        // without method
+        let payload = json!({
+            "canister_id": crate::utils::id().to_text(),
+            "nonce": 3,
+            "expires_at": expires_at(),
+            "to": crate::utils::caller().to_text(),
+            "token_identifier": 1
+        });
+        let response = UpdateApi::route(&signed_request("/mint", 1, payload)).unwrap();
+        assert_eq!(response.status_code, 400);
+
+        assert!(UpdateApi::route(&signed_request("/unknown", 1, json!({}))).is_none());
+    }
+}
@@ -1,9 +1,11 @@
 use candid::{Nat, Principal};
+use dip721_rs::GenericValue;
 use ic_cdk::api;
 #[cfg(target_family = "wasm")]
 use ic_cdk_macros::inspect_message;
 
 use crate::app::Inspect;
+use crate::did::DEFAULT_COLLECTION_ID;
 use crate::utils::caller;
 
 /// NOTE: inspect is disabled for non-wasm targets because without it we are getting a weird compilation error
@@ -22,21 +24,69 @@ fn inspect_message_impl() {
     let check_result = match method.as_str() {
         "dip721_mint"
         | "dip721_set_logo"
+        | "set_logo"
         | "dip721_set_name"
         | "dip721_set_symbol"
         | "dip721_set_custodians"
-        | "set_token_property" => Inspect::inspect_is_custodian(caller()),
+        | "set_token_property"
+        | "set_token_json_mapping"
+        | "set_http_minters"
+        | "set_voucher_signers"
+        | "set_provenance_hash"
+        | "reveal"
+        | "set_allowed_origins"
+        | "set_collection_royalty"
+        | "set_token_royalty"
+        | "set_marketplace_settings"
+        | "set_mint_sale"
+        | "set_allowlist_phase"
+        | "set_random_pool"
+        | "create_edition_template"
+        | "mint_edition"
+        | "account_mint"
+        | "create_collection"
+        | "create_asset"
+        | "upload_asset_chunk"
+        | "finalize_asset"
+        | "delete_asset"
+        | "set_token_asset" => Inspect::inspect_is_custodian(caller()),
+        "collection_mint" => {
+            let (collection_id, ..) =
+                api::call::arg_data::<(u64, Principal, Nat, Vec<(String, GenericValue)>)>();
+            Inspect::inspect_is_collection_custodian(caller(), collection_id)
+        }
+        "collection_burn" => {
+            let (collection_id, token_identifier) = api::call::arg_data::<(u64, Nat)>();
+            Inspect::inspect_is_owner_or_operator(caller(), collection_id, &token_identifier)
+                .is_ok()
+        }
+        "collection_transfer" => {
+            let (collection_id, _, token_identifier) =
+                api::call::arg_data::<(u64, Principal, Nat)>();
+            Inspect::inspect_is_owner_or_operator(caller(), collection_id, &token_identifier)
+                .is_ok()
+        }
         "dip721_burn" => {
             let token_identifier = api::call::arg_data::<(Nat,)>().0;
-            Inspect::inspect_is_owner_or_operator(caller(), &token_identifier).is_ok()
+            Inspect::inspect_is_owner_or_operator(
+                caller(),
+                DEFAULT_COLLECTION_ID,
+                &token_identifier,
+            )
+            .is_ok()
         }
         "dip721_approve" => {
             let (_operator, token_identifier) = api::call::arg_data::<(Principal, Nat)>();
-            Inspect::inspect_is_owner(caller(), &token_identifier)
+            Inspect::inspect_is_owner(caller(), DEFAULT_COLLECTION_ID, &token_identifier)
         }
         "dip721_transfer_from" => {
             let (_, _, token_identifier) = api::call::arg_data::<(Principal, Principal, Nat)>();
-            Inspect::inspect_is_owner_or_operator(caller(), &token_identifier).is_ok()
+            Inspect::inspect_is_owner_or_operator(
+                caller(),
+                DEFAULT_COLLECTION_ID,
+                &token_identifier,
+            )
+            .is_ok()
         }
         _ => true,
     };
@@ -1,7 +1,14 @@
 //! # DIP721 canister
 
 use candid::{candid_method, Nat, Principal};
-use did::{CanisterInitData, HttpRequest, HttpResponse};
+use did::{
+    Account, AllowlistPhase, AssetMetadata, Auction, AuctionKind, CanisterInitData, Collection,
+    CollectionId, CreateCollectionArgs, EditionTemplate, ExtAccountIdentifier, ExtCommonError,
+    ExtMetadata, ExtResult, ExtTokenIdentifier, ExtTokenIndex, ExtTokensResult, ExtTransferError,
+    ExtTransferRequest, HttpRequest, HttpResponse, Listing, MarketplaceSettings, MintSale,
+    MintVoucher, Offer, ProposeSwapArgs, Provenance, RandomPool, Royalty, RoyaltyPayment, Swap,
+    SwapPayment, TokenEdition, TokenJsonMapping, TransferBatch,
+};
 use dip721_rs::Dip721 as _;
 use ic_cdk_macros::{init, post_upgrade, query, update};
 
@@ -31,7 +38,787 @@ pub fn set_token_property(
     key: String,
     value: dip721_rs::GenericValue,
 ) -> Result<(), dip721_rs::NftError> {
-    App::set_token_property(token_identifier, key, value)
+    App::metered(
+        "set_token_property",
+        App::set_token_property(token_identifier, key, value),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_token_json_mapping() -> TokenJsonMapping {
+    App::get_token_json_mapping()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_token_json_mapping(mapping: TokenJsonMapping) {
+    App::record_call("set_token_json_mapping");
+    App::set_token_json_mapping(mapping)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_http_base_url() -> String {
+    App::get_http_base_url()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_http_base_url(url: Option<String>) -> Result<(), dip721_rs::NftError> {
+    App::metered("set_http_base_url", App::set_http_base_url(url))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_allowed_origins() -> Vec<String> {
+    App::get_allowed_origins()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_allowed_origins(origins: Vec<String>) {
+    App::record_call("set_allowed_origins");
+    App::set_allowed_origins(origins)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_http_minters() -> Vec<Principal> {
+    App::get_http_minters()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_http_minters(minters: Vec<Principal>) {
+    App::record_call("set_http_minters");
+    App::set_http_minters(minters)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_voucher_signers() -> Vec<Principal> {
+    App::get_voucher_signers()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_voucher_signers(signers: Vec<Principal>) {
+    App::record_call("set_voucher_signers");
+    App::set_voucher_signers(signers)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn redeem_voucher(
+    voucher: MintVoucher,
+    properties: Vec<(String, dip721_rs::GenericValue)>,
+    public_key: serde_bytes::ByteBuf,
+    signature: serde_bytes::ByteBuf,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "redeem_voucher",
+        App::redeem_voucher(voucher, properties, public_key, signature),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_random_pool() -> Option<RandomPool> {
+    App::get_random_pool()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_random_pool(size: u64) -> Result<(), dip721_rs::NftError> {
+    App::metered("set_random_pool", App::set_random_pool(size))
+}
+
+// Rental
+
+#[update]
+#[candid_method(update)]
+pub fn set_user(
+    token_identifier: dip721_rs::TokenIdentifier,
+    user: Principal,
+    expires_at: u64,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "set_user",
+        App::set_user(token_identifier, user, expires_at),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn user_of(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Option<Principal>, dip721_rs::NftError> {
+    App::user_of(token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn user_expires(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<u64, dip721_rs::NftError> {
+    App::user_expires(token_identifier)
+}
+
+// Editions
+
+#[query]
+#[candid_method(query)]
+pub fn get_edition_templates() -> Vec<(u64, EditionTemplate)> {
+    App::get_edition_templates()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn create_edition_template(
+    properties: Vec<(String, dip721_rs::GenericValue)>,
+    max_editions: Option<u64>,
+) -> Result<u64, dip721_rs::NftError> {
+    App::metered(
+        "create_edition_template",
+        App::create_edition_template(properties, max_editions),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn mint_edition(
+    to: Principal,
+    template_id: u64,
+    properties: Vec<(String, dip721_rs::GenericValue)>,
+) -> Result<dip721_rs::TokenIdentifier, dip721_rs::NftError> {
+    App::metered(
+        "mint_edition",
+        App::mint_edition(to, template_id, properties),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_edition_count(template_id: u64) -> Result<u64, dip721_rs::NftError> {
+    App::get_edition_count(template_id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_token_edition(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Option<TokenEdition>, dip721_rs::NftError> {
+    App::get_token_edition(token_identifier)
+}
+
+// Collections
+
+#[query]
+#[candid_method(query)]
+pub fn get_collection(collection_id: CollectionId) -> Option<Collection> {
+    App::get_collection(collection_id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_collections() -> Vec<(CollectionId, Collection)> {
+    App::get_collections()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn create_collection(args: CreateCollectionArgs) -> Result<CollectionId, dip721_rs::NftError> {
+    App::metered("create_collection", App::create_collection(args))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn collection_mint(
+    collection_id: CollectionId,
+    to: Principal,
+    token_identifier: dip721_rs::TokenIdentifier,
+    properties: Vec<(String, dip721_rs::GenericValue)>,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "collection_mint",
+        App::collection_mint(collection_id, to, token_identifier, properties),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn collection_transfer(
+    collection_id: CollectionId,
+    to: Principal,
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "collection_transfer",
+        App::collection_transfer(collection_id, to, token_identifier),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn collection_burn(
+    collection_id: CollectionId,
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "collection_burn",
+        App::collection_burn(collection_id, token_identifier),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn collection_token_metadata(
+    collection_id: CollectionId,
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<dip721_rs::TokenMetadata, dip721_rs::NftError> {
+    App::collection_token_metadata(collection_id, token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn collection_owner_of(
+    collection_id: CollectionId,
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Option<Principal>, dip721_rs::NftError> {
+    App::collection_owner_of(collection_id, token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn collection_owner_token_identifiers(
+    collection_id: CollectionId,
+    owner: Principal,
+) -> Result<Vec<dip721_rs::TokenIdentifier>, dip721_rs::NftError> {
+    App::collection_owner_token_identifiers(collection_id, owner)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn collection_balance_of(
+    collection_id: CollectionId,
+    owner: Principal,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::collection_balance_of(collection_id, owner)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn collection_total_supply(collection_id: CollectionId) -> Result<Nat, dip721_rs::NftError> {
+    App::collection_total_supply(collection_id)
+}
+
+// Composables
+
+#[update]
+#[candid_method(update)]
+pub fn attach_child(
+    parent: dip721_rs::TokenIdentifier,
+    child: dip721_rs::TokenIdentifier,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("attach_child", App::attach_child(parent, child))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn detach_child(
+    parent: dip721_rs::TokenIdentifier,
+    child: dip721_rs::TokenIdentifier,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("detach_child", App::detach_child(parent, child))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_token_children(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Vec<dip721_rs::TokenIdentifier>, dip721_rs::NftError> {
+    App::get_token_children(token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn root_owner_of(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Option<Principal>, dip721_rs::NftError> {
+    App::root_owner_of(token_identifier)
+}
+
+// Batch transfers
+
+#[update]
+#[candid_method(update)]
+pub fn transfer_batch(
+    transfers: Vec<(Principal, Principal, dip721_rs::TokenIdentifier)>,
+) -> Result<TransferBatch, dip721_rs::NftError> {
+    App::metered("transfer_batch", App::transfer_batch(transfers))
+}
+
+// Accounts
+
+#[query]
+#[candid_method(query)]
+pub fn account_balance_of(account: Account) -> Result<Nat, dip721_rs::NftError> {
+    App::account_balance_of(account)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn account_token_identifiers(
+    account: Account,
+) -> Result<Vec<dip721_rs::TokenIdentifier>, dip721_rs::NftError> {
+    App::account_token_identifiers(account)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn account_of(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Option<Account>, dip721_rs::NftError> {
+    App::account_of(token_identifier)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn account_transfer(
+    from_subaccount: Option<serde_bytes::ByteBuf>,
+    to: Account,
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "account_transfer",
+        App::account_transfer(from_subaccount, to, token_identifier),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn account_mint(
+    to: Account,
+    token_identifier: dip721_rs::TokenIdentifier,
+    properties: Vec<(String, dip721_rs::GenericValue)>,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered(
+        "account_mint",
+        App::account_mint(to, token_identifier, properties),
+    )
+}
+
+// EXT compatibility
+
+#[query]
+#[candid_method(query)]
+pub fn ext_token_identifier_of(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<ExtTokenIdentifier, dip721_rs::NftError> {
+    App::ext_token_identifier_of(token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn bearer(token: ExtTokenIdentifier) -> ExtResult<ExtAccountIdentifier, ExtCommonError> {
+    App::ext_bearer(token)
+}
+
+#[query(name = "getRegistry")]
+#[candid_method(query, rename = "getRegistry")]
+pub fn get_registry() -> Vec<(ExtTokenIndex, ExtAccountIdentifier)> {
+    App::ext_get_registry()
+}
+
+#[query(name = "getTokens")]
+#[candid_method(query, rename = "getTokens")]
+pub fn get_tokens() -> Vec<(ExtTokenIndex, ExtMetadata)> {
+    App::ext_get_tokens()
+}
+
+#[query]
+#[candid_method(query)]
+pub fn tokens_ext(account_identifier: ExtAccountIdentifier) -> ExtTokensResult {
+    App::ext_tokens(account_identifier)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn transfer(request: ExtTransferRequest) -> ExtResult<Nat, ExtTransferError> {
+    App::record_call("transfer");
+    App::ext_transfer(request)
+}
+
+// Delayed reveal
+
+#[query]
+#[candid_method(query)]
+pub fn get_provenance() -> Option<Provenance> {
+    App::get_provenance()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_provenance_hash(hash: serde_bytes::ByteBuf) -> Result<(), dip721_rs::NftError> {
+    App::metered("set_provenance_hash", App::set_provenance_hash(hash))
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn reveal(
+    metadata: Vec<Vec<(String, dip721_rs::GenericValue)>>,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered("reveal", App::reveal(metadata).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn verify_reveal() -> Result<bool, dip721_rs::NftError> {
+    App::verify_reveal()
+}
+
+// Royalties
+
+#[query]
+#[candid_method(query)]
+pub fn get_collection_royalty() -> Option<Royalty> {
+    App::get_collection_royalty()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_collection_royalty(royalty: Option<Royalty>) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "set_collection_royalty",
+        App::set_collection_royalty(royalty),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_token_royalty(token_identifier: dip721_rs::TokenIdentifier) -> Option<Royalty> {
+    App::get_token_royalty(token_identifier)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_token_royalty(
+    token_identifier: dip721_rs::TokenIdentifier,
+    royalty: Option<Royalty>,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "set_token_royalty",
+        App::set_token_royalty(token_identifier, royalty),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn royalty_info(
+    token_identifier: dip721_rs::TokenIdentifier,
+    sale_price: Nat,
+) -> Result<Vec<RoyaltyPayment>, dip721_rs::NftError> {
+    App::royalty_info(token_identifier, sale_price)
+}
+
+// Marketplace
+
+#[query]
+#[candid_method(query)]
+pub fn get_marketplace_settings() -> MarketplaceSettings {
+    App::get_marketplace_settings()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_marketplace_settings(settings: MarketplaceSettings) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "set_marketplace_settings",
+        App::set_marketplace_settings(settings),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_listing(token_identifier: dip721_rs::TokenIdentifier) -> Option<Listing> {
+    App::get_listing(token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_listings() -> Vec<(dip721_rs::TokenIdentifier, Listing)> {
+    App::get_listings()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn list_token(
+    token_identifier: dip721_rs::TokenIdentifier,
+    price: Nat,
+    ledger: Principal,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "list_token",
+        App::list_token(token_identifier, price, ledger),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn cancel_listing(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered("cancel_listing", App::cancel_listing(token_identifier))
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn buy(token_identifier: dip721_rs::TokenIdentifier) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("buy", App::buy(token_identifier).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_auction(token_identifier: dip721_rs::TokenIdentifier) -> Option<Auction> {
+    App::get_auction(token_identifier)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_auctions() -> Vec<(dip721_rs::TokenIdentifier, Auction)> {
+    App::get_auctions()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn create_auction(
+    token_identifier: dip721_rs::TokenIdentifier,
+    ledger: Principal,
+    kind: AuctionKind,
+    ends_at: u64,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "create_auction",
+        App::create_auction(token_identifier, ledger, kind, ends_at),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn cancel_auction(
+    token_identifier: dip721_rs::TokenIdentifier,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered("cancel_auction", App::cancel_auction(token_identifier))
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn bid(
+    token_identifier: dip721_rs::TokenIdentifier,
+    amount: Nat,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered("bid", App::bid(token_identifier, amount).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_offer(id: u64) -> Option<Offer> {
+    App::get_offer(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_token_offers(token_identifier: dip721_rs::TokenIdentifier) -> Vec<(u64, Offer)> {
+    App::get_token_offers(token_identifier)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn make_offer(
+    token_identifier: dip721_rs::TokenIdentifier,
+    amount: Nat,
+    expires_at: u64,
+    ledger: Principal,
+) -> Result<u64, dip721_rs::NftError> {
+    App::metered(
+        "make_offer",
+        App::make_offer(token_identifier, amount, expires_at, ledger).await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn withdraw_offer(id: u64) -> Result<(), dip721_rs::NftError> {
+    App::metered("withdraw_offer", App::withdraw_offer(id).await)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn accept_offer(id: u64) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("accept_offer", App::accept_offer(id).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_swap(id: u64) -> Option<Swap> {
+    App::get_swap(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_swaps(principal: Principal) -> Vec<(u64, Swap)> {
+    App::get_swaps(principal)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn propose_swap(args: ProposeSwapArgs) -> Result<u64, dip721_rs::NftError> {
+    App::metered("propose_swap", App::propose_swap(args))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn cancel_swap(id: u64) -> Result<(), dip721_rs::NftError> {
+    App::metered("cancel_swap", App::cancel_swap(id))
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn accept_swap(
+    id: u64,
+    offered: Vec<dip721_rs::TokenIdentifier>,
+    payment: Option<SwapPayment>,
+) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("accept_swap", App::accept_swap(id, offered, payment).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_proceeds(ledger: Principal, owner: Principal) -> Nat {
+    App::get_proceeds(ledger, owner)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn withdraw_proceeds(ledger: Principal) -> Result<Nat, dip721_rs::NftError> {
+    App::metered("withdraw_proceeds", App::withdraw_proceeds(ledger).await)
+}
+
+// Public sale
+
+#[query]
+#[candid_method(query)]
+pub fn get_mint_sale() -> Option<MintSale> {
+    App::get_mint_sale()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_mint_sale(sale: Option<MintSale>) -> Result<(), dip721_rs::NftError> {
+    App::metered("set_mint_sale", App::set_mint_sale(sale))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_sale_minted() -> u64 {
+    App::get_sale_minted()
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_sale_minted_by(owner: Principal) -> u64 {
+    App::get_sale_minted_by(owner)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn public_mint(
+    quantity: u64,
+) -> Result<Vec<dip721_rs::TokenIdentifier>, dip721_rs::NftError> {
+    App::metered("public_mint", App::public_mint(quantity).await)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_allowlist_phases() -> Vec<(u32, AllowlistPhase)> {
+    App::get_allowlist_phases()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_allowlist_phase(
+    id: u32,
+    phase: Option<AllowlistPhase>,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered("set_allowlist_phase", App::set_allowlist_phase(id, phase))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_allowlist_minted(phase_id: u32, owner: Principal) -> u64 {
+    App::get_allowlist_minted(phase_id, owner)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn allowlist_mint(
+    phase_id: u32,
+    quantity: u64,
+    max_quantity: u64,
+    proof: Vec<serde_bytes::ByteBuf>,
+) -> Result<Vec<dip721_rs::TokenIdentifier>, dip721_rs::NftError> {
+    App::metered(
+        "allowlist_mint",
+        App::allowlist_mint(phase_id, quantity, max_quantity, proof).await,
+    )
+}
+
+// Assets
+
+#[update]
+#[candid_method(update)]
+pub fn create_asset(
+    content_type: String,
+    sha256: serde_bytes::ByteBuf,
+) -> Result<u64, dip721_rs::NftError> {
+    App::metered("create_asset", App::create_asset(content_type, sha256))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn upload_asset_chunk(id: u64, chunk: serde_bytes::ByteBuf) -> Result<(), dip721_rs::NftError> {
+    App::metered("upload_asset_chunk", App::upload_asset_chunk(id, chunk))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn finalize_asset(id: u64) -> Result<AssetMetadata, dip721_rs::NftError> {
+    App::metered("finalize_asset", App::finalize_asset(id))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn delete_asset(id: u64) -> Result<(), dip721_rs::NftError> {
+    App::metered("delete_asset", App::delete_asset(id))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_asset(id: u64) -> Option<AssetMetadata> {
+    App::get_asset(id)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn set_token_asset(
+    token_identifier: dip721_rs::TokenIdentifier,
+    asset_id: u64,
+) -> Result<(), dip721_rs::NftError> {
+    App::metered(
+        "set_token_asset",
+        App::set_token_asset(token_identifier, asset_id),
+    )
 }
 
 // DIP721
@@ -57,9 +844,17 @@ pub fn dip721_logo() -> Option<String> {
 #[update]
 #[candid_method(update)]
 pub fn dip721_set_logo(logo: String) {
+    App::record_call("dip721_set_logo");
     App::dip721_set_logo(logo)
 }
 
+#[update]
+#[candid_method(update)]
+pub fn set_logo(content_type: String, data: serde_bytes::ByteBuf) {
+    App::record_call("set_logo");
+    App::set_logo(content_type, data)
+}
+
 #[query]
 #[candid_method(query)]
 pub fn dip721_name() -> Option<String> {
@@ -69,6 +864,7 @@ pub fn dip721_name() -> Option<String> {
 #[update]
 #[candid_method(update)]
 pub fn dip721_set_name(name: String) {
+    App::record_call("dip721_set_name");
     App::dip721_set_name(name)
 }
 
@@ -81,6 +877,7 @@ pub fn dip721_symbol() -> Option<String> {
 #[update]
 #[candid_method(update)]
 pub fn dip721_set_symbol(symbol: String) {
+    App::record_call("dip721_set_symbol");
     App::dip721_set_symbol(symbol)
 }
 
@@ -93,6 +890,7 @@ pub fn dip721_custodians() -> Vec<Principal> {
 #[update]
 #[candid_method(update)]
 pub fn dip721_set_custodians(custodians: Vec<Principal>) {
+    App::record_call("dip721_set_custodians");
     App::dip721_set_custodians(custodians)
 }
 
@@ -188,7 +986,10 @@ pub fn dip721_approve(
     spender: Principal,
     token_identifier: dip721_rs::TokenIdentifier,
 ) -> Result<dip721_rs::TokenIdentifier, dip721_rs::NftError> {
-    App::dip721_approve(spender, token_identifier)
+    App::metered(
+        "dip721_approve",
+        App::dip721_approve(spender, token_identifier),
+    )
 }
 
 #[update]
@@ -197,7 +998,10 @@ pub fn dip721_set_approval_for_all(
     operator: Principal,
     approved: bool,
 ) -> Result<dip721_rs::TokenIdentifier, dip721_rs::NftError> {
-    App::dip721_set_approval_for_all(operator, approved)
+    App::metered(
+        "dip721_set_approval_for_all",
+        App::dip721_set_approval_for_all(operator, approved),
+    )
 }
 
 #[update]
@@ -206,7 +1010,10 @@ pub fn dip721_is_approved_for_all(
     owner: Principal,
     operator: Principal,
 ) -> Result<bool, dip721_rs::NftError> {
-    App::dip721_is_approved_for_all(owner, operator)
+    App::metered(
+        "dip721_is_approved_for_all",
+        App::dip721_is_approved_for_all(owner, operator),
+    )
 }
 
 #[update]
@@ -215,7 +1022,10 @@ pub async fn dip721_transfer(
     to: Principal,
     token_identifier: dip721_rs::TokenIdentifier,
 ) -> Result<Nat, dip721_rs::NftError> {
-    App::dip721_transfer(to, token_identifier).await
+    App::metered(
+        "dip721_transfer",
+        App::dip721_transfer(to, token_identifier).await,
+    )
 }
 
 #[update]
@@ -225,7 +1035,10 @@ pub async fn dip721_transfer_from(
     to: Principal,
     token_identifier: dip721_rs::TokenIdentifier,
 ) -> Result<Nat, dip721_rs::NftError> {
-    App::dip721_transfer_from(from, to, token_identifier).await
+    App::metered(
+        "dip721_transfer_from",
+        App::dip721_transfer_from(from, to, token_identifier).await,
+    )
 }
 
 #[update]
@@ -235,7 +1048,10 @@ pub fn dip721_mint(
     token_identifier: dip721_rs::TokenIdentifier,
     properties: Vec<(String, dip721_rs::GenericValue)>,
 ) -> Result<Nat, dip721_rs::NftError> {
-    App::dip721_mint(to, token_identifier, properties)
+    App::metered(
+        "dip721_mint",
+        App::dip721_mint(to, token_identifier, properties),
+    )
 }
 
 #[update]
@@ -243,7 +1059,7 @@ pub fn dip721_mint(
 pub fn dip721_burn(
     token_identifier: dip721_rs::TokenIdentifier,
 ) -> Result<dip721_rs::TokenIdentifier, dip721_rs::NftError> {
-    App::dip721_burn(token_identifier)
+    App::metered("dip721_burn", App::dip721_burn(token_identifier))
 }
 
 #[query]
@@ -265,6 +1081,27 @@ pub async fn http_request(req: HttpRequest) -> HttpResponse {
     http::HttpApi::handle_http_request(req).await
 }
 
+#[update]
+#[candid_method(update)]
+pub async fn http_request_update(req: HttpRequest) -> HttpResponse {
+    App::record_call("http_request_update");
+    http::HttpApi::handle_http_request_update(req).await
+}
+
+/// NOTE: exported for wasm targets only, as `inspect_message`
+#[cfg(target_family = "wasm")]
+#[export_name = "canister_global_timer"]
+extern "C" fn canister_global_timer() {
+    global_timer_impl()
+}
+
+/// Settles the ended auctions and refunds the expired offers
+#[allow(dead_code)]
+fn global_timer_impl() {
+    ic_cdk::setup();
+    ic_cdk::spawn(App::on_global_timer());
+}
+
 #[allow(dead_code)]
 fn main() {
     // The line below generates did types and service definition from the
@@ -274,14 +1111,11 @@ fn main() {
     std::print!("{}", __export_service());
 }
 
-/// GetRandom fixup to allow getrandom compilation.
-/// A getrandom implementation that always fails
+/// Custom `getrandom` implementation, which the crate requires to compile for the IC, backed by the canister CSPRNG.
 ///
-/// This is a workaround for the fact that the `getrandom` crate does not compile
-/// for the `wasm32-unknown-ic` target. This is a dummy implementation that always
-/// fails with `Error::UNSUPPORTED`.
-pub fn getrandom_always_fail(_buf: &mut [u8]) -> Result<(), getrandom::Error> {
-    Err(getrandom::Error::UNSUPPORTED)
+/// It fails with `Error::UNSUPPORTED` until the CSPRNG is seeded from `raw_rand`.
+pub fn getrandom_from_csprng(buf: &mut [u8]) -> Result<(), getrandom::Error> {
+    App::random_fill(buf).map_err(|_| getrandom::Error::UNSUPPORTED)
 }
 
-getrandom::register_custom_getrandom!(getrandom_always_fail);
+getrandom::register_custom_getrandom!(getrandom_from_csprng);
@@ -1,11 +1,17 @@
 use std::borrow::Cow;
 
-use candid::{Nat, Principal};
-use dip721_rs::SupportedInterface;
+use candid::{Decode, Encode, Nat, Principal};
+use dip721_rs::{GenericValue, SupportedInterface};
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::Storable;
 use num_bigint::BigUint;
 
+use crate::did::{
+    AllowlistPhase, AssetMetadata, Auction, Collection, EditionTemplate, Listing, Logo,
+    MarketplaceSettings, MintSale, Offer, Provenance, RandomPool, Royalty, Swap, TokenEdition,
+    TokenJsonMapping, TokenUser,
+};
+
 /// Storable principal. May be used as a stable storage key.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct StorablePrincipal(pub Principal);
@@ -108,6 +114,242 @@ impl Storable for StorableNat {
     };
 }
 
+/// Storable list of strings
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct StorableStrings(pub Vec<String>);
+
+impl Storable for StorableStrings {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self.0).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self(Decode!(&bytes, Vec<String>).unwrap())
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Storable token properties
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct StorableProperties(pub Vec<(String, GenericValue)>);
+
+impl Storable for StorableProperties {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self.0).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self(Decode!(&bytes, Vec<(String, GenericValue)>).unwrap())
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AssetMetadata {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Logo {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Royalty {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Auction {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Listing {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for MarketplaceSettings {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AllowlistPhase {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Provenance {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RandomPool {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Collection {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for EditionTemplate {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TokenEdition {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TokenUser {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for MintSale {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Offer {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Swap {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TokenJsonMapping {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -157,4 +399,21 @@ mod tests {
             assert_eq!(source, decoded);
         }
     }
+
+    #[test]
+    fn test_storable_asset_metadata_roundtrip() {
+        let source = AssetMetadata {
+            id: 1,
+            content_type: "image/png".to_string(),
+            sha256: serde_bytes::ByteBuf::from(vec![1; 32]),
+            size: 1024,
+            chunks: 2,
+            finalized: true,
+            created_at: 5,
+            created_by: Principal::management_canister(),
+        };
+        let bytes = source.to_bytes();
+        let decoded = AssetMetadata::from_bytes(bytes);
+        assert_eq!(source, decoded);
+    }
 }
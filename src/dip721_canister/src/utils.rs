@@ -37,3 +37,54 @@ pub fn caller() -> Principal {
         ic_cdk::caller()
     }
 }
+
+/// Returns the canister id
+pub fn id() -> Principal {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Principal::from_text("bkyz2-fmaaa-aaaaa-qaaaq-cai").unwrap()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ic_cdk::id()
+    }
+}
+
+/// Returns the heap memory size in bytes
+pub fn heap_size() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        (core::arch::wasm32::memory_size(0) * 65536) as u64
+    }
+}
+
+/// Schedule the canister global timer at `timestamp` nanoseconds, replacing the previous one; 0 cancels it
+pub fn set_global_timer(timestamp: u64) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = timestamp;
+    }
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        ic0::global_timer_set(timestamp as i64);
+    }
+}
+
+/// Returns 32 random bytes from the management canister `raw_rand`
+pub async fn raw_rand() -> Result<Vec<u8>, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Ok(vec![7; 32])
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ic_cdk::api::management_canister::main::raw_rand()
+            .await
+            .map(|(bytes,)| bytes)
+            .map_err(|(code, message)| format!("raw_rand rejected: {code:?}: {message}"))
+    }
+}